@@ -1,8 +1,9 @@
 use sp_core::{Pair, Public, sr25519};
 use node_subtensor_runtime::{
 	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig,
-	SudoConfig, SystemConfig, WASM_BINARY, Signature
+	SudoConfig, SystemConfig, SubtensorModuleConfig, WASM_BINARY, Signature
 };
+use node_subtensor_runtime::pallet_subtensor::GenesisNeuron;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_finality_grandpa::AuthorityId as GrandpaId;
 use sp_runtime::traits::{Verify, IdentifyAccount};
@@ -57,6 +58,16 @@ pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
 	)
 }
 
+/// Generate a neuron registered at genesis, with the hotkey from seed and the coldkey from seed//stash.
+pub fn genesis_neuron_from_seed(seed: &str, stake: u64) -> GenesisNeuron<AccountId> {
+	GenesisNeuron {
+		hotkey: get_account_id_from_seed::<sr25519::Public>(seed),
+		coldkey: get_account_id_from_seed::<sr25519::Public>(&format!("{}//stash", seed)),
+		stake: stake,
+		..Default::default()
+	}
+}
+
 pub fn development_config() -> Result<ChainSpec, String> {
 	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
 
@@ -82,6 +93,11 @@ pub fn development_config() -> Result<ChainSpec, String> {
 				get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
 				get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
 			],
+			// Neurons registered at genesis
+			vec![
+				genesis_neuron_from_seed("Alice", 1_000_000_000),
+				genesis_neuron_from_seed("Bob", 1_000_000_000),
+			],
 			true,
 		),
 		// Bootnodes
@@ -119,6 +135,7 @@ pub fn nobunaga_stagenet_config() -> Result<ChainSpec, String> {
             vec![
                 AccountId::from_ss58check("5HoqMpw98Ys7MiF7vxN28a8KGyU1jbReTQJrddwXY9QpRpm1").unwrap(), // Sudo
             ],
+            vec![],
             true,
         ),
         vec![
@@ -152,6 +169,7 @@ pub fn akatsuki_testnet_config() -> Result<ChainSpec, String> {
             vec![
                 AccountId::from_ss58check("5FyVxvrk91qqcqc6w8wNLahukhkbAdkr4qYxn1iDMQcn2SMr").unwrap(), // Sudo
             ],
+            vec![],
             true,
         ),
         vec![
@@ -199,6 +217,13 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 				get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
 				get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 			],
+			// Neurons registered at genesis
+			vec![
+				genesis_neuron_from_seed("Alice", 1_000_000_000),
+				genesis_neuron_from_seed("Bob", 1_000_000_000),
+				genesis_neuron_from_seed("Charlie", 1_000_000_000),
+				genesis_neuron_from_seed("Dave", 1_000_000_000),
+			],
 			true,
 		),
 		// Bootnodes
@@ -235,6 +260,7 @@ pub fn nakamoto_mainnet_config() -> Result<ChainSpec, String> {
 			vec![
 				AccountId::from_ss58check("5GLKGJdjCwBYgtim7F4eZCxDC3bMe9VvhCnpG2k9ihdyPX9p").unwrap(), // Sudo
 			],
+			vec![],
 			true,
 		),
 		vec![
@@ -254,6 +280,7 @@ fn testnet_genesis(
 	initial_authorities: Vec<(AuraId, GrandpaId)>,
 	root_key: AccountId,
 	endowed_accounts: Vec<AccountId>,
+	initial_neurons: Vec<GenesisNeuron<AccountId>>,
 	_enable_println: bool,
 ) -> GenesisConfig {
 	GenesisConfig {
@@ -276,6 +303,11 @@ fn testnet_genesis(
 			// Assign network admin rights.
 			key: root_key,
 		},
+		subtensor_module: SubtensorModuleConfig {
+			// Populate the metagraph, hyperparameters keep their runtime defaults.
+			neurons: initial_neurons,
+			..Default::default()
+		},
 	}
 }
 
@@ -285,6 +317,7 @@ fn network_genesis(
 	initial_authorities: Vec<(AuraId, GrandpaId)>,
 	root_key: AccountId,
 	_endowed_accounts: Vec<AccountId>,
+	initial_neurons: Vec<GenesisNeuron<AccountId>>,
 	_enable_println: bool,
 ) -> GenesisConfig {
 	GenesisConfig {
@@ -592,5 +625,10 @@ fn network_genesis(
 			// Assign network admin rights.
 			key: root_key,
 		},
+		subtensor_module: SubtensorModuleConfig {
+			// Populate the metagraph, hyperparameters keep their runtime defaults.
+			neurons: initial_neurons,
+			..Default::default()
+		},
 	}
 }
@@ -1,5 +1,8 @@
 use structopt::StructOpt;
 use sc_cli::RunCmd;
+use crate::export_metagraph::ExportMetagraphCmd;
+use crate::fork_state::ForkStateCmd;
+use crate::replay_step::ReplayStepCmd;
 
 #[derive(Debug, StructOpt)]
 pub struct Cli {
@@ -26,6 +29,15 @@ pub enum Subcommand {
 	/// Export the state of a given block into a chain spec.
 	ExportState(sc_cli::ExportStateCmd),
 
+	/// Export the subtensor metagraph of a given block as json or csv.
+	ExportMetagraph(ExportMetagraphCmd),
+
+	/// Fork the state of a raw chain spec into a single-node dev chain spec.
+	ForkState(ForkStateCmd),
+
+	/// Replay the mechanism step on the state of a block and diff it against the chain.
+	ReplayStep(ReplayStepCmd),
+
 	/// Import blocks.
 	ImportBlocks(sc_cli::ImportBlocksCmd),
 
@@ -99,6 +99,21 @@ pub fn run() -> sc_cli::Result<()> {
 				Ok((cmd.run(client, config.chain_spec), task_manager))
 			})
 		},
+		Some(Subcommand::ExportMetagraph(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let PartialComponents { client, ..} = service::new_partial(&config)?;
+				cmd.run(client)
+			})
+		},
+		Some(Subcommand::ForkState(cmd)) => cmd.run(),
+		Some(Subcommand::ReplayStep(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let PartialComponents { client, ..} = service::new_partial(&config)?;
+				cmd.run(client)
+			})
+		},
 		Some(Subcommand::ImportBlocks(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
 			runner.async_run(|config| {
@@ -0,0 +1,294 @@
+//! The `export-metagraph` subcommand, dumps the subtensor metagraph at a block of the
+//! local database as json or csv, without a running node or an RPC client.
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{self, Write};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use codec::Decode;
+use node_subtensor_runtime::AccountId;
+use node_subtensor_runtime::pallet_subtensor::NeuronMetadata;
+use sc_cli::{BlockNumberOrHash, CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use sc_client_api::{StorageProvider, UsageProvider, backend::Backend};
+use serde::Serialize;
+use sp_blockchain::HeaderBackend;
+use sp_core::crypto::Ss58Codec;
+use sp_core::hashing::twox_128;
+use sp_core::storage::StorageKey;
+use sp_runtime::generic::BlockId;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, SaturatedConversion};
+use structopt::StructOpt;
+
+/// Name the subtensor pallet is registered under in construct_runtime, the storage prefix of its items.
+pub(crate) const SUBTENSOR_PREFIX: &str = "SubtensorModule";
+
+/// The global u64 hyperparameters of the pallet, by storage item name.
+const HYPERPARAMETERS: &[&str] = &[
+	"Rho", "Kappa", "IncentivePruningDenominator", "StakePruningDenominator", "ValidatorEpochLen",
+	"ValidatorEpochsPerReset", "ValidatorBatchSize", "ValidatorSequenceLength", "MaxAllowedUids",
+	"MinAllowedWeights", "MaxAllowedMaxMinRatio", "ImmunityPeriod", "BlocksPerStep", "StepChunkSize",
+	"BondsMovingAverage", "Difficulty", "ActivityCutoff", "AdjustmentInterval",
+	"TargetRegistrationsPerInterval", "MaxRegistrationsPerBlock", "FoundationDistribution",
+	"ColdkeySwapDelay", "UnbondingPeriod", "FeelessQuota", "FeelessQuotaInterval",
+];
+
+/// Output format of the export.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+	/// A single json document.
+	Json,
+	/// One row per neuron.
+	Csv,
+}
+
+impl FromStr for ExportFormat {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"json" => Ok(ExportFormat::Json),
+			"csv" => Ok(ExportFormat::Csv),
+			other => Err(format!("Unknown format {}, expected json or csv", other)),
+		}
+	}
+}
+
+/// The `export-metagraph` command.
+#[derive(Debug, StructOpt)]
+pub struct ExportMetagraphCmd {
+	/// Block hash or number to export the metagraph at, the best block if unset.
+	#[structopt(long = "at", value_name = "HASH or NUMBER")]
+	pub at: Option<BlockNumberOrHash>,
+
+	/// Output format, json or csv.
+	#[structopt(long = "format", value_name = "FORMAT", possible_values = &["json", "csv"], default_value = "json")]
+	pub format: ExportFormat,
+
+	/// File to write to, stdout if unset.
+	#[structopt(long = "output", short = "o", parse(from_os_str))]
+	pub output: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+/// A neuron as exported, keys are ss58 encoded and the ip is rendered as an address.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NeuronRow {
+	uid: u32,
+	hotkey: String,
+	coldkey: String,
+	version: u32,
+	ip: String,
+	port: u16,
+	ip_type: u8,
+	modality: u8,
+	active: u32,
+	last_update: u64,
+	block_at_registration: u64,
+	priority: u64,
+	stake: u64,
+	rank: u64,
+	trust: u64,
+	consensus: u64,
+	incentive: u64,
+	dividends: u64,
+	emission: u64,
+}
+
+/// The metagraph at a block. Hyperparameters without a stored value are None, the
+/// runtime falls back to its Initial* config value for those.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MetagraphSnapshot {
+	block: u64,
+	hash: String,
+	total_stake: Option<u64>,
+	total_issuance: Option<u64>,
+	hyperparameters: BTreeMap<&'static str, Option<u64>>,
+	neurons: Vec<NeuronRow>,
+}
+
+impl ExportMetagraphCmd {
+	/// Run the export-metagraph command.
+	pub fn run<B, BA, C>(&self, client: Arc<C>) -> sc_cli::Result<()>
+	where
+		B: BlockT,
+		BA: Backend<B>,
+		C: UsageProvider<B> + StorageProvider<B, BA> + HeaderBackend<B>,
+		<<B::Header as HeaderT>::Number as FromStr>::Err: Debug,
+	{
+		let hash = match &self.at {
+			Some(at) => client.expect_block_hash_from_id(&at.parse::<B>()?)?,
+			None => client.usage_info().chain.best_hash,
+		};
+		let block = client.expect_block_number_from_id(&BlockId::Hash(hash))?;
+		let snapshot = read_snapshot(&*client, hash, block.saturated_into::<u64>())?;
+
+		let mut out: Box<dyn Write> = match &self.output {
+			Some(path) => Box::new(File::create(path)?),
+			None => Box::new(io::stdout()),
+		};
+		match self.format {
+			ExportFormat::Json => {
+				serde_json::to_writer_pretty(&mut out, &snapshot)
+					.map_err(|e| sc_cli::Error::Input(format!("Unable to write json: {}", e)))?;
+				writeln!(out)?;
+			},
+			ExportFormat::Csv => write_csv(&mut *out, &snapshot)?,
+		}
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ExportMetagraphCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+/// Returns the storage key prefix of a storage item of the pallet registered as pallet in construct_runtime.
+pub(crate) fn storage_prefix(pallet: &str, item: &str) -> Vec<u8> {
+	let mut key = twox_128(pallet.as_bytes()).to_vec();
+	key.extend_from_slice(&twox_128(item.as_bytes()));
+	key
+}
+
+/// Reads and decodes the value under key, None if nothing is stored.
+pub(crate) fn read_value<B, BA, C, V>(client: &C, hash: B::Hash, key: Vec<u8>, item: &str) -> sc_cli::Result<Option<V>>
+where
+	B: BlockT,
+	BA: Backend<B>,
+	C: StorageProvider<B, BA>,
+	V: Decode,
+{
+	match client.storage(&BlockId::Hash(hash), &StorageKey(key))? {
+		Some(data) => V::decode(&mut &data.0[..])
+			.map(Some)
+			.map_err(|e| sc_cli::Error::Input(format!("Unable to decode {}: {}", item, e))),
+		None => Ok(None),
+	}
+}
+
+/// Reads the metagraph at the block with the given hash and number.
+fn read_snapshot<B, BA, C>(client: &C, hash: B::Hash, block: u64) -> sc_cli::Result<MetagraphSnapshot>
+where
+	B: BlockT,
+	BA: Backend<B>,
+	C: StorageProvider<B, BA>,
+{
+	let mut hyperparameters = BTreeMap::new();
+	for item in HYPERPARAMETERS {
+		hyperparameters.insert(*item, read_value::<B, BA, C, u64>(client, hash, storage_prefix(SUBTENSOR_PREFIX, item), item)?);
+	}
+
+	// Hotkeys is the hotkey -> uid index, keyed by blake2_128 ++ the hotkey itself.
+	let mut hotkeys: BTreeMap<u32, AccountId> = BTreeMap::new();
+	let hotkeys_prefix = storage_prefix(SUBTENSOR_PREFIX, "Hotkeys");
+	for (key, data) in client.storage_pairs(&BlockId::Hash(hash), &StorageKey(hotkeys_prefix.clone()))? {
+		let hotkey = AccountId::decode(&mut &key.0[hotkeys_prefix.len() + 16..])
+			.map_err(|e| sc_cli::Error::Input(format!("Unable to decode Hotkeys key: {}", e)))?;
+		let uid = u32::decode(&mut &data.0[..])
+			.map_err(|e| sc_cli::Error::Input(format!("Unable to decode Hotkeys: {}", e)))?;
+		hotkeys.insert(uid, hotkey);
+	}
+
+	let mut neurons = Vec::new();
+	for (_, data) in client.storage_pairs(&BlockId::Hash(hash), &StorageKey(storage_prefix(SUBTENSOR_PREFIX, "Neurons")))? {
+		let neuron = NeuronMetadata::<AccountId>::decode(&mut &data.0[..])
+			.map_err(|e| sc_cli::Error::Input(format!("Unable to decode Neurons: {}", e)))?;
+
+		// BlockAtRegistration is keyed by the uid as is.
+		let mut key = storage_prefix(SUBTENSOR_PREFIX, "BlockAtRegistration");
+		key.extend_from_slice(&neuron.uid.to_le_bytes());
+		let block_at_registration = read_value::<B, BA, C, u64>(client, hash, key, "BlockAtRegistration")?.unwrap_or(0);
+
+		let hotkey = hotkeys.remove(&neuron.uid).unwrap_or_else(|| neuron.hotkey.clone());
+		neurons.push(NeuronRow {
+			uid: neuron.uid,
+			hotkey: hotkey.to_ss58check(),
+			coldkey: neuron.coldkey.to_ss58check(),
+			version: neuron.version,
+			ip: ip_to_string(neuron.ip, neuron.ip_type),
+			port: neuron.port,
+			ip_type: neuron.ip_type,
+			modality: neuron.modality,
+			active: neuron.active,
+			last_update: neuron.last_update,
+			block_at_registration,
+			priority: neuron.priority,
+			stake: neuron.stake,
+			rank: neuron.rank,
+			trust: neuron.trust,
+			consensus: neuron.consensus,
+			incentive: neuron.incentive,
+			dividends: neuron.dividends,
+			emission: neuron.emission,
+		});
+	}
+	neurons.sort_by_key(|neuron| neuron.uid);
+
+	Ok(MetagraphSnapshot {
+		block,
+		hash: format!("{:?}", hash),
+		total_stake: read_value::<B, BA, C, u64>(client, hash, storage_prefix(SUBTENSOR_PREFIX, "TotalStake"), "TotalStake")?,
+		total_issuance: read_value::<B, BA, C, u64>(client, hash, storage_prefix(SUBTENSOR_PREFIX, "TotalIssuance"), "TotalIssuance")?,
+		hyperparameters,
+		neurons,
+	})
+}
+
+/// Renders a u128 encoded ip address, unserved neurons have ip type 0 and render as 0.
+fn ip_to_string(ip: u128, ip_type: u8) -> String {
+	match ip_type {
+		4 => Ipv4Addr::from(ip as u32).to_string(),
+		6 => Ipv6Addr::from(ip).to_string(),
+		_ => ip.to_string(),
+	}
+}
+
+/// Writes one row per neuron, preceded by the block, totals and hyperparameters as `# name,value` comment lines.
+fn write_csv(out: &mut dyn Write, snapshot: &MetagraphSnapshot) -> io::Result<()> {
+	let or_empty = |value: &Option<u64>| value.map(|v| v.to_string()).unwrap_or_default();
+	writeln!(out, "# block,{}", snapshot.block)?;
+	writeln!(out, "# hash,{}", snapshot.hash)?;
+	writeln!(out, "# TotalStake,{}", or_empty(&snapshot.total_stake))?;
+	writeln!(out, "# TotalIssuance,{}", or_empty(&snapshot.total_issuance))?;
+	for (name, value) in snapshot.hyperparameters.iter() {
+		writeln!(out, "# {},{}", name, or_empty(value))?;
+	}
+	writeln!(out, "uid,hotkey,coldkey,version,ip,port,ip_type,modality,active,last_update,block_at_registration,priority,stake,rank,trust,consensus,incentive,dividends,emission")?;
+	for n in snapshot.neurons.iter() {
+		writeln!(
+			out,
+			"{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+			n.uid, n.hotkey, n.coldkey, n.version, n.ip, n.port, n.ip_type, n.modality, n.active,
+			n.last_update, n.block_at_registration, n.priority, n.stake, n.rank, n.trust,
+			n.consensus, n.incentive, n.dividends, n.emission,
+		)?;
+	}
+	Ok(())
+}
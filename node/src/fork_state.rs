@@ -0,0 +1,291 @@
+//! The `fork-state` subcommand, turns the raw chain spec of a live chain, as written by
+//! `export-state` or shipped as `nakamotoSpecRaw.json`, into a single-node dev chain spec
+//! that boots with the real metagraph.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use codec::{Decode, DecodeAll, Encode};
+use node_subtensor_runtime::{AccountId, Balance, Index, MinimumDifficulty};
+use node_subtensor_runtime::pallet_subtensor::{NeuronMetadata, PendingStepState, Releases};
+use node_subtensor_runtime::pallet_subtensor::migrations::OldNeuronMetadata;
+use serde_json::Value;
+use sp_core::bytes::{from_hex, to_hex};
+use sp_core::hashing::{blake2_128, twox_128};
+use sp_core::sr25519;
+use sp_finality_grandpa::{GRANDPA_AUTHORITIES_KEY, VersionedAuthorityList};
+use structopt::StructOpt;
+
+use crate::chain_spec::{authority_keys_from_seed, get_account_id_from_seed};
+use crate::export_metagraph::{storage_prefix, SUBTENSOR_PREFIX};
+
+/// Raw genesis storage, hex decoded.
+type RawStorage = BTreeMap<Vec<u8>, Vec<u8>>;
+
+/// The `fork-state` command.
+#[derive(Debug, StructOpt)]
+pub struct ForkStateCmd {
+	/// Raw chain spec holding the state to fork.
+	#[structopt(parse(from_os_str))]
+	pub input: PathBuf,
+
+	/// File to write the forked raw chain spec to, stdout if unset.
+	#[structopt(long = "output", short = "o", parse(from_os_str))]
+	pub output: Option<PathBuf>,
+
+	/// Dev account that becomes the Aura and Grandpa authority and the sudo key.
+	#[structopt(long = "authority", default_value = "Alice")]
+	pub authority: String,
+
+	/// Balance in rao credited to the authority account so it can pay for transactions.
+	#[structopt(long = "endowment", default_value = "1000000000000")]
+	pub endowment: Balance,
+}
+
+impl ForkStateCmd {
+	/// Run the fork-state command.
+	pub fn run(&self) -> sc_cli::Result<()> {
+		let mut spec: Value = serde_json::from_slice(&fs::read(&self.input)?)
+			.map_err(|e| format!("Unable to parse chain spec: {}", e))?;
+		let mut storage = read_raw_storage(&spec)?;
+
+		self.fork(&mut storage)?;
+
+		// A fresh name, id and network, so the fork neither shares a database
+		// directory nor tries to peer with the forked chain.
+		let name = spec["name"].as_str().unwrap_or("Subtensor").to_string();
+		let id = spec["id"].as_str().unwrap_or("subtensor").to_string();
+		spec["name"] = Value::from(format!("{} Fork", name));
+		spec["id"] = Value::from(format!("{}_fork", id));
+		spec["chainType"] = Value::from("Development");
+		spec["bootNodes"] = Value::Array(vec![]);
+		spec["telemetryEndpoints"] = Value::Null;
+		spec["genesis"]["raw"]["top"] = Value::Object(
+			storage.iter().map(|(key, value)| (to_hex(key, false), Value::from(to_hex(value, false)))).collect()
+		);
+
+		let json = serde_json::to_string_pretty(&spec)
+			.map_err(|e| format!("Unable to write chain spec: {}", e))?;
+		match &self.output {
+			Some(path) => fs::write(path, json)?,
+			None => writeln!(io::stdout(), "{}", json)?,
+		}
+		Ok(())
+	}
+
+	/// Rewrites the forked state to boot as a dev chain run by the authority account.
+	fn fork(&self, storage: &mut RawStorage) -> sc_cli::Result<()> {
+		let (aura, grandpa) = authority_keys_from_seed(&self.authority);
+		let authority = get_account_id_from_seed::<sr25519::Public>(&self.authority);
+
+		// --- Consensus is reset to a single authority, clearing slots, set ids and pending changes.
+		remove_prefix(storage, &twox_128(b"Aura"));
+		remove_prefix(storage, &twox_128(b"Grandpa"));
+		storage.insert(storage_prefix("Aura", "Authorities"), vec![aura].encode());
+		storage.insert(GRANDPA_AUTHORITIES_KEY.to_vec(), VersionedAuthorityList::from(vec![(grandpa, 1)]).encode());
+		storage.insert(storage_prefix("Sudo", "Key"), authority.encode());
+
+		// --- Registration is made as cheap as the runtime allows.
+		storage.insert(storage_prefix(SUBTENSOR_PREFIX, "Difficulty"), MinimumDifficulty::get().encode());
+
+		// --- The fork restarts at block 0, block numbers stored by subtensor are moved back by the
+		// fork block so the pallet does not see them in the future. Neurons are rewritten in the
+		// layout of the stored release, which the :code of the spec reads.
+		let fork_block: u64 = read_value::<u32>(storage, storage_prefix("System", "Number"), "System Number")?.unwrap_or(0) as u64;
+		let release: Releases = read_value::<Releases>(storage, storage_prefix(SUBTENSOR_PREFIX, "StorageVersion"), "StorageVersion")?
+			.unwrap_or_default();
+		match release {
+			Releases::V1_0_0 | Releases::V2_0_0 => rebase_item(storage, "Neurons", |neuron: &mut OldNeuronMetadata<AccountId>| {
+				neuron.last_update = neuron.last_update.saturating_sub(fork_block);
+			})?,
+			Releases::V3_0_0 => rebase_item(storage, "Neurons", |neuron: &mut NeuronMetadata<AccountId>| {
+				neuron.last_update = neuron.last_update.saturating_sub(fork_block);
+			})?,
+		}
+		for item in ["BlockAtRegistration", "LastTxBlock", "LastDifficultyAdjustmentBlock", "LastMechansimStepBlock"].iter() {
+			rebase_item(storage, item, |block: &mut u64| *block = block.saturating_sub(fork_block))?;
+		}
+		rebase_item(storage, "Unbonding", |chunks: &mut Vec<(u64, u64)>| {
+			for (_, unlock_block) in chunks.iter_mut() {
+				*unlock_block = unlock_block.saturating_sub(fork_block);
+			}
+		})?;
+		rebase_item(storage, "ColdkeySwapAnnouncements", |(_, swap_block): &mut (AccountId, u64)| {
+			*swap_block = swap_block.saturating_sub(fork_block);
+		})?;
+		rebase_item(storage, "PendingStep", |state: &mut PendingStepState| state.block = state.block.saturating_sub(fork_block))?;
+
+		// --- Fund the authority, keeping both issuance counters in line.
+		self.endow(storage, &authority)
+	}
+
+	/// Credits the endowment to account, creating it if it does not exist.
+	fn endow(&self, storage: &mut RawStorage, account: &AccountId) -> sc_cli::Result<()> {
+		let mut key = storage_prefix("System", "Account");
+		key.extend_from_slice(&blake2_128(&account.encode()));
+		key.extend_from_slice(&account.encode());
+		let mut info = read_value::<frame_system::AccountInfo<Index, pallet_balances::AccountData<Balance>>>(storage, key.clone(), "System Account")?
+			.unwrap_or_default();
+		info.providers = info.providers.max(1);
+		info.data.free = info.data.free.saturating_add(self.endowment);
+		storage.insert(key, info.encode());
+
+		for (pallet, item) in [("Balances", "TotalIssuance"), (SUBTENSOR_PREFIX, "TotalIssuance")].iter() {
+			let key = storage_prefix(pallet, item);
+			let issuance: Balance = read_value::<Balance>(storage, key.clone(), item)?.unwrap_or(0);
+			storage.insert(key, issuance.saturating_add(self.endowment).encode());
+		}
+		Ok(())
+	}
+}
+
+/// Reads the hex encoded genesis.raw.top storage of a raw chain spec.
+fn read_raw_storage(spec: &Value) -> sc_cli::Result<RawStorage> {
+	let top = spec["genesis"]["raw"]["top"].as_object()
+		.ok_or("Not a raw chain spec, genesis.raw.top is missing")?;
+	let mut storage = RawStorage::new();
+	for (key, value) in top.iter() {
+		let value = value.as_str().ok_or_else(|| format!("Storage value of {} is not a string", key))?;
+		storage.insert(
+			from_hex(key).map_err(|e| format!("Invalid storage key {}: {}", key, e))?,
+			from_hex(value).map_err(|e| format!("Invalid storage value of {}: {}", key, e))?,
+		);
+	}
+	Ok(storage)
+}
+
+/// Reads and decodes the value under key, None if nothing is stored.
+fn read_value<V: Decode>(storage: &RawStorage, key: Vec<u8>, item: &str) -> sc_cli::Result<Option<V>> {
+	match storage.get(&key) {
+		Some(value) => V::decode(&mut &value[..])
+			.map(Some)
+			.map_err(|e| format!("Unable to decode {}: {}", item, e).into()),
+		None => Ok(None),
+	}
+}
+
+/// Decodes every value stored under the subtensor item, map or value, as V, applies rebase to it
+/// and writes it back. A value which does not decode as V in full fails the fork, rather than
+/// being written back truncated.
+fn rebase_item<V: Decode + Encode>(storage: &mut RawStorage, item: &str, rebase: impl Fn(&mut V)) -> sc_cli::Result<()> {
+	for (key, value) in prefixed_pairs(storage, &storage_prefix(SUBTENSOR_PREFIX, item)) {
+		let mut decoded = V::decode_all(&value[..])
+			.map_err(|e| format!("Unable to decode {}: {}", item, e))?;
+		rebase(&mut decoded);
+		storage.insert(key, decoded.encode());
+	}
+	Ok(())
+}
+
+/// Returns a copy of all pairs whose key starts with prefix.
+fn prefixed_pairs(storage: &RawStorage, prefix: &[u8]) -> Vec<(Vec<u8>, Vec<u8>)> {
+	storage.range(prefix.to_vec()..)
+		.take_while(|(key, _)| key.starts_with(prefix))
+		.map(|(key, value)| (key.clone(), value.clone()))
+		.collect()
+}
+
+/// Removes all pairs whose key starts with prefix.
+fn remove_prefix(storage: &mut RawStorage, prefix: &[u8]) {
+	for (key, _) in prefixed_pairs(storage, prefix) {
+		storage.remove(&key);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const FORK_BLOCK: u32 = 1_000;
+
+	fn fork_cmd() -> ForkStateCmd {
+		ForkStateCmd { input: PathBuf::new(), output: None, authority: "Alice".into(), endowment: 0 }
+	}
+
+	fn map_key(item: &str, key: &[u8]) -> Vec<u8> {
+		let mut full_key = storage_prefix(SUBTENSOR_PREFIX, item);
+		full_key.extend_from_slice(key);
+		full_key
+	}
+
+	fn blake2_key(item: &str, account: &AccountId) -> Vec<u8> {
+		let mut key = blake2_128(&account.encode()).to_vec();
+		key.extend_from_slice(&account.encode());
+		map_key(item, &key)
+	}
+
+	fn old_neuron(last_update: u64) -> OldNeuronMetadata<AccountId> {
+		OldNeuronMetadata {
+			version: 0, ip: 0, port: 0, ip_type: 4, uid: 0, modality: 0,
+			hotkey: AccountId::from([1u8; 32]), coldkey: AccountId::from([2u8; 32]),
+			active: 1, last_update, priority: 0, stake: 100, rank: 0, trust: 0, consensus: 0,
+			incentive: 0, dividends: 0, emission: 0, bonds: vec![(0, 5)], weights: vec![(0, u32::MAX)],
+		}
+	}
+
+	/// The raw state of a chain at FORK_BLOCK, in the V1 layout when release is None.
+	fn synthetic_storage(release: Option<Releases>) -> RawStorage {
+		let coldkey = AccountId::from([2u8; 32]);
+		let mut storage = RawStorage::new();
+		storage.insert(storage_prefix("System", "Number"), FORK_BLOCK.encode());
+		if let Some(release) = release {
+			storage.insert(storage_prefix(SUBTENSOR_PREFIX, "StorageVersion"), release.encode());
+		}
+		storage.insert(map_key("Neurons", &0u32.encode()), old_neuron(990).encode());
+		storage.insert(map_key("BlockAtRegistration", &0u32.encode()), 900u64.encode());
+		storage.insert(blake2_key("Unbonding", &coldkey), vec![(10u64, 1_100u64), (20u64, 8_000u64)].encode());
+		storage.insert(blake2_key("ColdkeySwapAnnouncements", &coldkey), (AccountId::from([3u8; 32]), 1_500u64).encode());
+		storage.insert(storage_prefix(SUBTENSOR_PREFIX, "PendingStep"), PendingStepState { n: 1, block: 995, ..Default::default() }.encode());
+		storage
+	}
+
+	fn read<V: Decode>(storage: &RawStorage, key: Vec<u8>) -> V {
+		V::decode_all(&storage[&key][..]).unwrap()
+	}
+
+	#[test]
+	fn fork_rebases_v1_state() {
+		let coldkey = AccountId::from([2u8; 32]);
+		let mut storage = synthetic_storage(None);
+		fork_cmd().fork(&mut storage).unwrap();
+
+		// The neuron keeps its V1 layout, weights and bonds included.
+		let neuron: OldNeuronMetadata<AccountId> = read(&storage, map_key("Neurons", &0u32.encode()));
+		assert_eq!(neuron.last_update, 0);
+		assert_eq!(neuron.bonds, vec![(0, 5)]);
+		assert_eq!(neuron.weights, vec![(0, u32::MAX)]);
+		assert_eq!(neuron.encode().len(), old_neuron(0).encode().len());
+
+		assert_eq!(read::<u64>(&storage, map_key("BlockAtRegistration", &0u32.encode())), 0);
+		assert_eq!(read::<Vec<(u64, u64)>>(&storage, blake2_key("Unbonding", &coldkey)), vec![(10, 100), (20, 7_000)]);
+		assert_eq!(read::<(AccountId, u64)>(&storage, blake2_key("ColdkeySwapAnnouncements", &coldkey)).1, 500);
+		assert_eq!(read::<PendingStepState>(&storage, storage_prefix(SUBTENSOR_PREFIX, "PendingStep")).block, 0);
+	}
+
+	#[test]
+	fn fork_rebases_v3_neurons() {
+		let mut storage = synthetic_storage(Some(Releases::V3_0_0));
+		let old = old_neuron(990);
+		let neuron = NeuronMetadata::<AccountId> {
+			version: old.version, ip: old.ip, port: old.port, ip_type: old.ip_type, uid: old.uid, modality: old.modality,
+			hotkey: old.hotkey, coldkey: old.coldkey, active: old.active, last_update: old.last_update,
+			priority: old.priority, stake: old.stake, rank: old.rank, trust: old.trust, consensus: old.consensus,
+			incentive: old.incentive, dividends: old.dividends, emission: old.emission,
+		};
+		storage.insert(map_key("Neurons", &0u32.encode()), neuron.encode());
+		fork_cmd().fork(&mut storage).unwrap();
+
+		let neuron: NeuronMetadata<AccountId> = read(&storage, map_key("Neurons", &0u32.encode()));
+		assert_eq!(neuron.last_update, 0);
+		assert_eq!(neuron.stake, 100);
+	}
+
+	#[test]
+	fn fork_fails_on_neurons_in_another_layout() {
+		// V1 neurons under a V3 storage version would be truncated if decoded as V3.
+		let mut storage = synthetic_storage(Some(Releases::V3_0_0));
+		assert!(fork_cmd().fork(&mut storage).is_err());
+	}
+}
@@ -6,6 +6,9 @@ mod chain_spec;
 mod service;
 mod cli;
 mod command;
+mod export_metagraph;
+mod fork_state;
+mod replay_step;
 mod rpc;
 
 fn main() -> sc_cli::Result<()> {
@@ -0,0 +1,337 @@
+//! The `replay-step` subcommand, reruns the mechanism step natively on the state of a block of
+//! the local database, without importing blocks, and diffs the result against what the chain
+//! wrote at the following step block. Rho, kappa and the bonds moving average can be overridden
+//! to try parameter changes on real data. State of blocks before the latest storage release is
+//! migrated before the replay.
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use codec::Decode;
+use node_subtensor_runtime::{AccountId, BlockNumber, Runtime, SubtensorModule, System};
+use node_subtensor_runtime::pallet_subtensor::{migrations, NeuronMetadata};
+use sc_cli::{BlockNumberOrHash, CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use sc_client_api::{StorageProvider, UsageProvider, backend::Backend};
+use serde::Serialize;
+use sp_blockchain::HeaderBackend;
+use sp_core::crypto::Ss58Codec;
+use sp_core::hashing::twox_128;
+use sp_core::storage::{Storage, StorageKey};
+use sp_runtime::generic::BlockId;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor, SaturatedConversion};
+use structopt::StructOpt;
+
+use crate::export_metagraph::{read_value, storage_prefix, ExportFormat, SUBTENSOR_PREFIX};
+
+/// The `replay-step` command.
+#[derive(Debug, StructOpt)]
+pub struct ReplayStepCmd {
+	/// Block hash or number whose state the next mechanism step is replayed on.
+	#[structopt(long = "at", value_name = "HASH or NUMBER")]
+	pub at: BlockNumberOrHash,
+
+	/// Rho to replay the step with instead of the stored one.
+	#[structopt(long = "rho")]
+	pub rho: Option<u64>,
+
+	/// Kappa to replay the step with instead of the stored one.
+	#[structopt(long = "kappa")]
+	pub kappa: Option<u64>,
+
+	/// Bonds moving average to replay the step with instead of the stored one.
+	#[structopt(long = "bonds-moving-average")]
+	pub bonds_moving_average: Option<u64>,
+
+	/// Output format, json or csv.
+	#[structopt(long = "format", value_name = "FORMAT", possible_values = &["json", "csv"], default_value = "json")]
+	pub format: ExportFormat,
+
+	/// File to write to, stdout if unset.
+	#[structopt(long = "output", short = "o", parse(from_os_str))]
+	pub output: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+/// The values the mechanism step writes into a neuron, or their differences.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StepValues<V> {
+	rank: V,
+	trust: V,
+	consensus: V,
+	incentive: V,
+	dividends: V,
+	emission: V,
+}
+
+impl StepValues<u64> {
+	/// Returns self - other, field by field.
+	fn diff(&self, other: &StepValues<u64>) -> StepValues<i128> {
+		StepValues {
+			rank: self.rank as i128 - other.rank as i128,
+			trust: self.trust as i128 - other.trust as i128,
+			consensus: self.consensus as i128 - other.consensus as i128,
+			incentive: self.incentive as i128 - other.incentive as i128,
+			dividends: self.dividends as i128 - other.dividends as i128,
+			emission: self.emission as i128 - other.emission as i128,
+		}
+	}
+}
+
+/// A neuron as replayed. Recorded and diff are None if the step was not found in the
+/// database or the uid was taken by another hotkey in between.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NeuronReplay {
+	uid: u32,
+	hotkey: String,
+	replayed: StepValues<u64>,
+	recorded: Option<StepValues<u64>>,
+	diff: Option<StepValues<i128>>,
+}
+
+/// The replayed step of a block.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StepReplay {
+	block: u64,
+	hash: String,
+	step_block: u64,
+	recorded_at: Option<u64>,
+	emission: u64,
+	rho: u64,
+	kappa: u64,
+	bonds_moving_average: u64,
+	neurons: Vec<NeuronReplay>,
+}
+
+impl ReplayStepCmd {
+	/// Run the replay-step command.
+	pub fn run<B, BA, C>(&self, client: Arc<C>) -> sc_cli::Result<()>
+	where
+		B: BlockT,
+		BA: Backend<B>,
+		C: UsageProvider<B> + StorageProvider<B, BA> + HeaderBackend<B>,
+		<<B::Header as HeaderT>::Number as FromStr>::Err: Debug,
+	{
+		let hash = client.expect_block_hash_from_id(&self.at.parse::<B>()?)?;
+		let block = client.expect_block_number_from_id(&BlockId::Hash(hash))?.saturated_into::<u64>();
+
+		// --- The step only reads subtensor storage, so the pallet's items are all the state it needs.
+		let mut top = BTreeMap::new();
+		let pallet_prefix = StorageKey(twox_128(SUBTENSOR_PREFIX.as_bytes()).to_vec());
+		for (key, data) in client.storage_pairs(&BlockId::Hash(hash), &pallet_prefix)? {
+			top.insert(key.0, data.0);
+		}
+		let mut ext = sp_io::TestExternalities::new(Storage { top, children_default: Default::default() });
+		let mut replay = ext.execute_with(|| self.replay(block))?;
+		replay.hash = format!("{:?}", hash);
+
+		if let Some((recorded_at, recorded_hash)) = find_step::<B, BA, C>(&*client, replay.step_block)? {
+			let mut recorded = read_step_values::<B, BA, C>(&*client, recorded_hash)?;
+			for neuron in replay.neurons.iter_mut() {
+				if let Some((hotkey, values)) = recorded.remove(&neuron.uid) {
+					if hotkey == neuron.hotkey {
+						neuron.diff = Some(neuron.replayed.diff(&values));
+						neuron.recorded = Some(values);
+					}
+				}
+			}
+			replay.recorded_at = Some(recorded_at);
+		}
+
+		let mut out: Box<dyn Write> = match &self.output {
+			Some(path) => Box::new(File::create(path)?),
+			None => Box::new(io::stdout()),
+		};
+		match self.format {
+			ExportFormat::Json => {
+				serde_json::to_writer_pretty(&mut out, &replay)
+					.map_err(|e| sc_cli::Error::Input(format!("Unable to write json: {}", e)))?;
+				writeln!(out)?;
+			},
+			ExportFormat::Csv => write_csv(&mut *out, &replay)?,
+		}
+		Ok(())
+	}
+
+	/// Computes the step the chain runs next on the state of block, within its externalities.
+	fn replay(&self, block: u64) -> sc_cli::Result<StepReplay> {
+		// --- State older than the latest release holds the weights and bonds inside the neurons,
+		// where the native pallet does not read them. The migrations move them first.
+		migrations::migrate::<Runtime>();
+		migrations::post_migrate_checks::<Runtime>()
+			.map_err(|e| format!("Unable to migrate the state of block {}: {}", block, e))?;
+		if SubtensorModule::has_pending_step() {
+			return Err(format!("A mechanism step is still running at block {}, replay a block before it started", block).into());
+		}
+		if let Some(rho) = self.rho {
+			SubtensorModule::set_rho(rho);
+		}
+		if let Some(kappa) = self.kappa {
+			SubtensorModule::set_kappa(kappa);
+		}
+		if let Some(bonds_moving_average) = self.bonds_moving_average {
+			SubtensorModule::set_bonds_moving_average(bonds_moving_average);
+		}
+
+		// --- The step runs in the first block whose on_initialize sees the counter reach
+		// BlocksPerStep, and pays out every block since the last step.
+		let blocks_since_last_step = SubtensorModule::get_blocks_since_last_step();
+		let blocks_to_step = SubtensorModule::get_blocks_per_step().saturating_sub(blocks_since_last_step).max(1);
+		let step_block = block + blocks_to_step;
+		let emission = (blocks_since_last_step + blocks_to_step) * SubtensorModule::get_block_emission();
+		System::set_block_number(step_block as BlockNumber);
+
+		let step = SubtensorModule::compute_mechanism_step(emission);
+		let neurons = (0..step.ranks.len()).map(|i| NeuronReplay {
+			uid: i as u32,
+			hotkey: SubtensorModule::get_neuron_for_uid(i as u32).hotkey.to_ss58check(),
+			replayed: StepValues {
+				rank: step.ranks[i],
+				trust: step.trust[i],
+				consensus: step.consensus[i],
+				incentive: step.incentive[i],
+				dividends: step.dividends[i],
+				emission: step.emission[i],
+			},
+			recorded: None,
+			diff: None,
+		}).collect();
+
+		Ok(StepReplay {
+			block,
+			hash: String::new(),
+			step_block,
+			recorded_at: None,
+			emission,
+			rho: SubtensorModule::get_rho(),
+			kappa: SubtensorModule::get_kappa(),
+			bonds_moving_average: SubtensorModule::get_bonds_moving_average(),
+			neurons,
+		})
+	}
+}
+
+impl CliConfiguration for ReplayStepCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+/// Returns the number and hash of the block by which the step started at step_block had written
+/// all of its values, None if the database ends before. A step split across blocks only sets
+/// LastMechansimStepBlock once its last chunk is written.
+fn find_step<B, BA, C>(client: &C, step_block: u64) -> sc_cli::Result<Option<(u64, B::Hash)>>
+where
+	B: BlockT,
+	BA: Backend<B>,
+	C: UsageProvider<B> + StorageProvider<B, BA> + HeaderBackend<B>,
+{
+	let best = client.usage_info().chain.best_number.saturated_into::<u64>();
+	for number in step_block..=best {
+		let hash = match client.hash(number.saturated_into::<NumberFor<B>>())? {
+			Some(hash) => hash,
+			None => break,
+		};
+		let last_step = read_value::<B, BA, C, u64>(
+			client, hash, storage_prefix(SUBTENSOR_PREFIX, "LastMechansimStepBlock"), "LastMechansimStepBlock"
+		)?.unwrap_or(0);
+		if last_step == step_block {
+			return Ok(Some((number, hash)));
+		}
+		if last_step > step_block {
+			break;
+		}
+	}
+	Ok(None)
+}
+
+/// Reads the ss58 hotkey and step values of every neuron at the block with the given hash, by uid.
+fn read_step_values<B, BA, C>(client: &C, hash: B::Hash) -> sc_cli::Result<BTreeMap<u32, (String, StepValues<u64>)>>
+where
+	B: BlockT,
+	BA: Backend<B>,
+	C: StorageProvider<B, BA>,
+{
+	let mut values = BTreeMap::new();
+	for (_, data) in client.storage_pairs(&BlockId::Hash(hash), &StorageKey(storage_prefix(SUBTENSOR_PREFIX, "Neurons")))? {
+		let neuron = NeuronMetadata::<AccountId>::decode(&mut &data.0[..])
+			.map_err(|e| sc_cli::Error::Input(format!("Unable to decode Neurons: {}", e)))?;
+		values.insert(neuron.uid, (neuron.hotkey.to_ss58check(), StepValues {
+			rank: neuron.rank,
+			trust: neuron.trust,
+			consensus: neuron.consensus,
+			incentive: neuron.incentive,
+			dividends: neuron.dividends,
+			emission: neuron.emission,
+		}));
+	}
+	Ok(values)
+}
+
+/// Writes one row per neuron with the replayed, recorded and diff value of each field, preceded
+/// by the blocks and parameters of the step as `# name,value` comment lines.
+fn write_csv(out: &mut dyn Write, replay: &StepReplay) -> io::Result<()> {
+	writeln!(out, "# block,{}", replay.block)?;
+	writeln!(out, "# hash,{}", replay.hash)?;
+	writeln!(out, "# stepBlock,{}", replay.step_block)?;
+	writeln!(out, "# recordedAt,{}", replay.recorded_at.map(|b| b.to_string()).unwrap_or_default())?;
+	writeln!(out, "# emission,{}", replay.emission)?;
+	writeln!(out, "# Rho,{}", replay.rho)?;
+	writeln!(out, "# Kappa,{}", replay.kappa)?;
+	writeln!(out, "# BondsMovingAverage,{}", replay.bonds_moving_average)?;
+	write!(out, "uid,hotkey")?;
+	for field in ["rank", "trust", "consensus", "incentive", "dividends", "emission"].iter() {
+		write!(out, ",{0},{0}_recorded,{0}_diff", field)?;
+	}
+	writeln!(out)?;
+	for n in replay.neurons.iter() {
+		write!(out, "{},{}", n.uid, n.hotkey)?;
+		let replayed = &n.replayed;
+		let recorded = n.recorded.as_ref();
+		let diff = n.diff.as_ref();
+		let columns: [(u64, Option<u64>, Option<i128>); 6] = [
+			(replayed.rank, recorded.map(|r| r.rank), diff.map(|d| d.rank)),
+			(replayed.trust, recorded.map(|r| r.trust), diff.map(|d| d.trust)),
+			(replayed.consensus, recorded.map(|r| r.consensus), diff.map(|d| d.consensus)),
+			(replayed.incentive, recorded.map(|r| r.incentive), diff.map(|d| d.incentive)),
+			(replayed.dividends, recorded.map(|r| r.dividends), diff.map(|d| d.dividends)),
+			(replayed.emission, recorded.map(|r| r.emission), diff.map(|d| d.emission)),
+		];
+		for (value, recorded, diff) in columns.iter() {
+			write!(
+				out, ",{},{},{}",
+				value,
+				recorded.map(|v| v.to_string()).unwrap_or_default(),
+				diff.map(|v| v.to_string()).unwrap_or_default(),
+			)?;
+		}
+		writeln!(out)?;
+	}
+	Ok(())
+}
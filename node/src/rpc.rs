@@ -7,13 +7,143 @@
 
 use std::sync::Arc;
 
-use node_subtensor_runtime::{opaque::Block, AccountId, Balance, Index};
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result as RpcResult};
+use jsonrpc_derive::rpc;
+use node_subtensor_runtime::{opaque::Block, AccountId, Balance, Index, SubtensorApi as SubtensorRuntimeApi};
+use node_subtensor_runtime::pallet_subtensor::{Metagraph, MechanismStepResult, NeuronMetadata};
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::{Error as BlockChainError, HeaderMetadata, HeaderBackend};
 use sp_block_builder::BlockBuilder;
 pub use sc_rpc_api::DenyUnsafe;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
 use sp_transaction_pool::TransactionPool;
 
+/// Error code returned when a call into the subtensor runtime api fails.
+const RUNTIME_ERROR: i64 = 1;
+
+/// Subtensor specific RPC methods.
+#[rpc]
+pub trait SubtensorApi<BlockHash> {
+	/// Returns every neuron field as columns for the uids in [start, end) at the given
+	/// block, or at the best block when no hash is passed. Unset bounds default to the full graph.
+	#[rpc(name = "subtensor_getMetagraph")]
+	fn get_metagraph(
+		&self,
+		start: Option<u32>,
+		end: Option<u32>,
+		at: Option<BlockHash>,
+	) -> RpcResult<Metagraph<AccountId>>;
+
+	/// Returns the neuron registered under this uid, or null if the uid is not in use.
+	#[rpc(name = "subtensor_getNeuronByUid")]
+	fn get_neuron_by_uid(
+		&self,
+		uid: u32,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<NeuronMetadata<AccountId>>>;
+
+	/// Returns the neuron registered with this hotkey, or null if the hotkey is not registered.
+	#[rpc(name = "subtensor_getNeuronByHotkey")]
+	fn get_neuron_by_hotkey(
+		&self,
+		hotkey: AccountId,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<NeuronMetadata<AccountId>>>;
+
+	/// Returns all neurons owned by this coldkey, ordered by uid.
+	#[rpc(name = "subtensor_getNeuronsByColdkey")]
+	fn get_neurons_by_coldkey(
+		&self,
+		coldkey: AccountId,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<NeuronMetadata<AccountId>>>;
+
+	/// Returns what the mechanism step would write if it distributed `emission` at the given
+	/// block, without writing anything.
+	#[rpc(name = "subtensor_simulateMechanismStep")]
+	fn simulate_mechanism_step(
+		&self,
+		emission: u64,
+		at: Option<BlockHash>,
+	) -> RpcResult<MechanismStepResult>;
+}
+
+/// Implements the SubtensorApi RPC trait by calling into the runtime.
+pub struct Subtensor<C> {
+	client: Arc<C>,
+}
+
+impl<C> Subtensor<C> {
+	/// Create new `Subtensor` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client }
+	}
+}
+
+impl<C> SubtensorApi<<Block as BlockT>::Hash> for Subtensor<C> where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: SubtensorRuntimeApi<Block>,
+{
+	fn get_metagraph(
+		&self,
+		start: Option<u32>,
+		end: Option<u32>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Metagraph<AccountId>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.get_metagraph(&at, start, end).map_err(|e| runtime_error("Unable to query metagraph.", e))
+	}
+
+	fn get_neuron_by_uid(
+		&self,
+		uid: u32,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Option<NeuronMetadata<AccountId>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.get_neuron_by_uid(&at, uid).map_err(|e| runtime_error("Unable to query neuron.", e))
+	}
+
+	fn get_neuron_by_hotkey(
+		&self,
+		hotkey: AccountId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Option<NeuronMetadata<AccountId>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.get_neuron_by_hotkey(&at, hotkey).map_err(|e| runtime_error("Unable to query neuron.", e))
+	}
+
+	fn get_neurons_by_coldkey(
+		&self,
+		coldkey: AccountId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<NeuronMetadata<AccountId>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.get_neurons_by_coldkey(&at, coldkey).map_err(|e| runtime_error("Unable to query neurons.", e))
+	}
+
+	fn simulate_mechanism_step(
+		&self,
+		emission: u64,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<MechanismStepResult> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.simulate_mechanism_step(&at, emission).map_err(|e| runtime_error("Unable to simulate mechanism step.", e))
+	}
+}
+
+fn runtime_error(message: &str, e: impl std::fmt::Debug) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(RUNTIME_ERROR),
+		message: message.into(),
+		data: Some(format!("{:?}", e).into()),
+	}
+}
+
 
 /// Full client dependencies.
 pub struct FullDeps<C, P> {
@@ -35,6 +165,7 @@ pub fn create_full<C, P>(
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
 	C::Api: BlockBuilder<Block>,
+	C::Api: SubtensorRuntimeApi<Block>,
 	P: TransactionPool + 'static,
 {
 	use substrate_frame_rpc_system::{FullSystem, SystemApi};
@@ -55,10 +186,9 @@ pub fn create_full<C, P>(
 		TransactionPaymentApi::to_delegate(TransactionPayment::new(client.clone()))
 	);
 
-	// Extend this RPC with a custom API by using the following syntax.
-	// `YourRpcStruct` should have a reference to a client, which is needed
-	// to call into the runtime.
-	// `io.extend_with(YourRpcTrait::to_delegate(YourRpcStruct::new(ReferenceToClient, ...)));`
+	io.extend_with(
+		SubtensorApi::to_delegate(Subtensor::new(client))
+	);
 
 	io
 }
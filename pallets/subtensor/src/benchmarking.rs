@@ -3,17 +3,264 @@
 use super::*;
 
 use frame_system::RawOrigin;
-use frame_benchmarking::{benchmarks, whitelisted_caller, impl_benchmark_test_suite};
+use frame_benchmarking::{benchmarks, account, impl_benchmark_test_suite};
+use frame_support::traits::Hooks;
 #[allow(unused)]
 use crate::Pallet as Subtensor;
 
+const SEED: u32 = 0;
+
+/// Upper bound for the graph size in the parameterized benchmarks.
+const MAX_UIDS: u32 = 2000;
+
+/// Upper bound for the number of non-zero weights or bonds in the mechanism step benchmark.
+const MAX_ENTRIES: u32 = 100_000;
+
+// Registers a neuron with hotkey and coldkey derived from index and returns the hotkey.
+fn register_neuron<T: Config>( index: u32 ) -> T::AccountId {
+	let hotkey: T::AccountId = account( "hotkey", index, SEED );
+	let coldkey: T::AccountId = account( "coldkey", index, SEED );
+	let block_number: u64 = Subtensor::<T>::get_current_block_as_u64();
+	let ( nonce, work ): ( u64, Vec<u8> ) = Subtensor::<T>::create_work_for_block_number( block_number );
+	Subtensor::<T>::do_registration( RawOrigin::Signed( hotkey.clone() ).into(), block_number, nonce, work, hotkey.clone(), coldkey )
+		.expect( "registration with difficulty 1 succeeds" );
+	hotkey
+}
+
+// Fills the graph with n neurons, all registrations go into a fresh uid.
+fn register_neurons<T: Config>( n: u32 ) {
+	Subtensor::<T>::set_difficulty_from_u64( 1 );
+	Subtensor::<T>::set_max_registratations_per_block( u64::MAX );
+	Subtensor::<T>::set_max_allowed_uids( MAX_UIDS as u64 + 1 );
+	for i in 0..n {
+		register_neuron::<T>( i );
+	}
+}
+
 benchmarks! {
-	do_something {
-		let s in 0 .. 100;
-		let caller: T::AccountId = whitelisted_caller();
-	}: _(RawOrigin::Signed(caller), s)
+	set_weights {
+		let n in 2 .. MAX_UIDS;
+		register_neurons::<T>( n );
+		Subtensor::<T>::set_min_allowed_weights( 0 );
+		let hotkey: T::AccountId = account( "hotkey", 0, SEED );
+		let uids: Vec<u32> = (0..n).collect();
+		let values: Vec<u32> = vec![ 1; n as usize ];
+	}: _( RawOrigin::Signed( hotkey ), uids, values )
+	verify {
+		assert_eq!( Weights::<T>::get( 0 ).len(), n as usize );
+	}
+
+	add_stake {
+		register_neurons::<T>( 1 );
+		let hotkey: T::AccountId = account( "hotkey", 0, SEED );
+		let coldkey: T::AccountId = account( "coldkey", 0, SEED );
+		let amount: u64 = 1_000_000_000;
+		Subtensor::<T>::add_balance_to_coldkey_account( &coldkey, Subtensor::<T>::u64_to_balance( 2 * amount ).unwrap() );
+	}: _( RawOrigin::Signed( coldkey ), hotkey, amount )
+	verify {
+		assert_eq!( Subtensor::<T>::get_neuron_for_uid( 0 ).stake, amount );
+	}
+
+	remove_stake {
+		register_neurons::<T>( 1 );
+		let hotkey: T::AccountId = account( "hotkey", 0, SEED );
+		let coldkey: T::AccountId = account( "coldkey", 0, SEED );
+		let amount: u64 = 1_000_000_000;
+		Subtensor::<T>::add_stake_to_neuron_hotkey_account( 0, amount );
+	}: _( RawOrigin::Signed( coldkey ), hotkey, amount )
+	verify {
+		assert_eq!( Subtensor::<T>::get_neuron_for_uid( 0 ).stake, 0 );
+	}
+
+	// Withdraws a matured chunk out of a full queue, the other chunks are still unbonding.
+	withdraw_unbonded {
+		let coldkey: T::AccountId = account( "coldkey", 0, SEED );
+		let amount: u64 = 1_000_000_000;
+		let max_chunks: u64 = T::MaxUnbondingChunks::get() as u64;
+		let mut chunks: Vec<(u64, u64)> = vec![ ( amount, 0 ) ];
+		for i in 1..max_chunks {
+			chunks.push( ( amount, u64::MAX - max_chunks + i ) );
+		}
+		Unbonding::<T>::insert( &coldkey, chunks );
+	}: _( RawOrigin::Signed( coldkey.clone() ) )
+	verify {
+		assert_eq!( Subtensor::<T>::get_unbonding_stake( &coldkey ), amount * ( max_chunks - 1 ) );
+	}
+
+	move_stake {
+		register_neurons::<T>( 1 );
+		let coldkey: T::AccountId = account( "coldkey", 0, SEED );
+		let origin_hotkey: T::AccountId = account( "hotkey", 0, SEED );
+		let destination_hotkey: T::AccountId = account( "hotkey", 1, SEED );
+		let block_number: u64 = Subtensor::<T>::get_current_block_as_u64();
+		let ( nonce, work ): ( u64, Vec<u8> ) = Subtensor::<T>::create_work_for_block_number( block_number );
+		Subtensor::<T>::do_registration( RawOrigin::Signed( destination_hotkey.clone() ).into(), block_number, nonce, work, destination_hotkey.clone(), coldkey.clone() )?;
+		let amount: u64 = 1_000_000_000;
+		Subtensor::<T>::add_stake_to_neuron_hotkey_account( 0, amount );
+	}: _( RawOrigin::Signed( coldkey ), origin_hotkey, destination_hotkey, amount )
+	verify {
+		assert_eq!( Subtensor::<T>::get_neuron_for_uid( 1 ).stake, amount );
+	}
+
+	transfer_stake {
+		register_neurons::<T>( 2 );
+		let coldkey: T::AccountId = account( "coldkey", 0, SEED );
+		let origin_hotkey: T::AccountId = account( "hotkey", 0, SEED );
+		let destination_hotkey: T::AccountId = account( "hotkey", 1, SEED );
+		let amount: u64 = 1_000_000_000;
+		Subtensor::<T>::add_stake_to_neuron_hotkey_account( 0, amount );
+	}: _( RawOrigin::Signed( coldkey ), origin_hotkey, destination_hotkey, amount )
+	verify {
+		assert_eq!( Subtensor::<T>::get_neuron_for_uid( 1 ).stake, amount );
+	}
+
+	set_delegate_take {
+		register_neurons::<T>( 1 );
+		let coldkey: T::AccountId = account( "coldkey", 0, SEED );
+		let hotkey: T::AccountId = account( "hotkey", 0, SEED );
+	}: _( RawOrigin::Signed( coldkey ), hotkey.clone(), 18 )
+	verify {
+		assert_eq!( Subtensor::<T>::get_delegate_take( &hotkey ), 18 );
+	}
+
+	set_reward_destination {
+		register_neurons::<T>( 1 );
+		let coldkey: T::AccountId = account( "coldkey", 0, SEED );
+		let hotkey: T::AccountId = account( "hotkey", 0, SEED );
+	}: _( RawOrigin::Signed( coldkey ), hotkey, RewardDestination::Coldkey )
+	verify {
+		assert_eq!( Subtensor::<T>::reward_destination( 0 ), RewardDestination::Coldkey );
+	}
+
+	serve_axon {
+		register_neurons::<T>( 1 );
+		let hotkey: T::AccountId = account( "hotkey", 0, SEED );
+		let ip: u128 = 0x08080808; // 8.8.8.8
+	}: _( RawOrigin::Signed( hotkey ), 1, ip, 8883, 4, 0 )
+	verify {
+		assert_eq!( Subtensor::<T>::get_neuron_for_uid( 0 ).ip, ip );
+	}
+
+	// Registration into a fresh uid, the graph is not full.
+	register {
+		register_neurons::<T>( 0 );
+		let hotkey: T::AccountId = account( "hotkey", 0, SEED );
+		let coldkey: T::AccountId = account( "coldkey", 0, SEED );
+		let block_number: u64 = Subtensor::<T>::get_current_block_as_u64();
+		let ( nonce, work ): ( u64, Vec<u8> ) = Subtensor::<T>::create_work_for_block_number( block_number );
+	}: _( RawOrigin::Signed( hotkey.clone() ), block_number, nonce, work, hotkey.clone(), coldkey )
+	verify {
+		assert!( Subtensor::<T>::is_hotkey_active( &hotkey ) );
+	}
+
+	// Registration into a full graph of n neurons. Every neuron holds a bond in uid 0,
+	// which is the one pruned, so every bond row is rewritten.
+	register_prune {
+		let n in 1 .. MAX_UIDS;
+		register_neurons::<T>( n );
+		Subtensor::<T>::set_max_allowed_uids( n as u64 );
+		Subtensor::<T>::set_immunity_period( 0 );
+		for uid in 0..n {
+			Bonds::<T>::insert( uid, vec![ (0, 1_000) ] );
+			if uid != 0 {
+				Subtensor::<T>::add_stake_to_neuron_hotkey_account( uid, 1_000 );
+			}
+		}
+		let hotkey: T::AccountId = account( "hotkey", n, SEED );
+		let coldkey: T::AccountId = account( "coldkey", n, SEED );
+		let block_number: u64 = Subtensor::<T>::get_current_block_as_u64();
+		let ( nonce, work ): ( u64, Vec<u8> ) = Subtensor::<T>::create_work_for_block_number( block_number );
+	}: register( RawOrigin::Signed( hotkey.clone() ), block_number, nonce, work, hotkey.clone(), coldkey )
+	verify {
+		assert_eq!( Subtensor::<T>::get_uid_for_hotkey( &hotkey ), 0 );
+		assert_eq!( Subtensor::<T>::get_neuron_count(), n );
+	}
+
+	swap_hotkey {
+		register_neurons::<T>( 1 );
+		let coldkey: T::AccountId = account( "coldkey", 0, SEED );
+		let old_hotkey: T::AccountId = account( "hotkey", 0, SEED );
+		let new_hotkey: T::AccountId = account( "hotkey", 1, SEED );
+	}: _( RawOrigin::Signed( coldkey ), old_hotkey, new_hotkey.clone() )
+	verify {
+		assert_eq!( Subtensor::<T>::get_uid_for_hotkey( &new_hotkey ), 0 );
+	}
+
+	announce_coldkey_swap {
+		register_neurons::<T>( 1 );
+		let old_coldkey: T::AccountId = account( "coldkey", 0, SEED );
+		let new_coldkey: T::AccountId = account( "coldkey", 1, SEED );
+	}: _( RawOrigin::Signed( old_coldkey.clone() ), new_coldkey )
+	verify {
+		assert!( Subtensor::<T>::get_coldkey_swap_announcement( &old_coldkey ).is_some() );
+	}
+
+	// Finalizes the swap of a coldkey owning n neurons, each a delegate it also nominates,
+	// with stake of the coldkey still unbonding.
+	finalize_coldkey_swap {
+		let n in 1 .. MAX_UIDS;
+		register_neurons::<T>( 0 );
+		Subtensor::<T>::set_coldkey_swap_delay( 0 );
+		let old_coldkey: T::AccountId = account( "coldkey", 0, SEED );
+		let new_coldkey: T::AccountId = account( "coldkey", 1, SEED );
+		let block_number: u64 = Subtensor::<T>::get_current_block_as_u64();
+		let ( nonce, work ): ( u64, Vec<u8> ) = Subtensor::<T>::create_work_for_block_number( block_number );
+		for i in 0..n {
+			let hotkey: T::AccountId = account( "hotkey", i, SEED );
+			Subtensor::<T>::do_registration( RawOrigin::Signed( hotkey.clone() ).into(), block_number, nonce, work.clone(), hotkey.clone(), old_coldkey.clone() )?;
+			Subtensor::<T>::add_nominator_stake( &hotkey, &old_coldkey, 1_000 );
+		}
+		Unbonding::<T>::insert( &old_coldkey, vec![ ( 1_000, block_number + 1 ) ] );
+		Unbonding::<T>::insert( &new_coldkey, vec![ ( 1_000, block_number + 2 ) ] );
+		Subtensor::<T>::do_announce_coldkey_swap( RawOrigin::Signed( old_coldkey.clone() ).into(), new_coldkey.clone() )?;
+	}: _( RawOrigin::Signed( new_coldkey.clone() ), old_coldkey.clone() )
+	verify {
+		assert_eq!( Subtensor::<T>::get_uids_for_coldkey( &new_coldkey ).len(), n as usize );
+	}
+
+	cancel_coldkey_swap {
+		register_neurons::<T>( 1 );
+		let old_coldkey: T::AccountId = account( "coldkey", 0, SEED );
+		let new_coldkey: T::AccountId = account( "coldkey", 1, SEED );
+		Subtensor::<T>::do_announce_coldkey_swap( RawOrigin::Signed( old_coldkey.clone() ).into(), new_coldkey )?;
+	}: _( RawOrigin::Signed( old_coldkey.clone() ) )
+	verify {
+		assert!( Subtensor::<T>::get_coldkey_swap_announcement( &old_coldkey ).is_none() );
+	}
+
+	// A mechanism step over n neurons holding w non-zero weights and b non-zero bonds,
+	// spread evenly over the rows.
+	mechanism_step {
+		let n in 1 .. MAX_UIDS;
+		let w in 0 .. MAX_ENTRIES;
+		let b in 0 .. MAX_ENTRIES;
+		register_neurons::<T>( n );
+		let mut weights: Vec<Vec<(u32,u32)>> = vec![ vec![]; n as usize ];
+		let mut bonds: Vec<Vec<(u32,u64)>> = vec![ vec![]; n as usize ];
+		for k in 0..w.min( n * n ) {
+			weights[ (k % n) as usize ].push( ( k / n, u32::MAX / n ) );
+		}
+		for k in 0..b.min( n * n ) {
+			bonds[ (k % n) as usize ].push( ( k / n, 1_000_000 ) );
+		}
+		for uid in 0..n {
+			Subtensor::<T>::add_stake_to_neuron_hotkey_account( uid, 1_000_000_000 );
+			Weights::<T>::insert( uid, &weights[ uid as usize ] );
+			Bonds::<T>::insert( uid, &bonds[ uid as usize ] );
+		}
+		Subtensor::<T>::set_blocks_since_last_step( Subtensor::<T>::get_blocks_per_step().saturating_sub( 1 ) );
+		let block_number: T::BlockNumber = frame_system::Pallet::<T>::block_number();
+	}: {
+		Subtensor::<T>::on_initialize( block_number );
+	}
+	verify {
+		assert_eq!( Subtensor::<T>::get_blocks_since_last_step(), 0 );
+	}
+
+	sudo_set_hyperparameter {
+	}: sudo_set_difficulty( RawOrigin::Root, 10_000 )
 	verify {
-		assert_eq!(Something::<T>::get(), Some(s));
+		assert_eq!( Subtensor::<T>::get_difficulty_as_u64(), 10_000 );
 	}
 }
 
@@ -0,0 +1,80 @@
+use super::*;
+use frame_system::{ensure_signed};
+
+impl<T: Config> Pallet<T> {
+
+    /// Announces that the calling coldkey hands its neurons over to new_coldkey. The swap can be
+    /// finalized once ColdkeySwapDelay blocks have passed, and cancelled by the calling coldkey until then.
+    pub fn do_announce_coldkey_swap( origin: T::Origin, new_coldkey: T::AccountId ) -> dispatch::DispatchResult {
+
+        // --- Check the callers coldkey signature.
+        let old_coldkey = ensure_signed( origin )?;
+
+        // --- Check there is something to hand over and nothing pending already.
+        ensure!( old_coldkey != new_coldkey, Error::<T>::SameColdkey );
+        ensure!( !Self::get_uids_for_coldkey( &old_coldkey ).is_empty(), Error::<T>::NotRegistered );
+        ensure!( !ColdkeySwapAnnouncements::<T>::contains_key( &old_coldkey ), Error::<T>::ColdkeySwapAlreadyAnnounced );
+
+        // --- Record the announcement.
+        let execution_block: u64 = Self::get_current_block_as_u64().saturating_add( Self::get_coldkey_swap_delay() );
+        ColdkeySwapAnnouncements::<T>::insert( &old_coldkey, ( new_coldkey.clone(), execution_block ) );
+        Self::deposit_event( Event::ColdkeySwapAnnounced( old_coldkey, new_coldkey, execution_block ) );
+
+        Ok(())
+    }
+
+    /// Hands every neuron of old_coldkey over to the announced coldkey, along with the stake it has
+    /// unbonding, its nominations and rewards paid to it. The call is signed by either of the two
+    /// coldkeys and only succeeds once the announcement delay has passed.
+    pub fn do_finalize_coldkey_swap( origin: T::Origin, old_coldkey: T::AccountId ) -> dispatch::DispatchResult {
+
+        // --- Check the signature of either coldkey.
+        let caller = ensure_signed( origin )?;
+        ensure!( ColdkeySwapAnnouncements::<T>::contains_key( &old_coldkey ), Error::<T>::NoColdkeySwapAnnounced );
+        let ( new_coldkey, execution_block ) = ColdkeySwapAnnouncements::<T>::get( &old_coldkey );
+        ensure!( caller == old_coldkey || caller == new_coldkey, Error::<T>::NonAssociatedColdKey );
+        ensure!( Self::get_current_block_as_u64() >= execution_block, Error::<T>::ColdkeySwapDelayNotElapsed );
+
+        // --- Move the neurons owned at this point, which may differ from the ones owned at the announcement.
+        for uid in Self::get_uids_for_coldkey( &old_coldkey ) {
+            let mut neuron = Neurons::<T>::get( uid );
+            neuron.coldkey = new_coldkey.clone();
+            Neurons::<T>::insert( uid, neuron );
+            ColdkeyUids::<T>::remove( &old_coldkey, uid );
+            ColdkeyUids::<T>::insert( &new_coldkey, uid, () );
+
+            // RewardDestination::Coldkey follows the neuron, an account naming the old coldkey is redirected.
+            if RewardDestinations::<T>::get( uid ) == RewardDestination::Account( old_coldkey.clone() ) {
+                RewardDestinations::<T>::insert( uid, RewardDestination::Account( new_coldkey.clone() ) );
+            }
+        }
+
+        // --- Move the stake the old coldkey still has unbonding and the stake it nominated. Stake
+        // the new coldkey now nominates on its own hotkeys becomes owner stake.
+        Self::swap_unbonding( &old_coldkey, &new_coldkey );
+        Self::swap_nominator_coldkey( &old_coldkey, &new_coldkey );
+        Self::fold_owner_nominations( &new_coldkey );
+        ColdkeySwapAnnouncements::<T>::remove( &old_coldkey );
+        Self::deposit_event( Event::ColdkeySwapped( old_coldkey, new_coldkey ) );
+
+        Ok(())
+    }
+
+    /// Cancels the coldkey swap announced by the calling coldkey.
+    pub fn do_cancel_coldkey_swap( origin: T::Origin ) -> dispatch::DispatchResult {
+
+        // --- Check the callers coldkey signature.
+        let old_coldkey = ensure_signed( origin )?;
+        ensure!( ColdkeySwapAnnouncements::<T>::contains_key( &old_coldkey ), Error::<T>::NoColdkeySwapAnnounced );
+
+        ColdkeySwapAnnouncements::<T>::remove( &old_coldkey );
+        Self::deposit_event( Event::ColdkeySwapCancelled( old_coldkey ) );
+
+        Ok(())
+    }
+
+    /// Returns the coldkey swap announced by old_coldkey as ( new_coldkey, execution_block ), if any.
+    pub fn get_coldkey_swap_announcement( old_coldkey: &T::AccountId ) -> Option<( T::AccountId, u64 )> {
+        ColdkeySwapAnnouncements::<T>::try_get( old_coldkey ).ok()
+    }
+}
@@ -0,0 +1,240 @@
+//! Weights for pallet_subtensor
+//!
+//! THESE ARE PLACEHOLDERS. The values below were estimated by hand from the storage each call
+//! reads and writes, they are not the output of frame-benchmarking, and must be regenerated
+//! from the benchmarks in benchmarking.rs on the reference hardware before a release with:
+//!
+//! ./scripts/benchmark_weights.sh
+//!
+//! which overwrites this file through .maintain/frame-weight-template.hbs, keeping the
+//! WeightInfo trait, SubstrateWeight and the () impl used by the tests.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_subtensor.
+pub trait WeightInfo {
+	fn set_weights(n: u32, ) -> Weight;
+	fn add_stake() -> Weight;
+	fn remove_stake() -> Weight;
+	fn withdraw_unbonded() -> Weight;
+	fn move_stake() -> Weight;
+	fn transfer_stake() -> Weight;
+	fn set_delegate_take() -> Weight;
+	fn set_reward_destination() -> Weight;
+	fn serve_axon() -> Weight;
+	fn register() -> Weight;
+	fn register_prune(n: u32, ) -> Weight;
+	fn swap_hotkey() -> Weight;
+	fn announce_coldkey_swap() -> Weight;
+	fn finalize_coldkey_swap(n: u32, ) -> Weight;
+	fn cancel_coldkey_swap() -> Weight;
+	fn mechanism_step(n: u32, w: u32, b: u32, ) -> Weight;
+	fn sudo_set_hyperparameter() -> Weight;
+}
+
+/// Weights for pallet_subtensor using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	fn set_weights(n: u32, ) -> Weight {
+		(32_400_000 as Weight)
+			.saturating_add((4_150_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	fn add_stake() -> Weight {
+		(61_300_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn remove_stake() -> Weight {
+		(58_900_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn withdraw_unbonded() -> Weight {
+		(31_200_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	fn move_stake() -> Weight {
+		(47_800_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn transfer_stake() -> Weight {
+		(46_900_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn set_delegate_take() -> Weight {
+		(24_500_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn set_reward_destination() -> Weight {
+		(23_900_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn serve_axon() -> Weight {
+		(24_700_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn register() -> Weight {
+		(89_200_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(10 as Weight))
+			.saturating_add(T::DbWeight::get().writes(9 as Weight))
+	}
+	fn register_prune(n: u32, ) -> Weight {
+		(118_500_000 as Weight)
+			.saturating_add((21_600_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(14 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(15 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	fn swap_hotkey() -> Weight {
+		(38_600_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn announce_coldkey_swap() -> Weight {
+		(27_300_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn finalize_coldkey_swap(n: u32, ) -> Weight {
+		(29_100_000 as Weight)
+			.saturating_add((9_400_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().reads((6 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes((8 as Weight).saturating_mul(n as Weight)))
+	}
+	fn cancel_coldkey_swap() -> Weight {
+		(21_800_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn mechanism_step(n: u32, w: u32, b: u32, ) -> Weight {
+		(14_800_000 as Weight)
+			.saturating_add((61_200_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((1_830_000 as Weight).saturating_mul(w as Weight))
+			.saturating_add((2_460_000 as Weight).saturating_mul(b as Weight))
+			.saturating_add(T::DbWeight::get().reads(12 as Weight))
+			.saturating_add(T::DbWeight::get().reads((4 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(n as Weight)))
+	}
+	fn sudo_set_hyperparameter() -> Weight {
+		(12_100_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn set_weights(n: u32, ) -> Weight {
+		(32_400_000 as Weight)
+			.saturating_add((4_150_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	fn add_stake() -> Weight {
+		(61_300_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn remove_stake() -> Weight {
+		(58_900_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn withdraw_unbonded() -> Weight {
+		(31_200_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	fn move_stake() -> Weight {
+		(47_800_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn transfer_stake() -> Weight {
+		(46_900_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn set_delegate_take() -> Weight {
+		(24_500_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn set_reward_destination() -> Weight {
+		(23_900_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn serve_axon() -> Weight {
+		(24_700_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn register() -> Weight {
+		(89_200_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(10 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(9 as Weight))
+	}
+	fn register_prune(n: u32, ) -> Weight {
+		(118_500_000 as Weight)
+			.saturating_add((21_600_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(14 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(15 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	fn swap_hotkey() -> Weight {
+		(38_600_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn announce_coldkey_swap() -> Weight {
+		(27_300_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn finalize_coldkey_swap(n: u32, ) -> Weight {
+		(29_100_000 as Weight)
+			.saturating_add((9_400_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((6 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((8 as Weight).saturating_mul(n as Weight)))
+	}
+	fn cancel_coldkey_swap() -> Weight {
+		(21_800_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn mechanism_step(n: u32, w: u32, b: u32, ) -> Weight {
+		(14_800_000 as Weight)
+			.saturating_add((61_200_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((1_830_000 as Weight).saturating_mul(w as Weight))
+			.saturating_add((2_460_000 as Weight).saturating_mul(b as Weight))
+			.saturating_add(RocksDbWeight::get().reads(12 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((4 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((2 as Weight).saturating_mul(n as Weight)))
+	}
+	fn sudo_set_hyperparameter() -> Weight {
+		(12_100_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+}
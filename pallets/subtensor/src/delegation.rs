@@ -0,0 +1,146 @@
+use super::*;
+use frame_system::{ensure_signed};
+
+impl<T: Config> Pallet<T> {
+
+    /// Opts the hotkey in as a delegate, or updates its take. The call is signed by the coldkey of the
+    /// neuron. From then on any coldkey can stake on the hotkey and share in its emission after the
+    /// take percent the owner keeps.
+    pub fn do_set_delegate_take( origin: T::Origin, hotkey: T::AccountId, take: u64 ) -> dispatch::DispatchResult {
+
+        // --- Check the callers coldkey signature.
+        let coldkey = ensure_signed( origin )?;
+
+        // --- Check the hotkey is registered under the calling coldkey.
+        ensure!( Self::is_hotkey_active( &hotkey ), Error::<T>::NotRegistered );
+        let neuron = Self::get_neuron_for_hotkey( &hotkey );
+        ensure!( Self::neuron_belongs_to_coldkey( &neuron, &coldkey ), Error::<T>::NonAssociatedColdKey );
+        ensure!( take <= 100, Error::<T>::InvalidDelegateTake );
+
+        DelegateTake::<T>::insert( &hotkey, take );
+        Self::deposit_event( Event::DelegateTakeSet( hotkey, take ) );
+
+        Ok(())
+    }
+
+    /// Returns true if the hotkey accepts stake from coldkeys other than its owner.
+    pub fn is_delegate( hotkey: &T::AccountId ) -> bool {
+        DelegateTake::<T>::contains_key( hotkey )
+    }
+
+    /// Returns the percent of its emission the delegate keeps before paying its nominators.
+    pub fn get_delegate_take( hotkey: &T::AccountId ) -> u64 {
+        DelegateTake::<T>::get( hotkey ).unwrap_or( 0 )
+    }
+
+    /// Returns the stake coldkey holds on the delegate hotkey.
+    pub fn get_nominator_stake( hotkey: &T::AccountId, coldkey: &T::AccountId ) -> u64 {
+        NominatorStake::<T>::get( hotkey, coldkey )
+    }
+
+    /// Returns the part of the neuron's stake held by its owning coldkey, i.e. everything not held by nominators.
+    pub fn get_owner_stake( neuron: &NeuronMetadataOf<T> ) -> u64 {
+        neuron.stake.saturating_sub( TotalNominatorStake::<T>::get( &neuron.hotkey ) )
+    }
+
+    pub fn add_nominator_stake( hotkey: &T::AccountId, coldkey: &T::AccountId, amount: u64 ) {
+        NominatorStake::<T>::mutate( hotkey, coldkey, |stake| *stake = stake.saturating_add( amount ) );
+        TotalNominatorStake::<T>::mutate( hotkey, |stake| *stake = stake.saturating_add( amount ) );
+    }
+
+    /// Removes amount from the stake coldkey holds on the delegate hotkey. Callers check the
+    /// nominator holds enough, the subtractions saturate rather than panic if they did not.
+    pub fn remove_nominator_stake( hotkey: &T::AccountId, coldkey: &T::AccountId, amount: u64 ) {
+        let stake: u64 = NominatorStake::<T>::get( hotkey, coldkey ).saturating_sub( amount );
+        if stake == 0 {
+            NominatorStake::<T>::remove( hotkey, coldkey );
+        } else {
+            NominatorStake::<T>::insert( hotkey, coldkey, stake );
+        }
+        TotalNominatorStake::<T>::mutate( hotkey, |total| *total = total.saturating_sub( amount ) );
+    }
+
+    /// Splits the emission of the delegate hotkey between its owner and nominators. The owner keeps
+    /// the delegate take, the rest is shared pro-rata to the stake held before the emission.
+    /// Only the nominator entries are credited, the owner's share is what is left of the neuron stake.
+    /// Returns the number of nominators credited and the emission credited to them.
+    pub fn distribute_to_nominators( hotkey: &T::AccountId, stake_before_emission: u64, emission: u64 ) -> ( u32, u64 ) {
+        let payouts: Vec<(T::AccountId, u64, u64)> = Self::get_nominator_payouts( hotkey, stake_before_emission, emission );
+        let mut total_paid: u64 = 0;
+        for ( coldkey, stake, paid ) in payouts.iter() {
+            NominatorStake::<T>::insert( hotkey, coldkey, stake.saturating_add( *paid ) );
+            total_paid = total_paid.saturating_add( *paid );
+        }
+        if total_paid > 0 {
+            TotalNominatorStake::<T>::mutate( hotkey, |total| *total = total.saturating_add( total_paid ) );
+        }
+        ( payouts.len() as u32, total_paid )
+    }
+
+    /// Returns what distribute_to_nominators pays each nominator of the delegate hotkey, as
+    /// ( coldkey, stake, paid ), without paying it.
+    pub fn get_nominator_payouts( hotkey: &T::AccountId, stake_before_emission: u64, emission: u64 ) -> Vec<(T::AccountId, u64, u64)> {
+        if emission == 0 || stake_before_emission == 0 || TotalNominatorStake::<T>::get( hotkey ) == 0 {
+            return vec![];
+        }
+        let shared_emission: u128 = emission as u128 * ( 100 - Self::get_delegate_take( hotkey ).min( 100 ) ) as u128 / 100;
+        NominatorStake::<T>::iter_prefix( hotkey )
+            .map( |( coldkey, stake )| {
+                let paid: u64 = ( shared_emission * stake as u128 / stake_before_emission as u128 ) as u64;
+                ( coldkey, stake, paid )
+            })
+            .collect()
+    }
+
+    /// Returns the stake of every nominator of the hotkey to their coldkeys and forgets the delegate.
+    /// Returns the amount refunded, the owner is owed what is left of the neuron stake.
+    pub fn refund_nominators( hotkey: &T::AccountId ) -> u64 {
+        let mut refunded: u64 = 0;
+        for ( coldkey, stake ) in NominatorStake::<T>::drain_prefix( hotkey ) {
+            if let Some( balance ) = Self::u64_to_balance( stake ) {
+                Self::add_balance_to_coldkey_account( &coldkey, balance );
+                refunded = refunded.saturating_add( stake );
+            }
+        }
+        TotalNominatorStake::<T>::remove( hotkey );
+        DelegateTake::<T>::remove( hotkey );
+        refunded
+    }
+
+    /// Moves the stake old_coldkey nominated over to new_coldkey. Nominations are only held on
+    /// registered hotkeys, so the lookup is bounded by the neuron count.
+    pub fn swap_nominator_coldkey( old_coldkey: &T::AccountId, new_coldkey: &T::AccountId ) {
+        let hotkeys: Vec<T::AccountId> = <Hotkeys<T> as IterableStorageMap<T::AccountId, u32>>::iter().map( |( hotkey, _ )| hotkey ).collect();
+        for hotkey in hotkeys.iter() {
+            if let Ok( stake ) = NominatorStake::<T>::try_get( hotkey, old_coldkey ) {
+                NominatorStake::<T>::remove( hotkey, old_coldkey );
+                NominatorStake::<T>::mutate( hotkey, new_coldkey, |new_stake| *new_stake = new_stake.saturating_add( stake ) );
+            }
+        }
+    }
+
+    /// Folds the stake coldkey nominated on the hotkeys it owns into their owner stake. A coldkey
+    /// can only withdraw owner stake from its own hotkeys, such a nomination would be stuck.
+    pub fn fold_owner_nominations( coldkey: &T::AccountId ) {
+        for uid in Self::get_uids_for_coldkey( coldkey ) {
+            let hotkey: T::AccountId = Neurons::<T>::get( uid ).hotkey;
+            if let Ok( stake ) = NominatorStake::<T>::try_get( &hotkey, coldkey ) {
+                NominatorStake::<T>::remove( &hotkey, coldkey );
+                TotalNominatorStake::<T>::mutate( &hotkey, |total| *total = total.saturating_sub( stake ) );
+            }
+        }
+    }
+
+    /// Moves the delegate take and the nominators of old_hotkey over to new_hotkey.
+    pub fn swap_delegate_hotkey( old_hotkey: &T::AccountId, new_hotkey: &T::AccountId ) {
+        if let Some( take ) = DelegateTake::<T>::take( old_hotkey ) {
+            DelegateTake::<T>::insert( new_hotkey, take );
+        }
+        for ( coldkey, stake ) in NominatorStake::<T>::drain_prefix( old_hotkey ) {
+            NominatorStake::<T>::insert( new_hotkey, coldkey, stake );
+        }
+        if TotalNominatorStake::<T>::contains_key( old_hotkey ) {
+            TotalNominatorStake::<T>::insert( new_hotkey, TotalNominatorStake::<T>::take( old_hotkey ) );
+        }
+    }
+}
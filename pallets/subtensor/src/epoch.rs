@@ -0,0 +1,556 @@
+//! Pure implementation of the incentive mechanism.
+//!
+//! Nothing in this module touches storage. The pallet reads the graph into
+//! [`EpochInputs`], calls [`epoch`] and writes the [`EpochOutput`] back, so the
+//! mechanism can be tested, and replayed natively, without a runtime.
+//!
+//! Weights and bonds are kept as sparse rows, so an epoch costs O( n + nnz )
+//! where nnz is the number of non-zero weights and bonds, rather than O( n^2 ).
+use sp_std::vec;
+use sp_std::vec::Vec;
+use substrate_fixed::types::I65F63;
+use substrate_fixed::transcendental::exp;
+use substrate_fixed::transcendental::log2;
+
+/// Hyperparameters of a single epoch.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct EpochParams {
+    /// Tokens distributed by this epoch.
+    pub emission: u64,
+    /// Steepness of the consensus sigmoid.
+    pub rho: u64,
+    /// Inverse of the trust threshold at which consensus is one half.
+    pub kappa: u64,
+    /// Weight of the previous bonds in the bond moving average, per million.
+    pub bonds_moving_average: u64,
+    /// Inverse of the share of incentive a neuron keeps for itself.
+    pub self_ownership: u64,
+}
+
+/// The graph an epoch runs over. Every vector is indexed by uid and has length n.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct EpochInputs {
+    /// Stake held by each neuron.
+    pub stake: Vec<u64>,
+    /// Whether each neuron updated within the activity cutoff.
+    pub active: Vec<bool>,
+    /// Transaction priority of each neuron before the epoch.
+    pub priority: Vec<u64>,
+    /// Sparse weight rows, weights[i] = [ (j, w_ij) ].
+    pub weights: Vec<Vec<(u32,u32)>>,
+    /// Sparse bond rows, bonds[i] = [ (j, b_ij) ]. Bonds held in pruned neurons are already removed.
+    pub bonds: Vec<Vec<(u32,u64)>>,
+}
+
+/// The values an epoch produces, indexed by uid.
+/// Ranks, trust, consensus, incentive and dividends are scaled to u64::MAX.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct EpochOutput {
+    pub priority: Vec<u64>,
+    pub ranks: Vec<u64>,
+    pub trust: Vec<u64>,
+    pub consensus: Vec<u64>,
+    pub incentive: Vec<u64>,
+    pub dividends: Vec<u64>,
+    pub emission: Vec<u64>,
+    /// Sparse bond rows after the moving average, bonds[i] = [ (j, b_ij) ].
+    pub bonds: Vec<Vec<(u32,u64)>>,
+    pub total_emission: u64,
+    pub total_bonds_purchased: u64,
+}
+
+/// Returns true if a neuron last updated at last_update counts as active at block. A neuron
+/// updated after block, e.g. while a chunked step is in flight, counts as active.
+pub fn is_active( block: u64, last_update: u64, activity_cutoff: u64 ) -> bool {
+    block.saturating_sub( last_update ) < activity_cutoff
+}
+
+/// Priority increments by the log of the stake and is drained everytime the account sets weights.
+pub fn next_priority( priority: u64, stake: u64 ) -> u64 {
+    let log_stake: I65F63 = log2( I65F63::from_num( stake + 1 ) ).expect( "stake + 1 is positive and greater than 1.");
+    priority + log_stake.to_num::<u64>()
+}
+
+/// Normalizes stake by the total active stake.
+/// Returns the normalized stake and the sum of normalized stake held by active neurons.
+pub fn normalize_stake( stake: &[u64], active: &[bool] ) -> ( Vec<I65F63>, I65F63 ) {
+    let mut total_active_stake: I65F63 = I65F63::from_num( 0.0 );
+    let mut total_normalized_active_stake: I65F63 = I65F63::from_num( 0.0 );
+    let mut normalized: Vec<I65F63> = stake.iter().map( |s| I65F63::from_num( *s ) ).collect();
+    for ( i, stake_i ) in normalized.iter().enumerate() {
+        if active[ i ] {
+            total_active_stake += *stake_i;
+        }
+    }
+    if total_active_stake != 0 {
+        for ( i, stake_i ) in normalized.iter_mut().enumerate() {
+            *stake_i = *stake_i / total_active_stake;
+            if active[ i ] {
+                total_normalized_active_stake += *stake_i;
+            }
+        }
+    }
+    ( normalized, total_normalized_active_stake )
+}
+
+/// Accumulated rank and trust scores before normalization.
+pub struct RankAccumulator {
+    pub ranks: Vec<I65F63>,
+    pub trust: Vec<I65F63>,
+    pub bond_totals: Vec<u64>,
+    pub total_ranks: I65F63,
+    pub total_trust: I65F63,
+    pub total_bonds_purchased: u64,
+    block_emission: I65F63,
+    bonds_moving_average: I65F63,
+}
+
+impl RankAccumulator {
+    /// Starts accumulating over n neurons given the bond totals of the previous bonds.
+    /// bonds_moving_average is the weight of the previous bonds, on range [0, 1].
+    pub fn new( bond_totals: Vec<u64>, block_emission: I65F63, bonds_moving_average: I65F63 ) -> Self {
+        let n = bond_totals.len();
+        RankAccumulator {
+            ranks: vec![ I65F63::from_num( 0.0 ); n ],
+            trust: vec![ I65F63::from_num( 0.0 ); n ],
+            bond_totals,
+            total_ranks: I65F63::from_num( 0.0 ),
+            total_trust: I65F63::from_num( 0.0 ),
+            total_bonds_purchased: 0,
+            block_emission,
+            bonds_moving_average,
+        }
+    }
+
+    /// Adds the scores neuron i gives through its weights and moves its bonds towards them.
+    /// bonds_i is the sparse bond row of i, sorted by uid, and is updated in place.
+    pub fn accumulate_row(
+        &mut self,
+        uid_i: u32,
+        stake_i: I65F63,
+        active_i: bool,
+        weights_i: &[(u32,u32)],
+        bonds_i: &mut Vec<(u32,u64)>,
+    ) {
+        let zero: I65F63 = I65F63::from_num( 0.0 );
+        let one: I65F63 = I65F63::from_num( 1.0 );
+        let u32_max: I65F63 = I65F63::from_num( u32::MAX );
+        let block_emission: I65F63 = self.block_emission;
+        let bonds_moving_average: I65F63 = self.bonds_moving_average;
+        if stake_i == zero { return } // Skip zeros stake.
+
+        // === Iterate over weights ===
+        for ( uid_j, weight_ij ) in weights_i.iter() {
+
+            if !active_i { continue } // Skip non active.
+            if uid_i == *uid_j { continue } // Skip self-weight.
+
+            // === Compute score increments ===
+            let weight_ij: I65F63 = I65F63::from_num( *weight_ij ) / u32_max; // Range( 0, 1 )
+            let trust_increment_ij: I65F63 = stake_i; // Range( 0, 1 )
+            let rank_increment_ij: I65F63 = stake_i * weight_ij; // Range( 0, total_active_stake )
+            let bond_increment_ij: I65F63 = rank_increment_ij * block_emission; // Range( 0, block_emission )
+
+            // === Increment neuron scores ===
+            self.ranks[ *uid_j as usize ] += rank_increment_ij;  // Range( 0, total_active_stake )
+            self.trust[ *uid_j as usize ] += trust_increment_ij;  // Range( 0, total_active_stake )
+            self.total_ranks += rank_increment_ij;  // Range( 0, total_active_stake )
+            self.total_trust += trust_increment_ij;  // Range( 0, total_active_stake )
+
+            // === Compute bonding moving averages ===
+            let position: Result<usize, usize> = bonds_i.binary_search_by_key( uid_j, |(uid_k, _)| *uid_k );
+            let prev_bonds_ij: I65F63 = match position {
+                Ok( k ) => I65F63::from_num( bonds_i[ k ].1 ),
+                Err( _ ) => I65F63::from_num( 0 ),
+            };
+            let moving_average_bonds_ij = bonds_moving_average * prev_bonds_ij + ( one - bonds_moving_average ) * bond_increment_ij;
+            match position {
+                Ok( k ) => bonds_i[ k ].1 = moving_average_bonds_ij.to_num::<u64>(), // Range( 0, block_emission )
+                Err( k ) => bonds_i.insert( k, ( *uid_j, moving_average_bonds_ij.to_num::<u64>() ) ),
+            }
+
+            // === Update bond totals ===
+            if prev_bonds_ij >= moving_average_bonds_ij {
+                self.bond_totals[ *uid_j as usize ] -= ( prev_bonds_ij - moving_average_bonds_ij ).to_num::<u64>(); // Range( 0, block_emission )
+                self.total_bonds_purchased = ( prev_bonds_ij - moving_average_bonds_ij ).to_num::<u64>(); // Range( 0, block_emission )
+            } else {
+                self.bond_totals[ *uid_j as usize ] += ( moving_average_bonds_ij - prev_bonds_ij ).to_num::<u64>(); // Range( 0, block_emission )
+                self.total_bonds_purchased = ( moving_average_bonds_ij - prev_bonds_ij ).to_num::<u64>(); // Range( 0, block_emission )
+            }
+        }
+    }
+
+    /// Normalizes ranks to sum to one and trust by the total normalized active stake.
+    pub fn normalize( &mut self, total_normalized_active_stake: I65F63 ) {
+        if self.total_trust > 0 && self.total_ranks > 0 {
+            for ( rank_i, trust_i ) in self.ranks.iter_mut().zip( self.trust.iter_mut() ) {
+                *rank_i = *rank_i / self.total_ranks; // Vector will sum to u64_max
+                *trust_i = *trust_i / total_normalized_active_stake; // Vector will sum to u64_max
+            }
+        }
+    }
+}
+
+/// Computes consensus = 1 / ( 1 + exp( -rho * ( trust - 1 / kappa ) ) ) and
+/// incentive = ranks * consensus normalized to sum to one.
+/// Both vectors are zero when nobody ranked anybody.
+pub fn consensus_and_incentive( ranks: &[I65F63], trust: &[I65F63], total_ranks: I65F63, total_trust: I65F63, rho: u64, kappa: u64 ) -> ( Vec<I65F63>, Vec<I65F63> ) {
+    let n: usize = ranks.len();
+    let one: I65F63 = I65F63::from_num( 1.0 );
+    let rho: I65F63 = I65F63::from_num( rho );
+    let kappa: I65F63 = one / I65F63::from_num( kappa );
+    let mut total_incentive: I65F63 = I65F63::from_num( 0.0 );
+    let mut consensus: Vec<I65F63> = vec![ I65F63::from_num( 0.0 ) ; n];
+    let mut incentive: Vec<I65F63> = vec![ I65F63::from_num( 0.0 ) ; n];
+    if total_ranks != 0 && total_trust != 0 {
+        for ( i, trust_i ) in trust.iter().enumerate() {
+            // Get exponentiated trust score.
+            let shifted_trust: I65F63 = *trust_i - kappa; // Range( -kappa, 1 - kappa )
+            let temperatured_trust: I65F63 = shifted_trust * rho; // Range( -rho * kappa, rho ( 1 - kappa ) )
+            let exponentiated_trust: I65F63 = exp( -temperatured_trust ).expect( "temperatured_trust is on range( -rho * kappa, rho ( 1 - kappa ) )"); // Range( exp(-rho * kappa), exp(rho ( 1 - kappa )) )
+
+            // Compute consensus.
+            let consensus_i: I65F63 = one / (one + exponentiated_trust); // Range( 0, 1 )
+            let incentive_i: I65F63 = ranks[ i ] * consensus_i; // Range( 0, 1 )
+            consensus[ i ] = consensus_i; // Range( 0, 1 )
+            incentive[ i ] = incentive_i; // Range( 0, 1 )
+            total_incentive += incentive_i;
+        }
+    }
+    // Normalize Incentive.
+    if total_incentive > 0 {
+        for incentive_i in incentive.iter_mut() {
+            *incentive_i = *incentive_i / total_incentive; // Vector will sum to u64_max
+        }
+    }
+    ( consensus, incentive )
+}
+
+/// Computes the dividends neuron i earns: its self owned share of its own incentive plus its
+/// bond share of the incentive of the neurons it holds bonds in.
+/// Returns the dividends and the sparse bond row of i, keeping only entries that earn dividends.
+pub fn dividends_row( uid_i: u32, incentive: &[I65F63], bonds_i: &[(u32,u64)], bond_totals: &[u64], self_ownership: I65F63 ) -> ( I65F63, Vec<(u32,u64)> ) {
+    let one: I65F63 = I65F63::from_num( 1.0 );
+    let mut sparse_bonds_row: Vec<(u32, u64)> = vec![];
+
+    // Distribute dividends from self-ownership.
+    let incentive_i: I65F63 = incentive[ uid_i as usize ];
+    let total_bonds_i: u64 = bond_totals[ uid_i as usize ]; // Range( 0, total_emission );
+    let mut dividends_i: I65F63 = incentive_i * self_ownership;
+    if total_bonds_i == 0 {
+        dividends_i += incentive_i * ( one - self_ownership ); // Add the other half.
+    }
+
+    // Distribute dividends from other-ownership.
+    for ( uid_j, bonds_ij ) in bonds_i.iter() {
+
+        // Get i -> j bonds.
+        let total_bonds_j: u64 = bond_totals[ *uid_j as usize ]; // Range( 0, total_emission );
+        if total_bonds_j == 0 { continue; } // No bond ownership in this neuron.
+        if *bonds_ij == 0 { continue; } // No need to distribute dividends for zero bonds.
+
+        // Compute bond fraction.
+        let bond_fraction_ij: I65F63 = I65F63::from_num( *bonds_ij ) / I65F63::from_num( total_bonds_j ); // Range( 0, 1 );
+
+        // Compute incentive owenership fraction.
+        let mut ownership_ji: I65F63 = one - self_ownership; // Range( 0, 1 );
+        ownership_ji = ownership_ji * bond_fraction_ij; // Range( 0, 1 );
+
+        // Compute dividends
+        dividends_i += incentive[ *uid_j as usize ] * ownership_ji; // Range( 0, 1 );
+        sparse_bonds_row.push( (*uid_j, *bonds_ij) );
+    }
+    ( dividends_i, sparse_bonds_row )
+}
+
+/// Normalizes dividends to sum to one and splits the block emission along them.
+/// Returns the emission per neuron and its sum.
+pub fn distribute_emission( dividends: &mut [I65F63], total_dividends: I65F63, block_emission: I65F63 ) -> ( Vec<u64>, u64 ) {
+    let mut total_emission: u64 = 0;
+    let mut emission: Vec<u64> = vec![ 0; dividends.len() ];
+    if total_dividends != 0 {
+        for ( i, dividends_i ) in dividends.iter_mut().enumerate() {
+            *dividends_i = *dividends_i / total_dividends;
+            let emission_i: u64 = (block_emission * *dividends_i).to_num::<u64>();
+            emission[ i ] = emission_i;
+            total_emission += emission_i;
+        }
+    }
+    ( emission, total_emission )
+}
+
+/// Runs one epoch of the incentive mechanism over the graph.
+pub fn epoch( params: &EpochParams, inputs: EpochInputs ) -> EpochOutput {
+    let n: usize = inputs.stake.len();
+    let block_emission: I65F63 = I65F63::from_num( params.emission );
+    let bonds_moving_average: I65F63 = I65F63::from_num( params.bonds_moving_average ) / I65F63::from_num( 1_000_000 );
+    let self_ownership: I65F63 = I65F63::from_num( 1.0 ) / I65F63::from_num( params.self_ownership );
+    let u64_max: I65F63 = I65F63::from_num( u64::MAX );
+    let to_u64 = |values: Vec<I65F63>| -> Vec<u64> { values.iter().map( |v| (*v * u64_max).to_num::<u64>() ).collect() };
+
+    // Priority.
+    let priority: Vec<u64> = inputs.priority.iter().zip( inputs.stake.iter() ).map( |(p, s)| next_priority( *p, *s ) ).collect();
+
+    // Stake normalized by activity.
+    let ( stake, total_normalized_active_stake ) = normalize_stake( &inputs.stake, &inputs.active );
+
+    // Ranks, trust and bond moving averages.
+    let mut bonds: Vec<Vec<(u32,u64)>> = inputs.bonds;
+    let mut bond_totals: Vec<u64> = vec![ 0; n ];
+    for bonds_i in bonds.iter_mut() {
+        bonds_i.sort_unstable_by_key( |(uid_j, _)| *uid_j );
+        for ( uid_j, bonds_ij ) in bonds_i.iter() {
+            bond_totals[ *uid_j as usize ] += *bonds_ij;
+        }
+    }
+    let mut accumulator = RankAccumulator::new( bond_totals, block_emission, bonds_moving_average );
+    for ( i, bonds_i ) in bonds.iter_mut().enumerate() {
+        accumulator.accumulate_row( i as u32, stake[ i ], inputs.active[ i ], &inputs.weights[ i ], bonds_i );
+    }
+    accumulator.normalize( total_normalized_active_stake );
+
+    // Consensus and incentive.
+    let ( consensus, incentive ) = consensus_and_incentive( &accumulator.ranks, &accumulator.trust, accumulator.total_ranks, accumulator.total_trust, params.rho, params.kappa );
+
+    // Dividends.
+    let mut total_dividends: I65F63 = I65F63::from_num( 0.0 );
+    let mut dividends: Vec<I65F63> = vec![ I65F63::from_num( 0.0 ); n ];
+    let mut sparse_bonds: Vec<Vec<(u32,u64)>> = vec![ vec![]; n ];
+    for ( i, bonds_i ) in bonds.iter().enumerate() {
+        let ( dividends_i, sparse_bonds_i ) = dividends_row( i as u32, &incentive, bonds_i, &accumulator.bond_totals, self_ownership );
+        dividends[ i ] = dividends_i;
+        total_dividends += dividends_i;
+        sparse_bonds[ i ] = sparse_bonds_i;
+    }
+
+    // Emission.
+    let ( emission, total_emission ) = distribute_emission( &mut dividends, total_dividends, block_emission );
+
+    EpochOutput {
+        priority,
+        ranks: to_u64( accumulator.ranks ),
+        trust: to_u64( accumulator.trust ),
+        consensus: to_u64( consensus ),
+        incentive: to_u64( incentive ),
+        dividends: to_u64( dividends ),
+        emission,
+        bonds: sparse_bonds,
+        total_emission,
+        total_bonds_purchased: accumulator.total_bonds_purchased,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::epoch::*;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    fn params( emission: u64 ) -> EpochParams {
+        EpochParams { emission, rho: 10, kappa: 2, bonds_moving_average: 500_000, self_ownership: 2 }
+    }
+
+    fn random_inputs( rng: &mut StdRng, n: usize ) -> EpochInputs {
+        let mut inputs = EpochInputs {
+            stake: (0..n).map( |_| rng.gen_range( 0, 1_000_000_000_000 ) ).collect(),
+            active: (0..n).map( |_| rng.gen_bool( 0.8 ) ).collect(),
+            priority: (0..n).map( |_| rng.gen_range( 0, 1_000 ) ).collect(),
+            weights: vec![ vec![]; n ],
+            bonds: vec![ vec![]; n ],
+        };
+        for i in 0..n {
+            for j in 0..n {
+                if rng.gen_bool( 0.5 ) {
+                    inputs.weights[ i ].push( (j as u32, rng.gen::<u32>()) );
+                }
+                if rng.gen_bool( 0.5 ) {
+                    inputs.bonds[ i ].push( (j as u32, rng.gen_range( 0, 1_000_000_000 )) );
+                }
+            }
+        }
+        inputs
+    }
+
+    // Relabels uid i as uid perm[i].
+    fn permute( inputs: &EpochInputs, perm: &[usize] ) -> EpochInputs {
+        let n = perm.len();
+        let mut permuted = EpochInputs {
+            stake: vec![ 0; n ],
+            active: vec![ false; n ],
+            priority: vec![ 0; n ],
+            weights: vec![ vec![]; n ],
+            bonds: vec![ vec![]; n ],
+        };
+        for i in 0..n {
+            permuted.stake[ perm[ i ] ] = inputs.stake[ i ];
+            permuted.active[ perm[ i ] ] = inputs.active[ i ];
+            permuted.priority[ perm[ i ] ] = inputs.priority[ i ];
+            permuted.weights[ perm[ i ] ] = inputs.weights[ i ].iter().map( |(j, w)| (perm[ *j as usize ] as u32, *w) ).collect();
+            permuted.bonds[ perm[ i ] ] = inputs.bonds[ i ].iter().map( |(j, b)| (perm[ *j as usize ] as u32, *b) ).collect();
+        }
+        permuted
+    }
+
+    #[test]
+    fn epoch_empty_graph() {
+        let output = epoch( &params( 1_000_000_000 ), EpochInputs::default() );
+        assert_eq!( output, EpochOutput::default() );
+    }
+
+    #[test]
+    fn epoch_without_weights_emits_nothing() {
+        let inputs = EpochInputs {
+            stake: vec![ 1_000_000_000, 1_000_000_000 ],
+            active: vec![ true, true ],
+            priority: vec![ 0, 0 ],
+            weights: vec![ vec![], vec![] ],
+            bonds: vec![ vec![], vec![] ],
+        };
+        let output = epoch( &params( 1_000_000_000 ), inputs );
+        assert_eq!( output.ranks, vec![ 0, 0 ] );
+        assert_eq!( output.emission, vec![ 0, 0 ] );
+        assert_eq!( output.total_emission, 0 );
+        assert_eq!( output.bonds, vec![ vec![], vec![] ] );
+    }
+
+    #[test]
+    fn epoch_mutual_weights_split_emission() {
+        let inputs = EpochInputs {
+            stake: vec![ 1_000_000_000, 1_000_000_000 ],
+            active: vec![ true, true ],
+            priority: vec![ 0, 0 ],
+            weights: vec![ vec![ (1, u32::MAX) ], vec![ (0, u32::MAX) ] ],
+            bonds: vec![ vec![], vec![] ],
+        };
+        let output = epoch( &params( 1_000_000_000 ), inputs );
+        assert_eq!( output.ranks[ 0 ], output.ranks[ 1 ] );
+        assert_eq!( output.ranks[ 0 ], u64::MAX / 2 );
+        assert_eq!( output.emission[ 0 ], output.emission[ 1 ] );
+        assert!( output.total_emission <= 1_000_000_000 );
+        assert!( output.total_emission >= 1_000_000_000 - 2 );
+        // Half the bond increment is kept by the moving average.
+        assert_eq!( output.bonds, vec![ vec![ (1, 250_000_000) ], vec![ (0, 250_000_000) ] ] );
+        assert_eq!( output.priority, vec![ next_priority( 0, 1_000_000_000 ); 2 ] );
+    }
+
+    #[test]
+    fn epoch_inactive_neurons_do_not_rank() {
+        let inputs = EpochInputs {
+            stake: vec![ 1_000_000_000, 1_000_000_000, 1_000_000_000 ],
+            active: vec![ true, false, true ],
+            priority: vec![ 0, 0, 0 ],
+            weights: vec![ vec![ (2, u32::MAX) ], vec![ (0, u32::MAX) ], vec![ (0, u32::MAX) ] ],
+            bonds: vec![ vec![]; 3 ],
+        };
+        let output = epoch( &params( 1_000_000_000 ), inputs );
+        assert_eq!( output.ranks[ 0 ], output.ranks[ 2 ] );
+        assert_eq!( output.ranks[ 1 ], 0 );
+        assert_eq!( output.bonds[ 1 ], vec![] );
+    }
+
+    #[test]
+    fn epoch_ignores_self_weight() {
+        let inputs = EpochInputs {
+            stake: vec![ 1_000_000_000 ],
+            active: vec![ true ],
+            priority: vec![ 0 ],
+            weights: vec![ vec![ (0, u32::MAX) ] ],
+            bonds: vec![ vec![] ],
+        };
+        let output = epoch( &params( 1_000_000_000 ), inputs );
+        assert_eq!( output.ranks, vec![ 0 ] );
+        assert_eq!( output.total_emission, 0 );
+    }
+
+    #[test]
+    fn accumulate_row_updates_sparse_bonds() {
+        let mut accumulator = RankAccumulator::new( vec![ 0, 100, 0, 0 ], I65F63::from_num( 1_000 ), I65F63::from_num( 0.5 ) );
+        let mut bonds_i: Vec<(u32,u64)> = vec![ (1, 100), (3, 0) ];
+        accumulator.accumulate_row( 0, I65F63::from_num( 1 ), true, &[ (2, u32::MAX), (1, u32::MAX) ], &mut bonds_i );
+        // New entries are inserted in uid order, entries without a weight are left as is.
+        assert_eq!( bonds_i, vec![ (1, 550), (2, 500), (3, 0) ] );
+        assert_eq!( accumulator.bond_totals, vec![ 0, 550, 500, 0 ] );
+    }
+
+    #[test]
+    fn is_active_respects_cutoff() {
+        assert!( is_active( 10, 10, 5 ) );
+        assert!( is_active( 14, 10, 5 ) );
+        assert!( !is_active( 15, 10, 5 ) );
+    }
+
+    #[test]
+    fn property_is_active_after_block() {
+        let mut rng = StdRng::seed_from_u64( 4 );
+        for _ in 0..100 {
+            let block: u64 = rng.gen_range( 0, u64::MAX / 2 );
+            let last_update: u64 = rng.gen_range( block, u64::MAX );
+            let activity_cutoff: u64 = rng.gen_range( 1, u64::MAX );
+            assert!( is_active( block, last_update, activity_cutoff ) );
+            assert!( !is_active( block, last_update, 0 ) );
+        }
+    }
+
+    #[test]
+    fn property_emission_bounded_by_block_emission() {
+        let mut rng = StdRng::seed_from_u64( 0 );
+        for _ in 0..100 {
+            let n: usize = rng.gen_range( 1, 16 );
+            let emission: u64 = rng.gen_range( 0, 1_000_000_000_000 );
+            let output = epoch( &params( emission ), random_inputs( &mut rng, n ) );
+            assert_eq!( output.total_emission, output.emission.iter().sum::<u64>() );
+            assert!( output.total_emission <= emission );
+        }
+    }
+
+    #[test]
+    fn property_incentive_sums_to_one() {
+        let mut rng = StdRng::seed_from_u64( 1 );
+        for _ in 0..100 {
+            let n: usize = rng.gen_range( 1, 16 );
+            let output = epoch( &params( 1_000_000_000 ), random_inputs( &mut rng, n ) );
+            let total_incentive: u128 = output.incentive.iter().map( |i| *i as u128 ).sum();
+            if output.ranks.iter().all( |r| *r == 0 ) {
+                assert_eq!( total_incentive, 0 );
+            } else {
+                // Each entry is rounded down on its own, losing at most a few units.
+                assert!( total_incentive <= u64::MAX as u128 );
+                assert!( total_incentive >= u64::MAX as u128 - n as u128 * 4 );
+            }
+        }
+    }
+
+    #[test]
+    fn property_zero_stake_emits_nothing() {
+        let mut rng = StdRng::seed_from_u64( 2 );
+        for _ in 0..100 {
+            let n: usize = rng.gen_range( 1, 16 );
+            let mut inputs = random_inputs( &mut rng, n );
+            inputs.stake = vec![ 0; n ];
+            let output = epoch( &params( 1_000_000_000 ), inputs );
+            assert_eq!( output.ranks, vec![ 0; n ] );
+            assert_eq!( output.total_emission, 0 );
+        }
+    }
+
+    #[test]
+    fn property_permutation_invariant() {
+        let mut rng = StdRng::seed_from_u64( 3 );
+        for _ in 0..100 {
+            let n: usize = rng.gen_range( 1, 16 );
+            let inputs = random_inputs( &mut rng, n );
+            let mut perm: Vec<usize> = (0..n).collect();
+            for i in (1..n).rev() {
+                perm.swap( i, rng.gen_range( 0, i + 1 ) );
+            }
+            let output = epoch( &params( 1_000_000_000 ), inputs.clone() );
+            let permuted_output = epoch( &params( 1_000_000_000 ), permute( &inputs, &perm ) );
+            for i in 0..n {
+                assert_eq!( output.ranks[ i ], permuted_output.ranks[ perm[ i ] ] );
+                assert_eq!( output.trust[ i ], permuted_output.trust[ perm[ i ] ] );
+                assert_eq!( output.incentive[ i ], permuted_output.incentive[ perm[ i ] ] );
+                assert_eq!( output.emission[ i ], permuted_output.emission[ perm[ i ] ] );
+            }
+            assert_eq!( output.total_emission, permuted_output.total_emission );
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use super::*;
+use sp_runtime::transaction_validity::InvalidTransaction;
+use sp_runtime::DispatchError;
+
+impl<T: Config> Pallet<T> {
+
+    /// Returns the transaction fee in rao per byte for calls of call_type. Without a fee set,
+    /// registrations, which are paid for with proof of work, and calls outside this pallet, which
+    /// pay through the transaction payment pallet, are free. All other calls pay InitialTransactionByteFee.
+    pub fn get_transaction_byte_fee( call_type: &CallType ) -> u64 {
+        match TransactionByteFee::<T>::get( call_type ) {
+            Some( transaction_byte_fee ) => transaction_byte_fee,
+            None => match call_type {
+                CallType::Register | CallType::Other => 0,
+                _ => T::InitialTransactionByteFee::get(),
+            }
+        }
+    }
+
+    /// Returns the transaction fee in rao for a call of call_type, len bytes long.
+    pub fn calculate_transaction_fee( call_type: &CallType, len: u64 ) -> u64 {
+        len.saturating_mul( Self::get_transaction_byte_fee( call_type ) )
+    }
+
+    /// Returns the fee who pays for a call, zero while who is a neuron hotkey with fee-less calls left.
+    pub fn get_transaction_fee( who: &T::AccountId, call_type: &CallType, len: u64 ) -> u64 {
+        let transaction_fee: u64 = Self::calculate_transaction_fee( call_type, len );
+        if transaction_fee == 0 || Self::get_feeless_uid( who ).is_some() {
+            return 0;
+        }
+        transaction_fee
+    }
+
+    /// Returns true if who can pay the fee of a call, without charging it.
+    pub fn can_pay_transaction_fee( who: &T::AccountId, call_type: &CallType, len: u64 ) -> bool {
+        let transaction_fee: u64 = Self::get_transaction_fee( who, call_type, len );
+        if transaction_fee == 0 {
+            return true;
+        }
+        match Self::u64_to_balance( transaction_fee ) {
+            Some( transaction_fee_as_balance ) => Self::can_remove_balance_from_coldkey_account( who, transaction_fee_as_balance ),
+            None => false,
+        }
+    }
+
+    /// Charges who for a call. Returns the fee withdrawn from its balance and, for a neuron hotkey
+    /// with fee-less calls left which uses up one of them instead, the uid of the neuron.
+    pub fn charge_transaction_fee( who: &T::AccountId, call_type: &CallType, len: u64 ) -> Result<( u64, Option<u32> ), TransactionValidityError> {
+        let transaction_fee: u64 = Self::calculate_transaction_fee( call_type, len );
+        if transaction_fee == 0 {
+            return Ok( ( 0, None ) );
+        }
+        if let Some( uid ) = Self::get_feeless_uid( who ) {
+            Self::use_feeless_call( uid );
+            return Ok( ( 0, Some( uid ) ) );
+        }
+        let transaction_fee_as_balance = Self::u64_to_balance( transaction_fee ).ok_or( InvalidTransaction::Payment )?;
+        if !Self::remove_balance_from_coldkey_account( who, transaction_fee_as_balance ) {
+            return Err( InvalidTransaction::Payment.into() );
+        }
+        Ok( ( transaction_fee, None ) )
+    }
+
+    /// Settles the fee charged for a call once it is dispatched. The fee is burnt when the call
+    /// succeeded or failed on an avoidable error, see fn is_avoidable_error, and refunded to who
+    /// when it failed otherwise. A fee-less call used by a failed call is given back to the
+    /// neuron the same way.
+    pub fn settle_transaction_fee( who: &T::AccountId, transaction_fee: u64, feeless_uid: Option<u32>, result: &dispatch::DispatchResult ) {
+        let burn: bool = match result {
+            Ok( () ) => true,
+            Err( error ) => Self::is_avoidable_error( error ),
+        };
+        if let ( Some( uid ), false ) = ( feeless_uid, burn ) {
+            Self::restore_feeless_call( uid );
+        }
+        if transaction_fee == 0 {
+            return;
+        }
+        if burn {
+            // The withdrawal already reduced the currency issuance.
+            TotalIssuance::<T>::mutate( |val| *val = val.saturating_sub( transaction_fee ) );
+        } else if let Some( transaction_fee_as_balance ) = Self::u64_to_balance( transaction_fee ) {
+            Self::add_balance_to_coldkey_account( who, transaction_fee_as_balance );
+        }
+    }
+
+    /// Returns true if the signer could have checked for error before sending the call. These are
+    /// the errors the pool rejects calls for, a call failing on one of them keeps its fee burnt.
+    pub fn is_avoidable_error( error: &DispatchError ) -> bool {
+        *error == Error::<T>::TxRateLimitExceeded.into()
+    }
+
+    /// Returns the uid of the neuron behind hotkey if it has fee-less calls left in the current quota interval.
+    pub fn get_feeless_uid( hotkey: &T::AccountId ) -> Option<u32> {
+        if !Hotkeys::<T>::contains_key( hotkey ) {
+            return None;
+        }
+        let uid: u32 = Hotkeys::<T>::get( hotkey );
+        let ( interval, calls ) = FeelessCalls::<T>::get( uid );
+        let used: u64 = if interval == Self::get_current_feeless_quota_interval() { calls } else { 0 };
+        if used < Self::get_feeless_quota() { Some( uid ) } else { None }
+    }
+
+    /// Uses up one fee-less call of the neuron in the current quota interval.
+    pub fn use_feeless_call( uid: u32 ) {
+        let current_interval: u64 = Self::get_current_feeless_quota_interval();
+        FeelessCalls::<T>::mutate( uid, |( interval, calls )| {
+            if *interval != current_interval {
+                *interval = current_interval;
+                *calls = 0;
+            }
+            *calls += 1;
+        });
+    }
+
+    /// Gives one fee-less call used in the current quota interval back to the neuron.
+    pub fn restore_feeless_call( uid: u32 ) {
+        let current_interval: u64 = Self::get_current_feeless_quota_interval();
+        FeelessCalls::<T>::mutate( uid, |( interval, calls )| {
+            if *interval == current_interval {
+                *calls = calls.saturating_sub( 1 );
+            }
+        });
+    }
+
+    /// Returns the index of the current quota interval. With an interval of zero the quota never resets.
+    pub fn get_current_feeless_quota_interval() -> u64 {
+        let feeless_quota_interval: u64 = Self::get_feeless_quota_interval();
+        if feeless_quota_interval == 0 {
+            return 0;
+        }
+        Self::get_current_block_as_u64() / feeless_quota_interval
+    }
+}
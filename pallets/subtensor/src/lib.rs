@@ -37,7 +37,8 @@ use sp_runtime::{
 	},
 	transaction_validity::{
         TransactionValidityError, 
-		TransactionValidity
+		TransactionValidity,
+		InvalidTransaction
     }
 };
 use sp_std::vec::Vec;
@@ -52,6 +53,27 @@ mod staking;
 mod serving;
 mod step;
 mod registration;
+mod coldkey_swap;
+mod delegation;
+mod fees;
+mod rate_limiting;
+mod metagraph;
+pub mod migrations;
+pub mod epoch;
+pub mod default_weights;
+pub use default_weights::WeightInfo;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+// The benchmark test suite runs the benchmarks against the mock of the integration tests,
+// which names this crate pallet_subtensor.
+#[cfg(all(test, feature = "runtime-benchmarks"))]
+extern crate self as pallet_subtensor;
+#[cfg(all(test, feature = "runtime-benchmarks"))]
+#[allow(dead_code)]
+#[path = "../tests/mock.rs"]
+mod mock;
 
 #[frame_support::pallet]
 pub mod pallet {
@@ -62,6 +84,9 @@ pub mod pallet {
 	use sp_std::vec;
 	use sp_std::convert::TryInto;
 	use frame_support::IterableStorageMap;
+	#[cfg(feature = "std")]
+	use serde::{Serialize, Deserialize};
+	use super::CallType;
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
@@ -79,8 +104,8 @@ pub mod pallet {
 		/// --- Currency type that will be used to place deposits on neurons
 		type Currency: Currency<Self::AccountId> + Send + Sync;
 		
-		/// --- The transaction fee in RAO per byte
-		type TransactionByteFee: Get<BalanceOf<Self>>;
+		/// --- Weight information for the extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
 
 		/// Debug is on
 		#[pallet::constant]
@@ -142,6 +167,15 @@ pub mod pallet {
 		#[pallet::constant]
 		type InitialBlocksPerStep: Get<u64>;
 
+		/// Rows of the mechanism step run per block, 0 runs the whole step in one block.
+		#[pallet::constant]
+		type InitialStepChunkSize: Get<u64>;
+
+		/// Weight the mechanism step may consume in on_initialize. A step whose worst case weight
+		/// exceeds it is run in chunks that fit, whatever the step chunk size.
+		#[pallet::constant]
+		type MaxStepWeight: Get<Weight>;
+
 		/// Blocks per era.
 		#[pallet::constant]
 		type InitialBondsMovingAverage: Get<u64>;
@@ -181,6 +215,38 @@ pub mod pallet {
 		/// Initial target registrations per interval.
 		#[pallet::constant]
 		type InitialTargetRegistrationsPerInterval: Get<u64>;
+
+		/// Initial number of blocks between a coldkey swap announcement and its finalization.
+		#[pallet::constant]
+		type InitialColdkeySwapDelay: Get<u64>;
+
+		/// Initial number of blocks removed stake stays unbonding before it can be withdrawn.
+		#[pallet::constant]
+		type InitialUnbondingPeriod: Get<u64>;
+
+		/// Maximum number of unbonding chunks a coldkey can have queued at once.
+		#[pallet::constant]
+		type MaxUnbondingChunks: Get<u32>;
+
+		/// Initial transaction fee in rao per byte, for call types without a fee set.
+		#[pallet::constant]
+		type InitialTransactionByteFee: Get<u64>;
+
+		/// Initial number of fee-less calls each neuron hotkey has per quota interval.
+		#[pallet::constant]
+		type InitialFeelessQuota: Get<u64>;
+
+		/// Initial number of blocks per fee-less quota interval.
+		#[pallet::constant]
+		type InitialFeelessQuotaInterval: Get<u64>;
+
+		/// Initial number of blocks a hotkey has to wait between two set_weights calls.
+		#[pallet::constant]
+		type InitialWeightsRateLimit: Get<u64>;
+
+		/// Initial number of blocks a hotkey has to wait between two serve_axon calls.
+		#[pallet::constant]
+		type InitialServingRateLimit: Get<u64>;
 	}
 
 	/// ************************************************************
@@ -190,13 +256,16 @@ pub mod pallet {
 	pub type AccountIdOf<T> = <T as frame_system::Config>::AccountId;
 	pub type NeuronMetadataOf<T> = NeuronMetadata<AccountIdOf<T>>;
 	pub type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
-    #[derive(Encode, Decode, Default)]
+    #[derive(Encode, Decode, Default, Clone, PartialEq, RuntimeDebug)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
     pub struct NeuronMetadata<AccountId> {
 
 		/// ---- The endpoint's code version.
         pub version: u32,
 
         /// ---- The endpoint's u128 encoded ip address of type v6 or v4.
+		#[cfg_attr(feature = "std", serde(with = "crate::serde_u128"))]
         pub ip: u128,
 
         /// ---- The endpoint's u16 encoded port.
@@ -258,13 +327,271 @@ pub mod pallet {
 
 		/// ---- The associated emission last block for this account.
 		pub emission: u64,
+    }
 
-		/// ---- The associated bond ownership.
-		pub bonds: Vec<(u32,u64)>,
+	/// ---- A neuron registered at genesis, see GenesisConfig.
+	pub type GenesisNeuronOf<T> = GenesisNeuron<AccountIdOf<T>>;
+	#[derive(Encode, Decode, Default, Clone, PartialEq, RuntimeDebug)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+	pub struct GenesisNeuron<AccountId> {
 
-		/// ---- The associated weights ownership.
-		pub weights: Vec<(u32,u32)>,
-    }
+		/// ---- The hotkey the neuron is registered under.
+		pub hotkey: AccountId,
+
+		/// ---- The coldkey owning the neuron.
+		pub coldkey: AccountId,
+
+		/// ---- Stake minted onto the neuron.
+		pub stake: u64,
+
+		/// ---- The endpoint's code version.
+		pub version: u32,
+
+		/// ---- The endpoint's u128 encoded ip address of type v6 or v4.
+		#[cfg_attr(feature = "std", serde(with = "crate::serde_u128"))]
+		pub ip: u128,
+
+		/// ---- The endpoint's u16 encoded port.
+		pub port: u16,
+
+		/// ---- The endpoint's ip type, 4 for ipv4 and 6 for ipv6.
+		pub ip_type: u8,
+
+		/// ---- The endpoint's modality.
+		pub modality: u8,
+
+		/// ---- Weights as ( uid, weight ) pairs, normalized as in set_weights. Empty keeps the self weight.
+		pub weights: Vec<(u32, u32)>,
+	}
+
+	/// ---- Storage layout versions of this pallet, used to gate migrations.
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+	pub enum Releases {
+		/// ---- Initial layout.
+		V1_0_0,
+		/// ---- Adds the ColdkeyUids reverse index.
+		V2_0_0,
+		/// ---- Moves weights and bonds out of NeuronMetadata into the Weights and Bonds maps.
+		V3_0_0,
+	}
+	impl Default for Releases {
+		fn default() -> Self {
+			Releases::V1_0_0
+		}
+	}
+
+	/// ---- The values a mechanism step writes, computed without writing them.
+	/// Vectors are indexed by uid.
+	#[derive(Encode, Decode, Default, Clone, PartialEq, RuntimeDebug)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+	pub struct MechanismStepResult {
+
+		/// ---- Block number the step was computed at.
+		pub block: u64,
+
+		/// ---- Activity flags after the step.
+		pub active: Vec<u32>,
+
+		/// ---- Transaction priorities after the step.
+		pub priority: Vec<u64>,
+
+		/// ---- Stake after the emission is paid out, without the rewards paid into accounts.
+		pub stake: Vec<u64>,
+
+		/// ---- Ranks, trust, consensus, incentive and dividends scaled to u64::MAX.
+		pub ranks: Vec<u64>,
+		pub trust: Vec<u64>,
+		pub consensus: Vec<u64>,
+		pub incentive: Vec<u64>,
+		pub dividends: Vec<u64>,
+
+		/// ---- Emission each neuron receives this step.
+		pub emission: Vec<u64>,
+
+		/// ---- Sparse bond rows after the step.
+		pub bonds: Vec<Vec<(u32,u64)>>,
+
+		/// ---- Change of each bond entry ( uid_j, next_bonds_ij - prev_bonds_ij ), zero changes are omitted.
+		pub bond_deltas: Vec<Vec<(u32,i128)>>,
+
+		/// ---- Uids pruned since the last step, the step drops them from NeuronsToPruneAtNextEpoch.
+		pub pruned_uids: Vec<u32>,
+
+		/// ---- Sum of the emission vector.
+		pub total_emission: u64,
+
+		/// ---- Bonds purchased this step.
+		pub total_bonds_purchased: u64,
+	}
+
+	/// ---- Phases of a mechanism step split across blocks. Each phase walks the uids in chunks.
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+	pub enum StepPhase {
+		/// ---- Sums the active stake and the bonds held in each uid.
+		Totals,
+		/// ---- Accumulates ranks and trust and moves the bonds towards the weights.
+		Ranks,
+		/// ---- Computes dividends from the finalized incentive.
+		Dividends,
+		/// ---- Writes the results back and distributes the emission.
+		Emission,
+	}
+	impl Default for StepPhase {
+		fn default() -> Self {
+			StepPhase::Totals
+		}
+	}
+
+	/// ---- Where the emission of a neuron goes.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub enum RewardDestination<AccountId> {
+		/// ---- Added onto the stake of the neuron, compounding it.
+		Stake,
+		/// ---- Paid into the free balance of the coldkey owning the neuron.
+		Coldkey,
+		/// ---- Paid into the free balance of the given account.
+		Account(AccountId),
+	}
+	impl<AccountId> Default for RewardDestination<AccountId> {
+		fn default() -> Self {
+			RewardDestination::Stake
+		}
+	}
+	pub type RewardDestinationOf<T> = RewardDestination<AccountIdOf<T>>;
+
+	/// ---- State of a mechanism step split across blocks. Vectors are indexed by uid,
+	/// fixed point values are held as the bits of their I65F63.
+	#[derive(Encode, Decode, Default, Clone, PartialEq, RuntimeDebug)]
+	pub struct PendingStepState {
+
+		/// ---- Phase being run and the next uid it runs on.
+		pub phase: StepPhase,
+		pub cursor: u32,
+
+		/// ---- Number of neurons when the step started, uids registered later are not part of it.
+		pub n: u32,
+
+		/// ---- Block number the step started at.
+		pub block: u64,
+
+		/// ---- Tokens distributed by this step.
+		pub emission_this_step: u64,
+
+		/// ---- Hyperparameters read when the step started.
+		pub rho: u64,
+		pub kappa: u64,
+		pub bonds_moving_average: u64,
+		pub self_ownership: u64,
+
+		/// ---- Stake and activity read in the Totals phase.
+		pub stake: Vec<u64>,
+		pub active: Vec<bool>,
+		pub total_active_stake: i128,
+		pub total_normalized_active_stake: i128,
+
+		/// ---- Bonds held in each uid.
+		pub bond_totals: Vec<u64>,
+
+		/// ---- Rank accumulator, normalized at the end of the Ranks phase.
+		pub ranks: Vec<i128>,
+		pub trust: Vec<i128>,
+		pub total_ranks: i128,
+		pub total_trust: i128,
+		pub total_bonds_purchased: u64,
+
+		/// ---- Consensus and incentive computed at the end of the Ranks phase.
+		pub consensus: Vec<i128>,
+		pub incentive: Vec<i128>,
+
+		/// ---- Dividends, normalized at the end of the Dividends phase.
+		pub dividends: Vec<i128>,
+		pub total_dividends: i128,
+
+		/// ---- Emission per uid and the amount paid out so far in the Emission phase,
+		/// and the part of it added onto stake.
+		pub emission: Vec<u64>,
+		pub total_emission: u64,
+		pub total_restaked: u64,
+	}
+
+	/// ---- A column oriented view over a range of neurons. Each NeuronMetadata
+	/// field is held as a column, the i-th entry of every column belongs to uids[i].
+	pub type MetagraphOf<T> = Metagraph<AccountIdOf<T>>;
+	#[derive(Encode, Decode, Default, Clone, PartialEq, RuntimeDebug)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+	pub struct Metagraph<AccountId> {
+
+		/// ---- Total number of neurons in the graph, regardless of the requested range.
+		pub n: u32,
+
+		/// ---- Block number at which the metagraph was read.
+		pub block: u64,
+
+		/// ---- Neuron uids, one per row.
+		pub uids: Vec<u32>,
+
+		/// ---- Endpoint code versions.
+		pub versions: Vec<u32>,
+
+		/// ---- Endpoint ip addresses, serialized as strings since they do not fit a json number.
+		#[cfg_attr(feature = "std", serde(with = "crate::serde_u128::vec"))]
+		pub ips: Vec<u128>,
+
+		/// ---- Endpoint ports.
+		pub ports: Vec<u16>,
+
+		/// ---- Endpoint ip types, 4 for ipv4 and 6 for ipv6.
+		pub ip_types: Vec<u8>,
+
+		/// ---- Neuron modalities.
+		pub modalities: Vec<u8>,
+
+		/// ---- Hotkey accounts.
+		pub hotkeys: Vec<AccountId>,
+
+		/// ---- Coldkey accounts.
+		pub coldkeys: Vec<AccountId>,
+
+		/// ---- Activity flags.
+		pub active: Vec<u32>,
+
+		/// ---- Block numbers of the last chain update.
+		pub last_update: Vec<u64>,
+
+		/// ---- Transaction priorities.
+		pub priority: Vec<u64>,
+
+		/// ---- Stake per neuron.
+		pub stake: Vec<u64>,
+
+		/// ---- Ranks per neuron.
+		pub ranks: Vec<u64>,
+
+		/// ---- Trust per neuron.
+		pub trust: Vec<u64>,
+
+		/// ---- Consensus per neuron.
+		pub consensus: Vec<u64>,
+
+		/// ---- Incentive per neuron.
+		pub incentive: Vec<u64>,
+
+		/// ---- Dividends per neuron.
+		pub dividends: Vec<u64>,
+
+		/// ---- Emission last step per neuron.
+		pub emission: Vec<u64>,
+
+		/// ---- Sparse bond rows.
+		pub bonds: Vec<Vec<(u32,u64)>>,
+
+		/// ---- Sparse weight rows.
+		pub weights: Vec<Vec<(u32,u32)>>,
+	}
 
 	/// ************************************************************
 	///	*---- Storage Objects
@@ -450,6 +777,35 @@ pub mod pallet {
 		DefaultBlocksPerStep<T>
 	>;
 
+	#[pallet::type_value] 
+	pub fn DefaultStepChunkSize<T: Config>() -> u64 { T::InitialStepChunkSize::get() }
+	#[pallet::storage]
+	pub type StepChunkSize<T> = StorageValue<
+		_, 
+		u64, 
+		ValueQuery,
+		DefaultStepChunkSize<T>
+	>;
+
+	/// ---- The mechanism step being run across blocks, if any.
+	#[pallet::storage]
+	pub(super) type PendingStep<T:Config> = StorageValue<
+		_, 
+		PendingStepState, 
+		OptionQuery
+	>;
+
+	/// ---- Maps from uid to its bond row as computed by the pending step, written back
+	/// into Bonds in the Emission phase.
+	#[pallet::storage]
+    pub(super) type PendingBonds<T:Config> = StorageMap<
+		_, 
+		Identity, 
+		u32, 
+		Vec<(u32,u64)>, 
+		ValueQuery
+	>;
+
 	#[pallet::type_value] 
 	pub fn DefaultBondsMovingAverage<T: Config>() -> u64 { T::InitialBondsMovingAverage::get() }
 	#[pallet::storage]
@@ -529,6 +885,90 @@ pub mod pallet {
 		ValueQuery
 	>;
 
+	#[pallet::type_value] 
+	pub fn DefaultColdkeySwapDelay<T: Config>() -> u64 { T::InitialColdkeySwapDelay::get() }
+	#[pallet::storage]
+	pub type ColdkeySwapDelay<T> = StorageValue<
+		_, 
+		u64, 
+		ValueQuery,
+		DefaultColdkeySwapDelay<T>
+	>;
+
+	#[pallet::type_value] 
+	pub fn DefaultUnbondingPeriod<T: Config>() -> u64 { T::InitialUnbondingPeriod::get() }
+	#[pallet::storage]
+	pub type UnbondingPeriod<T> = StorageValue<
+		_, 
+		u64, 
+		ValueQuery,
+		DefaultUnbondingPeriod<T>
+	>;
+
+	/// ---- Transaction fee in rao per byte for each call type, see fn get_transaction_byte_fee
+	/// for the call types without an entry.
+	#[pallet::storage]
+	pub type TransactionByteFee<T> = StorageMap<
+		_, 
+		Twox64Concat, 
+		CallType, 
+		u64, 
+		OptionQuery
+	>;
+
+	#[pallet::type_value] 
+	pub fn DefaultFeelessQuota<T: Config>() -> u64 { T::InitialFeelessQuota::get() }
+	#[pallet::storage]
+	pub type FeelessQuota<T> = StorageValue<
+		_, 
+		u64, 
+		ValueQuery,
+		DefaultFeelessQuota<T>
+	>;
+
+	#[pallet::type_value] 
+	pub fn DefaultFeelessQuotaInterval<T: Config>() -> u64 { T::InitialFeelessQuotaInterval::get() }
+	#[pallet::storage]
+	pub type FeelessQuotaInterval<T> = StorageValue<
+		_, 
+		u64, 
+		ValueQuery,
+		DefaultFeelessQuotaInterval<T>
+	>;
+
+	/// ---- Maps from uid to ( quota interval, fee-less calls the neuron made in it ).
+	#[pallet::storage]
+	pub(super) type FeelessCalls<T> = StorageMap<
+		_, 
+		Identity, 
+		u32, 
+		(u64, u64), 
+		ValueQuery
+	>;
+
+	/// ---- Number of blocks a hotkey has to wait between two calls of each call type, see
+	/// fn get_tx_rate_limit for the call types without an entry.
+	#[pallet::storage]
+	pub type TxRateLimit<T> = StorageMap<
+		_, 
+		Twox64Concat, 
+		CallType, 
+		u64, 
+		OptionQuery
+	>;
+
+	/// ---- Maps from ( call type, hotkey ) to the block the hotkey last made a call of that type.
+	#[pallet::storage]
+	pub(super) type LastTxBlock<T:Config> = StorageDoubleMap<
+		_, 
+		Twox64Concat, 
+		CallType, 
+		Blake2_128Concat, 
+		T::AccountId, 
+		u64, 
+		OptionQuery
+	>;
+
 	#[pallet::storage]
 	pub type LastDifficultyAdjustmentBlock<T> = StorageValue<
 		_, 
@@ -580,7 +1020,29 @@ pub mod pallet {
 		ValueQuery
 	>;
 
-	/// ---- Maps from uid to uid as a set which we use to record uids to prune at next epoch.
+	/// ---- Maps from uid to the sparse weights ( uid_j, w_ij ) this neuron sets.
+	#[pallet::storage]
+	#[pallet::getter(fn weights)]
+    pub(super) type Weights<T:Config> = StorageMap<
+		_, 
+		Identity, 
+		u32, 
+		Vec<(u32,u32)>, 
+		ValueQuery
+	>;
+
+	/// ---- Maps from uid to the sparse bonds ( uid_j, b_ij ) this neuron owns.
+	#[pallet::storage]
+	#[pallet::getter(fn bonds)]
+    pub(super) type Bonds<T:Config> = StorageMap<
+		_, 
+		Identity, 
+		u32, 
+		Vec<(u32,u64)>, 
+		ValueQuery
+	>;
+
+	/// ---- Maps from uid to uid as a set which we use to record uids pruned since the last epoch.
 	#[pallet::storage]
 	#[pallet::getter(fn uid_to_prune)]
     pub(super) type NeuronsToPruneAtNextEpoch<T:Config> = StorageMap<
@@ -604,45 +1066,239 @@ pub mod pallet {
 		DefaultBlockAtRegistration<T>
 	>;
 
+	/// ---- Maps from coldkey to the uids it owns as a set. Kept in sync with
+	/// Neurons so that ownership lookups do not need to scan every neuron.
+	#[pallet::storage]
+    pub(super) type ColdkeyUids<T:Config> = StorageDoubleMap<
+		_, 
+		Blake2_128Concat, 
+		T::AccountId, 
+		Identity, 
+		u32, 
+		(), 
+		ValueQuery,
+	>;
+
+	/// ---- Maps from uid to where the emission of the neuron goes. Uids without an entry restake.
+	#[pallet::storage]
+	#[pallet::getter(fn reward_destination)]
+    pub(super) type RewardDestinations<T:Config> = StorageMap<
+		_, 
+		Identity, 
+		u32, 
+		RewardDestinationOf<T>, 
+		ValueQuery
+	>;
+
+	/// ---- Maps from a coldkey to the stake it removed which is still unbonding,
+	/// as ( amount, block from which it can be withdrawn ) chunks in the order they were removed.
+	#[pallet::storage]
+	#[pallet::getter(fn unbonding)]
+    pub(super) type Unbonding<T:Config> = StorageMap<
+		_, 
+		Blake2_128Concat, 
+		T::AccountId, 
+		Vec<(u64, u64)>, 
+		ValueQuery
+	>;
+
+	/// ---- Maps from the hotkey of a delegate to the percent of its emission its owner keeps.
+	/// Hotkeys in this map accept stake from any coldkey.
+	#[pallet::storage]
+	#[pallet::getter(fn delegate_take)]
+    pub(super) type DelegateTake<T:Config> = StorageMap<
+		_, 
+		Blake2_128Concat, 
+		T::AccountId, 
+		u64, 
+		OptionQuery
+	>;
+
+	/// ---- Maps from ( delegate hotkey, nominator coldkey ) to the stake the coldkey holds on the hotkey.
+	/// Keyed by hotkey first so the nominators of a delegate can be paid by prefix.
+	/// The stake held by the owning coldkey is not part of this map.
+	#[pallet::storage]
+	#[pallet::getter(fn nominator_stake)]
+    pub(super) type NominatorStake<T:Config> = StorageDoubleMap<
+		_, 
+		Blake2_128Concat, 
+		T::AccountId, 
+		Blake2_128Concat, 
+		T::AccountId, 
+		u64, 
+		ValueQuery
+	>;
+
+	/// ---- Maps from the hotkey of a delegate to the sum of its NominatorStake entries.
+	#[pallet::storage]
+    pub(super) type TotalNominatorStake<T:Config> = StorageMap<
+		_, 
+		Blake2_128Concat, 
+		T::AccountId, 
+		u64, 
+		ValueQuery
+	>;
+
+	/// ---- Maps from a coldkey to the coldkey it announced to hand its neurons over to,
+	/// and the block from which the swap can be finalized.
+	#[pallet::storage]
+	#[pallet::getter(fn coldkey_swap_announcements)]
+    pub(super) type ColdkeySwapAnnouncements<T:Config> = StorageMap<
+		_, 
+		Blake2_128Concat, 
+		T::AccountId, 
+		(T::AccountId, u64), 
+		ValueQuery
+	>;
+
+	/// ---- The storage layout version, new chains start at the latest release.
+	#[pallet::storage]
+    pub(super) type StorageVersion<T:Config> = StorageValue<
+		_, 
+		Releases, 
+		ValueQuery
+	>;
+
 
 	/// ************************************************************
 	///	-Genesis-Configuration
 	/// ************************************************************
-	/// ---- Genesis Configuration (Mostly used for testing.)
-    #[pallet::genesis_config]
-    pub struct GenesisConfig {
-        pub stake: Vec<(u64, u64)>,
-    }
+	/// ---- Genesis Configuration. Seeds the metagraph with registered neurons and overrides
+	/// hyperparameters, hyperparameters left as None keep their Initial* config value.
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// ---- Neurons registered at genesis, the i-th neuron gets uid i.
+		pub neurons: Vec<GenesisNeuronOf<T>>,
+
+		/// ---- Account the foundation distribution is paid into.
+		pub foundation_account: Option<T::AccountId>,
+
+		/// ---- Hyperparameter overrides, named after the storage values they set.
+		pub rho: Option<u64>,
+		pub kappa: Option<u64>,
+		pub incentive_pruning_denominator: Option<u64>,
+		pub stake_pruning_denominator: Option<u64>,
+		pub validator_epoch_len: Option<u64>,
+		pub validator_epochs_per_reset: Option<u64>,
+		pub validator_batch_size: Option<u64>,
+		pub validator_sequence_length: Option<u64>,
+		pub max_allowed_uids: Option<u64>,
+		pub min_allowed_weights: Option<u64>,
+		pub max_allowed_max_min_ratio: Option<u64>,
+		pub immunity_period: Option<u64>,
+		pub blocks_per_step: Option<u64>,
+		pub step_chunk_size: Option<u64>,
+		pub bonds_moving_average: Option<u64>,
+		pub difficulty: Option<u64>,
+		pub activity_cutoff: Option<u64>,
+		pub adjustment_interval: Option<u64>,
+		pub target_registrations_per_interval: Option<u64>,
+		pub max_registrations_per_block: Option<u64>,
+		pub foundation_distribution: Option<u64>,
+		pub coldkey_swap_delay: Option<u64>,
+		pub unbonding_period: Option<u64>,
+		pub feeless_quota: Option<u64>,
+		pub feeless_quota_interval: Option<u64>,
+	}
 
 	#[cfg(feature = "std")]
-	impl Default for GenesisConfig {
+	impl<T: Config> Default for GenesisConfig<T> {
 		fn default() -> Self {
 			Self {
-				stake: Default::default(),
+				neurons: Default::default(),
+				foundation_account: None,
+				rho: None,
+				kappa: None,
+				incentive_pruning_denominator: None,
+				stake_pruning_denominator: None,
+				validator_epoch_len: None,
+				validator_epochs_per_reset: None,
+				validator_batch_size: None,
+				validator_sequence_length: None,
+				max_allowed_uids: None,
+				min_allowed_weights: None,
+				max_allowed_max_min_ratio: None,
+				immunity_period: None,
+				blocks_per_step: None,
+				step_chunk_size: None,
+				bonds_moving_average: None,
+				difficulty: None,
+				activity_cutoff: None,
+				adjustment_interval: None,
+				target_registrations_per_interval: None,
+				max_registrations_per_block: None,
+				foundation_distribution: None,
+				coldkey_swap_delay: None,
+				unbonding_period: None,
+				feeless_quota: None,
+				feeless_quota_interval: None,
 			}
 		}
 	}
-    
-    #[pallet::genesis_build]
-    impl<T:Config> GenesisBuild<T> for GenesisConfig {
-        fn build(&self) {		
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			StorageVersion::<T>::put( Releases::V3_0_0 );
+
+			// --- Hyperparameters go first, the neurons below are checked against MaxAllowedUids.
+			if let Some( rho ) = self.rho { Rho::<T>::put( rho ); }
+			if let Some( kappa ) = self.kappa { Kappa::<T>::put( kappa ); }
+			if let Some( incentive_pruning_denominator ) = self.incentive_pruning_denominator { IncentivePruningDenominator::<T>::put( incentive_pruning_denominator ); }
+			if let Some( stake_pruning_denominator ) = self.stake_pruning_denominator { StakePruningDenominator::<T>::put( stake_pruning_denominator ); }
+			if let Some( validator_epoch_len ) = self.validator_epoch_len { ValidatorEpochLen::<T>::put( validator_epoch_len ); }
+			if let Some( validator_epochs_per_reset ) = self.validator_epochs_per_reset { ValidatorEpochsPerReset::<T>::put( validator_epochs_per_reset ); }
+			if let Some( validator_batch_size ) = self.validator_batch_size { ValidatorBatchSize::<T>::put( validator_batch_size ); }
+			if let Some( validator_sequence_length ) = self.validator_sequence_length { ValidatorSequenceLength::<T>::put( validator_sequence_length ); }
+			if let Some( max_allowed_uids ) = self.max_allowed_uids { MaxAllowedUids::<T>::put( max_allowed_uids ); }
+			if let Some( min_allowed_weights ) = self.min_allowed_weights { MinAllowedWeights::<T>::put( min_allowed_weights ); }
+			if let Some( max_allowed_max_min_ratio ) = self.max_allowed_max_min_ratio { MaxAllowedMaxMinRatio::<T>::put( max_allowed_max_min_ratio ); }
+			if let Some( immunity_period ) = self.immunity_period { ImmunityPeriod::<T>::put( immunity_period ); }
+			if let Some( blocks_per_step ) = self.blocks_per_step { BlocksPerStep::<T>::put( blocks_per_step ); }
+			if let Some( step_chunk_size ) = self.step_chunk_size { StepChunkSize::<T>::put( step_chunk_size ); }
+			if let Some( bonds_moving_average ) = self.bonds_moving_average { BondsMovingAverage::<T>::put( bonds_moving_average ); }
+			if let Some( difficulty ) = self.difficulty { Difficulty::<T>::put( difficulty ); }
+			if let Some( activity_cutoff ) = self.activity_cutoff { ActivityCutoff::<T>::put( activity_cutoff ); }
+			if let Some( adjustment_interval ) = self.adjustment_interval { AdjustmentInterval::<T>::put( adjustment_interval ); }
+			if let Some( target_registrations_per_interval ) = self.target_registrations_per_interval { TargetRegistrationsPerInterval::<T>::put( target_registrations_per_interval ); }
+			if let Some( max_registrations_per_block ) = self.max_registrations_per_block { MaxRegistrationsPerBlock::<T>::put( max_registrations_per_block ); }
+			if let Some( foundation_distribution ) = self.foundation_distribution { FoundationDistribution::<T>::put( foundation_distribution ); }
+			if let Some( coldkey_swap_delay ) = self.coldkey_swap_delay { ColdkeySwapDelay::<T>::put( coldkey_swap_delay ); }
+			if let Some( unbonding_period ) = self.unbonding_period { UnbondingPeriod::<T>::put( unbonding_period ); }
+			if let Some( feeless_quota ) = self.feeless_quota { FeelessQuota::<T>::put( feeless_quota ); }
+			if let Some( feeless_quota_interval ) = self.feeless_quota_interval { FeelessQuotaInterval::<T>::put( feeless_quota_interval ); }
+			if let Some( foundation_account ) = &self.foundation_account { FoundationAccount::<T>::put( foundation_account ); }
+
+			// --- Register the neurons, their stake is minted at genesis.
+			assert!( self.neurons.len() as u64 <= Pallet::<T>::get_max_allowed_uids(), "more genesis neurons than MaxAllowedUids" );
+			let mut total_stake: u64 = 0;
+			for neuron in self.neurons.iter() {
+				total_stake = total_stake.checked_add( neuron.stake ).expect( "genesis stake overflows u64" );
+				Pallet::<T>::register_genesis_neuron( neuron );
+			}
+
+			// --- Weights are set once every uid they point at exists.
+			for ( uid, neuron ) in self.neurons.iter().enumerate() {
+				Pallet::<T>::set_genesis_weights( uid as u32, &neuron.weights );
+			}
+			TotalStake::<T>::put( total_stake );
+			TotalIssuance::<T>::put( Pallet::<T>::get_total_issuance().saturating_add( total_stake ) );
 		}
 	}
 
-
 	#[cfg(feature = "std")]
-	impl GenesisConfig {
+	impl<T: Config> GenesisConfig<T> {
 		/// Direct implementation of `GenesisBuild::build_storage`.
 		///
 		/// Kept in order not to break dependency.
-		pub fn build_storage<T: Config>(&self) -> Result<sp_runtime::Storage, String> {
+		pub fn build_storage(&self) -> Result<sp_runtime::Storage, String> {
 			<Self as GenesisBuild<T>>::build_storage(self)
 		}
 
 		/// Direct implementation of `GenesisBuild::assimilate_storage`.
 		///
 		/// Kept in order not to break dependency.
-		pub fn assimilate_storage<T: Config>(
+		pub fn assimilate_storage(
 			&self,
 			storage: &mut sp_runtime::Storage
 		) -> Result<(), String> {
@@ -738,26 +1394,87 @@ pub mod pallet {
 
 		/// --- Event created when the immunity period has been set.
 		ImmunityPeriodSet(u64),
-	}
 
-	/// ************************************************************
-	///	-Errors
-	/// ************************************************************
-	// Errors inform users that something went wrong.
-	#[pallet::error]
-	pub enum Error<T> {
-        /// ---- Thrown when the user tries to serve an axon which is not of type
-	    /// 4 (IPv4) or 6 (IPv6).
-		InvalidIpType,
+		/// --- Event created when the mechanism step chunk size has been set.
+		StepChunkSizeSet(u64),
 
-		/// --- Thrown when an invalid IP address is passed to the serve function.
-		InvalidIpAddress,
+		/// --- Event created when a mechanism step pays the foundation account its share of the emission.
+		FoundationDistributed( T::AccountId, u64 ),
 
-		/// --- Thrown when an invalid modality attempted on serve.
-		/// Currently the chain only accepts modality TEXT = 0.
-		InvalidModality,
+		/// --- Event created when stake has been moved from the first
+		/// hotkey to the second, both under the same coldkey.
+		StakeMoved(T::AccountId, T::AccountId, u64),
 
-		/// ---- Thrown when the caller attempts to set the weight keys
+		/// --- Event created when stake has been transferred from the first
+		/// hotkey to the neuron of the second, which may belong to another coldkey.
+		StakeTransferred(T::AccountId, T::AccountId, u64),
+
+		/// --- Event created when a neuron's hotkey has been replaced by a new one.
+		HotkeySwapped(T::AccountId, T::AccountId),
+
+		/// --- Event created when a coldkey announces handing its neurons over to a new
+		/// coldkey, from the given block on.
+		ColdkeySwapAnnounced(T::AccountId, T::AccountId, u64),
+
+		/// --- Event created when the neurons of the first coldkey have been handed over to the second.
+		ColdkeySwapped(T::AccountId, T::AccountId),
+
+		/// --- Event created when a coldkey cancels its swap announcement.
+		ColdkeySwapCancelled(T::AccountId),
+
+		/// --- Event created when the coldkey swap delay has been set.
+		ColdkeySwapDelaySet(u64),
+
+		/// --- Event created when a hotkey opts in as a delegate or updates its take.
+		DelegateTakeSet(T::AccountId, u64),
+
+		/// --- Event created when removed stake has started unbonding for a coldkey,
+		/// until the given block.
+		StakeUnbonding(T::AccountId, u64, u64),
+
+		/// --- Event created when unbonded stake has been paid into the coldkey account.
+		UnbondedWithdrawn(T::AccountId, u64),
+
+		/// --- Event created when the unbonding period has been set.
+		UnbondingPeriodSet(u64),
+
+		/// --- Event created when the reward destination of a neuron has been set.
+		RewardDestinationSet(T::AccountId, RewardDestinationOf<T>),
+
+		/// --- Event created when emission has been paid into an account instead of onto stake.
+		RewardPaid(T::AccountId, u64),
+
+		/// --- Event created when the transaction fee per byte of a call type has been set.
+		TransactionByteFeeSet(CallType, u64),
+
+		/// --- Event created when the fee-less quota has been set.
+		FeelessQuotaSet(u64),
+
+		/// --- Event created when the fee-less quota interval has been set.
+		FeelessQuotaIntervalSet(u64),
+
+		/// --- Event created when the rate limit of a call type has been set.
+		TxRateLimitSet(CallType, u64),
+	}
+
+	/// ************************************************************
+	///	-Errors
+	/// ************************************************************
+	// Errors inform users that something went wrong.
+	#[pallet::error]
+	pub enum Error<T> {
+        /// ---- Thrown when the user tries to serve an axon which is not of type
+	    /// 4 (IPv4) or 6 (IPv6).
+		InvalidIpType,
+
+		/// --- Thrown when an invalid IP address is passed to the serve function.
+		InvalidIpAddress,
+
+		/// --- Thrown when an invalid modality attempted on serve.
+		/// Currently the chain only accepts modality TEXT = 0.
+		InvalidModality,
+
+		/// ---- Thrown when the caller attempts to set the weight keys
 		/// and values but these vectors have different size.
 		WeightVecNotEqualSize,
 
@@ -817,6 +1534,39 @@ pub mod pallet {
 		/// ---- Thrown when the dispatch attempts to set weights on chain with where the normalized
 		/// max value is more than MaxAllowedMaxMinRatio.
 		MaxAllowedMaxMinRatioExceeded,
+
+		/// ---- Thrown when the caller requests moving or transferring stake from a hotkey
+		/// to itself. See: fn move_stake and fn transfer_stake.
+		SameHotkey,
+
+		/// ---- Thrown when a coldkey announces a swap to itself.
+		SameColdkey,
+
+		/// ---- Thrown when a coldkey announces a swap while another one is pending.
+		ColdkeySwapAlreadyAnnounced,
+
+		/// ---- Thrown when finalizing or cancelling a coldkey swap that was not announced.
+		NoColdkeySwapAnnounced,
+
+		/// ---- Thrown when finalizing a coldkey swap before its announcement delay has passed.
+		ColdkeySwapDelayNotElapsed,
+
+		/// ---- Thrown when a delegate take above 100 percent is set.
+		InvalidDelegateTake,
+
+		/// ---- Thrown when withdrawing unbonded stake while none has finished unbonding.
+		NoUnbondedStake,
+
+		/// ---- Thrown when a hotkey makes a call before the rate limit of its call type has passed.
+		TxRateLimitExceeded,
+
+		/// ---- Thrown when a registration would prune a neuron while a mechanism step split
+		/// across blocks is running. See: fn register.
+		StepInProgress,
+
+		/// ---- Thrown when removed stake would start a new unbonding chunk while the coldkey
+		/// already has MaxUnbondingChunks chunks queued. See: fn remove_stake.
+		TooManyUnbondingChunks,
 	}
     impl<T: Config> Printable for Error<T> {
         fn print(&self) {
@@ -841,20 +1591,37 @@ pub mod pallet {
 		/// # Args:
 		/// 	* 'n': (T::BlockNumber):
 		/// 		- The number of the block we are initializing.
+		///
+		/// # Returns:
+		/// 	* The weight consumed by the mechanism step, or the chunk of it, if one ran, and the difficulty update.
 		fn on_initialize( _n: BlockNumberFor<T> ) -> Weight {
+			let mut weight: Weight = T::DbWeight::get().reads_writes( 3, 1 ); // Step counter and pending step.
 			
-			// Only run the block step every `blocks_per_step`.
+			// Only run the block step every `blocks_per_step`, unless a step is still running.
 			// Initially `blocks_since_last_step + 1` is 0 but increments until it reaches `blocks_per_step`.
 			// We use the >= here in the event that we lower get_blocks per step and these qualities never meet.
-			if Self::get_blocks_since_last_step() + 1 >= Self::get_blocks_per_step() {
+			if Self::has_pending_step() {
+
+				// A step split across blocks is running, advance it by one chunk. The counter keeps
+				// counting so the blocks spent here are paid out by the next step.
+				weight = weight.saturating_add( Self::continue_chunked_step() );
+				Self::set_blocks_since_last_step( Self::get_blocks_since_last_step() + 1 );
+
+			} else if Self::get_blocks_since_last_step() + 1 >= Self::get_blocks_per_step() {
 
 				// Compute the amount of emission we perform this step.
 				// Note that we use blocks_since_last_step here instead of block_per_step incase this is lowered
 				// This would mint more tao than is allowed.
 				let emission_this_step:u64 = ( Self::get_blocks_since_last_step() + 1 ) * Self::get_block_emission();
 
-				// Apply emission step based on mechanism and updates values.
-				Self::mechanism_step( emission_this_step );
+				// Apply emission step based on mechanism and updates values, either in this block
+				// or across the next blocks starting with this one.
+				if Self::get_step_rows_per_block( Self::get_neuron_count() ) == 0 {
+					weight = weight.saturating_add( Self::mechanism_step( emission_this_step ) );
+				} else {
+					Self::start_chunked_step( emission_this_step );
+					weight = weight.saturating_add( Self::continue_chunked_step() );
+				}
 
 				// Reset counter down to 0, this ensures that if `blocks_per_step=1` we will do an emission on every block.
 				// If `blocks_per_step=2` we will skip the next block, since (0+1) !>= 2, add one to the counter, and then apply the next
@@ -867,9 +1634,26 @@ pub mod pallet {
 			}
 
 			// Make a difficulty update.
-			Self::update_difficulty();
+			weight = weight.saturating_add( Self::update_difficulty() );
 			
-			return 0;
+			return weight;
+		}
+
+		/// ---- Called when the runtime is upgraded, runs the pending storage migrations.
+		fn on_runtime_upgrade() -> Weight {
+			migrations::migrate::<T>()
+		}
+
+		/// ---- Called by try-runtime before on_runtime_upgrade, checks the stored state can be migrated.
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<(), &'static str> {
+			migrations::pre_migrate_checks::<T>()
+		}
+
+		/// ---- Called by try-runtime after on_runtime_upgrade, checks the migrated state.
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			migrations::post_migrate_checks::<T>()
 		}
 	}
     
@@ -922,7 +1706,10 @@ pub mod pallet {
 		/// 		- When the amount to stake exceeds the amount of balance in the
 		/// 		associated colkey account.
 		///
-        #[pallet::weight((0, DispatchClass::Normal, Pays::No))]
+		/// 	* 'TxRateLimitExceeded':
+		/// 		- When the hotkey set weights within the last TxRateLimit blocks.
+		///
+        #[pallet::weight((T::WeightInfo::set_weights( dests.len() as u32 ), DispatchClass::Normal, Pays::No))]
 		pub fn set_weights(
 			origin:OriginFor<T>, 
 			dests: Vec<u32>, 
@@ -930,16 +1717,19 @@ pub mod pallet {
 		) -> DispatchResult {
 			Self::do_set_weights(origin, dests, weights)
 		}
-		
+
 		/// --- Adds stake to a neuron account. The call is made from the
 		/// coldkey account linked in the neurons's NeuronMetadata.
 		/// Only the associated coldkey is allowed to make staking and
 		/// unstaking requests. This protects the neuron against
 		/// attacks on its hotkey running in production code.
+		/// Hotkeys which opted in as delegates accept stake from any coldkey,
+		/// see fn set_delegate_take.
 		///
 		/// # Args:
 		/// 	* 'origin': (<T as frame_system::Config>Origin):
-		/// 		- The caller, a coldkey signature associated with the hotkey account.
+		/// 		- The caller, a coldkey signature associated with the hotkey account,
+		/// 		or any coldkey if the hotkey is a delegate.
 		///
 		/// 	* 'hotkey' (T::AccountId):
 		/// 		- The hotkey account to add stake to.
@@ -957,13 +1747,14 @@ pub mod pallet {
 		/// 		- If the hotkey account is not active (has not subscribed)
 		///
 		/// 	* 'NonAssociatedColdKey':
-		/// 		- When the calling coldkey is not associated with the hotkey account.
+		/// 		- When the calling coldkey is not associated with the hotkey account
+		/// 		and the hotkey is not a delegate.
 		///
 		/// 	* 'InsufficientBalance':
 		/// 		- When the amount to stake exceeds the amount of balance in the
 		/// 		associated colkey account.
 		///
-		#[pallet::weight((0, DispatchClass::Normal, Pays::No))]
+		#[pallet::weight((T::WeightInfo::add_stake(), DispatchClass::Normal, Pays::No))]
 		pub fn add_stake(
 			origin:OriginFor<T>, 
 			hotkey: T::AccountId, 
@@ -973,12 +1764,15 @@ pub mod pallet {
 		}
 
 		/// ---- Remove stake from the staking account. The call must be made
-		/// from the coldkey account attached to the neuron metadata. Only this key
-		/// has permission to make staking and unstaking requests.
+		/// from the coldkey account attached to the neuron metadata, or from a
+		/// coldkey which nominated the hotkey. Each can only remove the stake it holds.
+		/// The stake leaves the neuron right away and unbonds for UnbondingPeriod blocks,
+		/// after which it can be withdrawn into the coldkey balance with withdraw_unbonded.
 		///
 		/// # Args:
 		/// 	* 'origin': (<T as frame_system::Config>Origin):
-		/// 		- The caller, a coldkey signature associated with the hotkey account.
+		/// 		- The caller, a coldkey signature associated with the hotkey account
+		/// 		or holding nominator stake on it.
 		///
 		/// 	* 'hotkey' (T::AccountId):
 		/// 		- The hotkey account to withdraw stake from.
@@ -991,15 +1785,23 @@ pub mod pallet {
 		/// 	* 'StakeRemoved':
 		/// 		- On successful withdrawl.
 		///
+		/// 	* 'StakeUnbonding':
+		/// 		- When the removed stake is put into the unbonding queue.
+		///
 		/// # Raises:
 		/// 	* 'NonAssociatedColdKey':
-		/// 		- When the calling coldkey is not associated with the hotkey account.
+		/// 		- When the calling coldkey is not associated with the hotkey account
+		/// 		and holds no nominator stake on it.
 		///
 		/// 	* 'NotEnoughStaketoWithdraw':
-		/// 		- When the amount to unstake exceeds the quantity staked in the
-		/// 		associated hotkey staking account.
+		/// 		- When the amount to unstake exceeds the quantity the calling coldkey
+		/// 		staked in the hotkey staking account.
 		///
-		#[pallet::weight((0, DispatchClass::Normal, Pays::No))]
+		/// 	* 'TooManyUnbondingChunks':
+		/// 		- When the removed stake would start a new unbonding chunk and the coldkey
+		/// 		already has MaxUnbondingChunks chunks unbonding.
+		///
+		#[pallet::weight((T::WeightInfo::remove_stake(), DispatchClass::Normal, Pays::No))]
 		pub fn remove_stake(
 			origin:OriginFor<T>, 
 			hotkey: T::AccountId, 
@@ -1031,7 +1833,11 @@ pub mod pallet {
 		/// 	* 'AxonServed':
 		/// 		- On subscription of a new neuron to the active set.
 		///
-		#[pallet::weight((0, DispatchClass::Normal, Pays::No))]
+		/// # Raises:
+		/// 	* 'TxRateLimitExceeded':
+		/// 		- When the hotkey served an axon within the last TxRateLimit blocks.
+		///
+		#[pallet::weight((T::WeightInfo::serve_axon(), DispatchClass::Normal, Pays::No))]
 		pub fn serve_axon (
 			origin:OriginFor<T>, 
 			version: u32, 
@@ -1068,7 +1874,7 @@ pub mod pallet {
 		/// 	* 'NeuronRegistered':
 		/// 		- On subscription of a new neuron to the active set.
 		///
-		#[pallet::weight((0, DispatchClass::Normal, Pays::No))]
+		#[pallet::weight((T::WeightInfo::register_prune( Pallet::<T>::get_max_allowed_uids() as u32 ), DispatchClass::Normal, Pays::No))]
 		pub fn register( 
 				origin:OriginFor<T>, 
 				block_number: u64, 
@@ -1080,7 +1886,6 @@ pub mod pallet {
 			Self::do_registration(origin, block_number, nonce, work, hotkey, coldkey)
 		}
 
-
 		/// ---- SUDO ONLY FUNCTIONS
 		///
 		/// # Args:
@@ -1099,7 +1904,7 @@ pub mod pallet {
 		///		* 'TargetRegistrationsPerIntervalSet'
 		///
 		/// 
-		#[pallet::weight((0, DispatchClass::Operational, Pays::No))]
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
 		pub fn sudo_set_blocks_per_step ( 
 			origin:OriginFor<T>, 
 			blocks_per_step: u64 
@@ -1110,7 +1915,7 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight((0, DispatchClass::Operational, Pays::No))]
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
 		pub fn sudo_set_bonds_moving_average ( 
 			origin:OriginFor<T>, 
 			bonds_moving_average: u64 
@@ -1121,7 +1926,7 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight((0, DispatchClass::Operational, Pays::No))]
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
 		pub fn sudo_set_difficulty ( 
 			origin:OriginFor<T>, 
 			difficulty: u64 
@@ -1132,7 +1937,7 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight((0, DispatchClass::Operational, Pays::No))]
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
 		pub fn sudo_set_adjustment_interval ( 
 			origin:OriginFor<T>, 
 			adjustment_interval: u64 
@@ -1143,7 +1948,7 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight((0, DispatchClass::Operational, Pays::No))]
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
 		pub fn sudo_set_activity_cutoff ( 
 			origin:OriginFor<T>, 
 			activity_cutoff: u64 
@@ -1154,7 +1959,7 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight((0, DispatchClass::Operational, Pays::No))]
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
 		pub fn sudo_target_registrations_per_interval ( 
 			origin:OriginFor<T>, 
 			target_registrations_per_interval: u64 
@@ -1165,7 +1970,7 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight((0, DispatchClass::Operational, Pays::No))]
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
 		pub fn sudo_set_rho ( 
 			origin:OriginFor<T>, 
 			rho: u64 
@@ -1176,7 +1981,7 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight((0, DispatchClass::Operational, Pays::No))]
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
 		pub fn sudo_set_kappa ( 
 			origin:OriginFor<T>, 
 			kappa: u64 
@@ -1187,7 +1992,7 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight((0, DispatchClass::Operational, Pays::No))]
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
 		pub fn sudo_set_max_allowed_uids ( 
 			origin:OriginFor<T>, 
 			max_allowed_uids: u64 
@@ -1198,7 +2003,7 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight((0, DispatchClass::Operational, Pays::No))]
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
 		pub fn sudo_set_min_allowed_weights ( 
 			origin:OriginFor<T>, 
 			min_allowed_weights: u64 
@@ -1209,7 +2014,7 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight((0, DispatchClass::Operational, Pays::No))]
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
 		pub fn sudo_set_max_allowed_max_min_ratio ( 
 			origin:OriginFor<T>, 
 			max_allowed_max_min_ratio: u64 
@@ -1220,7 +2025,7 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight((0, DispatchClass::Operational, Pays::No))]
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
 		pub fn sudo_set_validator_batch_size ( 
 			origin:OriginFor<T>, 
 			validator_batch_size: u64 
@@ -1231,7 +2036,7 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight((0, DispatchClass::Operational, Pays::No))]
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
 		pub fn sudo_set_validator_sequence_length ( 
 			origin:OriginFor<T>, 
 			validator_sequence_length: u64 
@@ -1242,7 +2047,7 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight((0, DispatchClass::Operational, Pays::No))]
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
 		pub fn sudo_set_validator_epoch_len ( 
 			origin:OriginFor<T>, 
 			validator_epoch_len : u64 
@@ -1253,7 +2058,7 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight((0, DispatchClass::Operational, Pays::No))]
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
 		pub fn sudo_set_validator_epochs_per_reset ( 
 			origin:OriginFor<T>, 
 			validator_epochs_per_reset : u64 
@@ -1264,7 +2069,7 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight((0, DispatchClass::Operational, Pays::No))]
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
 		pub fn sudo_set_incentive_pruning_denominator( 
 			origin:OriginFor<T>, 
 			incentive_pruning_denominator: u64 
@@ -1274,8 +2079,8 @@ pub mod pallet {
 			Self::deposit_event( Event::IncentivePruningDenominatorSet( incentive_pruning_denominator ));
 			Ok(())
 		}
-		
-		#[pallet::weight((0, DispatchClass::Operational, Pays::No))]
+
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
 		pub fn sudo_set_stake_pruning_denominator( 
 			origin:OriginFor<T>, 
 			stake_pruning_denominator: u64 
@@ -1286,7 +2091,7 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight((0, DispatchClass::Operational, Pays::No))]
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
 		pub fn sudo_set_foundation_account( 
 			origin:OriginFor<T>, 
 			foundation_account: T::AccountId 
@@ -1297,7 +2102,9 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight((0, DispatchClass::Operational, Pays::No))]
+		/// ---- Sets the percent of each step's emission paid to the foundation account.
+		/// Values above 100 pay out the whole emission.
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
 		pub fn sudo_set_foundation_distribution ( 
 			origin:OriginFor<T>, 
 			foundation_distribution: u64 
@@ -1308,7 +2115,7 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight((0, DispatchClass::Operational, Pays::No))]
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
 		pub fn sudo_set_immunity_period ( 
 			origin:OriginFor<T>, 
 			immunity_period: u64 
@@ -1319,6 +2126,396 @@ pub mod pallet {
 			Ok(())
 		}
 
+		/// ---- Sets the number of uids the mechanism step runs over per block. A step walks
+		/// the uids four times, so a graph of n neurons takes about 4n / step_chunk_size blocks.
+		/// 0 runs the whole step in the block it is due. Either way a block runs no more uids than
+		/// fit in T::MaxStepWeight.
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
+		pub fn sudo_set_step_chunk_size ( 
+			origin:OriginFor<T>, 
+			step_chunk_size: u64 
+		) -> DispatchResult {
+			ensure_root( origin )?;
+			StepChunkSize::<T>::set( step_chunk_size );
+			Self::deposit_event( Event::StepChunkSizeSet( step_chunk_size ) );
+			Ok(())
+		}
+
+		/// ---- Moves stake between two hotkeys registered under the calling coldkey, without
+		/// unstaking it into the coldkey balance first.
+		///
+		/// # Args:
+		/// 	* 'origin': (<T as frame_system::Config>Origin):
+		/// 		- The caller, the coldkey associated with both hotkey accounts.
+		///
+		/// 	* 'origin_hotkey' (T::AccountId):
+		/// 		- The hotkey account to move stake from.
+		///
+		/// 	* 'destination_hotkey' (T::AccountId):
+		/// 		- The hotkey account to move stake to.
+		///
+		/// 	* 'ammount_moved' (u64):
+		/// 		- The ammount of stake to move.
+		///
+		/// # Event:
+		/// 	* 'StakeMoved':
+		/// 		- On the successful move.
+		///
+		/// # Raises:
+		/// 	* 'NotRegistered':
+		/// 		- If either hotkey account is not active.
+		///
+		/// 	* 'SameHotkey':
+		/// 		- If the origin and destination hotkeys are the same.
+		///
+		/// 	* 'NonAssociatedColdKey':
+		/// 		- When the calling coldkey is not associated with both hotkey accounts.
+		///
+		/// 	* 'NotEnoughStaketoWithdraw':
+		/// 		- When the amount to move exceeds the stake of the origin hotkey.
+		///
+		#[pallet::weight((T::WeightInfo::move_stake(), DispatchClass::Normal, Pays::No))]
+		pub fn move_stake(
+			origin:OriginFor<T>, 
+			origin_hotkey: T::AccountId, 
+			destination_hotkey: T::AccountId, 
+			ammount_moved: u64
+		) -> DispatchResult {
+			Self::do_move_stake(origin, origin_hotkey, destination_hotkey, ammount_moved)
+		}
+
+		/// ---- Transfers stake from a hotkey registered under the calling coldkey to the
+		/// neuron of any other hotkey. The stake then belongs to the coldkey of that neuron.
+		///
+		/// # Args:
+		/// 	* 'origin': (<T as frame_system::Config>Origin):
+		/// 		- The caller, the coldkey associated with the origin hotkey account.
+		///
+		/// 	* 'origin_hotkey' (T::AccountId):
+		/// 		- The hotkey account to transfer stake from.
+		///
+		/// 	* 'destination_hotkey' (T::AccountId):
+		/// 		- The hotkey account receiving the stake.
+		///
+		/// 	* 'ammount_transferred' (u64):
+		/// 		- The ammount of stake to transfer.
+		///
+		/// # Event:
+		/// 	* 'StakeTransferred':
+		/// 		- On the successful transfer.
+		///
+		/// # Raises:
+		/// 	* 'NotRegistered':
+		/// 		- If either hotkey account is not active.
+		///
+		/// 	* 'SameHotkey':
+		/// 		- If the origin and destination hotkeys are the same.
+		///
+		/// 	* 'NonAssociatedColdKey':
+		/// 		- When the calling coldkey is not associated with the origin hotkey account.
+		///
+		/// 	* 'NotEnoughStaketoWithdraw':
+		/// 		- When the amount to transfer exceeds the stake of the origin hotkey.
+		///
+		#[pallet::weight((T::WeightInfo::transfer_stake(), DispatchClass::Normal, Pays::No))]
+		pub fn transfer_stake(
+			origin:OriginFor<T>, 
+			origin_hotkey: T::AccountId, 
+			destination_hotkey: T::AccountId, 
+			ammount_transferred: u64
+		) -> DispatchResult {
+			Self::do_transfer_stake(origin, origin_hotkey, destination_hotkey, ammount_transferred)
+		}
+
+		/// ---- Replaces the hotkey of a registered neuron, for instance when the old hotkey
+		/// is compromised. The neuron keeps its uid, stake, weights and bonds.
+		///
+		/// # Args:
+		/// 	* 'origin': (<T as frame_system::Config>Origin):
+		/// 		- The caller, the coldkey associated with the neuron.
+		///
+		/// 	* 'old_hotkey' (T::AccountId):
+		/// 		- The hotkey the neuron is currently registered under.
+		///
+		/// 	* 'new_hotkey' (T::AccountId):
+		/// 		- The hotkey to register the neuron under, must not be registered.
+		///
+		/// # Event:
+		/// 	* 'HotkeySwapped':
+		/// 		- On the successful swap.
+		///
+		/// # Raises:
+		/// 	* 'NotRegistered':
+		/// 		- If the old hotkey is not registered.
+		///
+		/// 	* 'NonAssociatedColdKey':
+		/// 		- When the calling coldkey is not associated with the old hotkey.
+		///
+		/// 	* 'AlreadyRegistered':
+		/// 		- If the new hotkey is already registered.
+		///
+		#[pallet::weight((T::WeightInfo::swap_hotkey(), DispatchClass::Normal, Pays::No))]
+		pub fn swap_hotkey(
+			origin:OriginFor<T>, 
+			old_hotkey: T::AccountId, 
+			new_hotkey: T::AccountId 
+		) -> DispatchResult {
+			Self::do_swap_hotkey(origin, old_hotkey, new_hotkey)
+		}
+
+		/// ---- Announces that the calling coldkey hands all of its neurons over to a new coldkey.
+		/// The swap can be finalized after ColdkeySwapDelay blocks and cancelled until then.
+		///
+		/// # Args:
+		/// 	* 'origin': (<T as frame_system::Config>Origin):
+		/// 		- The caller, a coldkey owning at least one neuron.
+		///
+		/// 	* 'new_coldkey' (T::AccountId):
+		/// 		- The coldkey to hand the neurons over to.
+		///
+		/// # Event:
+		/// 	* 'ColdkeySwapAnnounced':
+		/// 		- On the successful announcement.
+		///
+		/// # Raises:
+		/// 	* 'SameColdkey':
+		/// 		- If the new coldkey is the calling coldkey.
+		///
+		/// 	* 'NotRegistered':
+		/// 		- If the calling coldkey owns no neurons.
+		///
+		/// 	* 'ColdkeySwapAlreadyAnnounced':
+		/// 		- If the calling coldkey already announced a swap.
+		///
+		#[pallet::weight((T::WeightInfo::announce_coldkey_swap(), DispatchClass::Normal, Pays::No))]
+		pub fn announce_coldkey_swap(
+			origin:OriginFor<T>, 
+			new_coldkey: T::AccountId 
+		) -> DispatchResult {
+			Self::do_announce_coldkey_swap(origin, new_coldkey)
+		}
+
+		/// ---- Hands the neurons of a coldkey over to the coldkey it announced, once the
+		/// announcement delay has passed.
+		///
+		/// # Args:
+		/// 	* 'origin': (<T as frame_system::Config>Origin):
+		/// 		- The caller, either the old or the announced coldkey.
+		///
+		/// 	* 'old_coldkey' (T::AccountId):
+		/// 		- The coldkey which announced the swap.
+		///
+		/// # Event:
+		/// 	* 'ColdkeySwapped':
+		/// 		- On the successful swap.
+		///
+		/// # Raises:
+		/// 	* 'NoColdkeySwapAnnounced':
+		/// 		- If the old coldkey has no pending announcement.
+		///
+		/// 	* 'NonAssociatedColdKey':
+		/// 		- If the caller is neither the old nor the announced coldkey.
+		///
+		/// 	* 'ColdkeySwapDelayNotElapsed':
+		/// 		- If the announcement delay has not passed yet.
+		///
+		#[pallet::weight((T::WeightInfo::finalize_coldkey_swap( Pallet::<T>::get_max_allowed_uids() as u32 ), DispatchClass::Normal, Pays::No))]
+		pub fn finalize_coldkey_swap(
+			origin:OriginFor<T>, 
+			old_coldkey: T::AccountId 
+		) -> DispatchResult {
+			Self::do_finalize_coldkey_swap(origin, old_coldkey)
+		}
+
+		/// ---- Cancels the coldkey swap announced by the calling coldkey.
+		///
+		/// # Args:
+		/// 	* 'origin': (<T as frame_system::Config>Origin):
+		/// 		- The caller, the coldkey which announced the swap.
+		///
+		/// # Event:
+		/// 	* 'ColdkeySwapCancelled':
+		/// 		- On the successful cancellation.
+		///
+		/// # Raises:
+		/// 	* 'NoColdkeySwapAnnounced':
+		/// 		- If the calling coldkey has no pending announcement.
+		///
+		#[pallet::weight((T::WeightInfo::cancel_coldkey_swap(), DispatchClass::Normal, Pays::No))]
+		pub fn cancel_coldkey_swap(
+			origin:OriginFor<T>
+		) -> DispatchResult {
+			Self::do_cancel_coldkey_swap(origin)
+		}
+
+		/// ---- Sets the number of blocks between a coldkey swap announcement and its finalization.
+		/// Announcements already made keep the delay they were made with.
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
+		pub fn sudo_set_coldkey_swap_delay ( 
+			origin:OriginFor<T>, 
+			coldkey_swap_delay: u64 
+		) -> DispatchResult {
+			ensure_root( origin )?;
+			ColdkeySwapDelay::<T>::set( coldkey_swap_delay );
+			Self::deposit_event( Event::ColdkeySwapDelaySet( coldkey_swap_delay ) );
+			Ok(())
+		}
+
+		/// ---- Opts a hotkey in as a delegate, or updates its take. Any coldkey can then stake on
+		/// the hotkey through add_stake. The emission of the neuron is shared pro-rata to stake between
+		/// the owner and its nominators, after the take percent which the owner keeps.
+		///
+		/// # Args:
+		/// 	* 'origin': (<T as frame_system::Config>Origin):
+		/// 		- The caller, the coldkey associated with the hotkey account.
+		///
+		/// 	* 'hotkey' (T::AccountId):
+		/// 		- The hotkey account to open to nominators.
+		///
+		/// 	* 'take' (u64):
+		/// 		- The percent of the neuron emission the owner keeps, at most 100.
+		///
+		/// # Event:
+		/// 	* 'DelegateTakeSet':
+		/// 		- On the successful update.
+		///
+		/// # Raises:
+		/// 	* 'NotRegistered':
+		/// 		- If the hotkey account is not active.
+		///
+		/// 	* 'NonAssociatedColdKey':
+		/// 		- When the calling coldkey is not associated with the hotkey account.
+		///
+		/// 	* 'InvalidDelegateTake':
+		/// 		- When the take is above 100.
+		///
+		#[pallet::weight((T::WeightInfo::set_delegate_take(), DispatchClass::Normal, Pays::No))]
+		pub fn set_delegate_take(
+			origin:OriginFor<T>, 
+			hotkey: T::AccountId, 
+			take: u64
+		) -> DispatchResult {
+			Self::do_set_delegate_take(origin, hotkey, take)
+		}
+
+		/// ---- Pays the stake of the calling coldkey which finished unbonding into its balance.
+		///
+		/// # Args:
+		/// 	* 'origin': (<T as frame_system::Config>Origin):
+		/// 		- The caller, the coldkey which removed the stake.
+		///
+		/// # Event:
+		/// 	* 'UnbondedWithdrawn':
+		/// 		- On the successful withdrawl.
+		///
+		/// # Raises:
+		/// 	* 'NoUnbondedStake':
+		/// 		- When none of the stake of the coldkey has finished unbonding.
+		///
+		#[pallet::weight((T::WeightInfo::withdraw_unbonded(), DispatchClass::Normal, Pays::No))]
+		pub fn withdraw_unbonded(
+			origin:OriginFor<T>
+		) -> DispatchResult {
+			Self::do_withdraw_unbonded(origin)
+		}
+
+		/// ---- Sets the number of blocks removed stake unbonds for. Stake already unbonding
+		/// keeps the unlock block it was removed with.
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
+		pub fn sudo_set_unbonding_period ( 
+			origin:OriginFor<T>, 
+			unbonding_period: u64 
+		) -> DispatchResult {
+			ensure_root( origin )?;
+			UnbondingPeriod::<T>::set( unbonding_period );
+			Self::deposit_event( Event::UnbondingPeriodSet( unbonding_period ) );
+			Ok(())
+		}
+
+		/// ---- Sets where the emission of a neuron goes: onto its stake, which is the default,
+		/// into the balance of its coldkey or into the balance of any other account.
+		/// Emission shared with nominators of a delegate is always added onto stake.
+		///
+		/// # Args:
+		/// 	* 'origin': (<T as frame_system::Config>Origin):
+		/// 		- The caller, the coldkey associated with the hotkey account.
+		///
+		/// 	* 'hotkey' (T::AccountId):
+		/// 		- The hotkey account of the neuron.
+		///
+		/// 	* 'destination' (RewardDestination):
+		/// 		- Where the emission of the neuron goes.
+		///
+		/// # Event:
+		/// 	* 'RewardDestinationSet':
+		/// 		- On the successful update.
+		///
+		/// # Raises:
+		/// 	* 'NotRegistered':
+		/// 		- If the hotkey account is not active.
+		///
+		/// 	* 'NonAssociatedColdKey':
+		/// 		- When the calling coldkey is not associated with the hotkey account.
+		///
+		#[pallet::weight((T::WeightInfo::set_reward_destination(), DispatchClass::Normal, Pays::No))]
+		pub fn set_reward_destination(
+			origin:OriginFor<T>, 
+			hotkey: T::AccountId, 
+			destination: RewardDestinationOf<T>
+		) -> DispatchResult {
+			Self::do_set_reward_destination(origin, hotkey, destination)
+		}
+
+		/// ---- Sets the transaction fee in rao per byte charged for calls of the given type.
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
+		pub fn sudo_set_transaction_byte_fee ( 
+			origin:OriginFor<T>, 
+			call_type: CallType,
+			transaction_byte_fee: u64 
+		) -> DispatchResult {
+			ensure_root( origin )?;
+			TransactionByteFee::<T>::insert( call_type, transaction_byte_fee );
+			Self::deposit_event( Event::TransactionByteFeeSet( call_type, transaction_byte_fee ) );
+			Ok(())
+		}
+
+		/// ---- Sets the number of fee-less calls each neuron hotkey has per quota interval.
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
+		pub fn sudo_set_feeless_quota ( 
+			origin:OriginFor<T>, 
+			feeless_quota: u64 
+		) -> DispatchResult {
+			ensure_root( origin )?;
+			FeelessQuota::<T>::set( feeless_quota );
+			Self::deposit_event( Event::FeelessQuotaSet( feeless_quota ) );
+			Ok(())
+		}
+
+		/// ---- Sets the number of blocks per fee-less quota interval.
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
+		pub fn sudo_set_feeless_quota_interval ( 
+			origin:OriginFor<T>, 
+			feeless_quota_interval: u64 
+		) -> DispatchResult {
+			ensure_root( origin )?;
+			FeelessQuotaInterval::<T>::set( feeless_quota_interval );
+			Self::deposit_event( Event::FeelessQuotaIntervalSet( feeless_quota_interval ) );
+			Ok(())
+		}
+
+		/// ---- Sets the number of blocks a hotkey has to wait between two calls of the given type.
+		#[pallet::weight((T::WeightInfo::sudo_set_hyperparameter(), DispatchClass::Operational, Pays::No))]
+		pub fn sudo_set_tx_rate_limit ( 
+			origin:OriginFor<T>, 
+			call_type: CallType,
+			tx_rate_limit: u64 
+		) -> DispatchResult {
+			ensure_root( origin )?;
+			TxRateLimit::<T>::insert( call_type, tx_rate_limit );
+			Self::deposit_event( Event::TxRateLimitSet( call_type, tx_rate_limit ) );
+			Ok(())
+		}
+
 	}
 	
 	// ---- Subtensor helper functions.
@@ -1343,6 +2540,45 @@ pub mod pallet {
 		pub fn set_blocks_per_step( blocks_per_step: u64 ) {
 			BlocksPerStep::<T>::set( blocks_per_step );
 		}
+		// -- Transaction fees.
+		pub fn get_feeless_quota( ) -> u64 {
+			FeelessQuota::<T>::get()
+		}
+		pub fn set_feeless_quota( feeless_quota: u64 ) {
+			FeelessQuota::<T>::set( feeless_quota );
+		}
+		pub fn get_feeless_quota_interval( ) -> u64 {
+			FeelessQuotaInterval::<T>::get()
+		}
+		pub fn set_feeless_quota_interval( feeless_quota_interval: u64 ) {
+			FeelessQuotaInterval::<T>::set( feeless_quota_interval );
+		}
+		pub fn set_transaction_byte_fee( call_type: CallType, transaction_byte_fee: u64 ) {
+			TransactionByteFee::<T>::insert( call_type, transaction_byte_fee );
+		}
+		pub fn set_tx_rate_limit( call_type: CallType, tx_rate_limit: u64 ) {
+			TxRateLimit::<T>::insert( call_type, tx_rate_limit );
+		}
+		// -- Unbonding period.
+		pub fn get_unbonding_period( ) -> u64 {
+			UnbondingPeriod::<T>::get()
+		}
+		pub fn set_unbonding_period( unbonding_period: u64 ) {
+			UnbondingPeriod::<T>::set( unbonding_period );
+		}
+		// -- Coldkey swap delay.
+		pub fn get_coldkey_swap_delay( ) -> u64 {
+			ColdkeySwapDelay::<T>::get()
+		}
+		pub fn set_coldkey_swap_delay( coldkey_swap_delay: u64 ) {
+			ColdkeySwapDelay::<T>::set( coldkey_swap_delay );
+		}
+		pub fn get_step_chunk_size( ) -> u64 {
+			StepChunkSize::<T>::get()
+		}
+		pub fn set_step_chunk_size( step_chunk_size: u64 ) {
+			StepChunkSize::<T>::set( step_chunk_size );
+		}
 		pub fn get_bonds_moving_average( ) -> u64 {
 			BondsMovingAverage::<T>::get()
 		}
@@ -1573,7 +2809,7 @@ pub mod pallet {
 		}
 		pub fn get_bonds_for_neuron( neuron: &NeuronMetadataOf<T> ) -> Vec<u64>  {
 			let mut bonds: Vec<u64> = vec![ 0; Self::get_neuron_count() as usize ];
-			for (uid_j, bonds_ij) in neuron.bonds.iter(){
+			for (uid_j, bonds_ij) in Bonds::<T>::get( neuron.uid ).iter(){
 				bonds[ *uid_j as usize ] = *bonds_ij;
 			}
 			return bonds
@@ -1587,7 +2823,7 @@ pub mod pallet {
 		}
 		pub fn get_weights_for_neuron( neuron: &NeuronMetadataOf<T> ) -> Vec<u32>  {
 			let mut weights: Vec<u32> = vec![ 0; Self::get_neuron_count() as usize ];
-			for (uid_j, weights_ij) in neuron.weights.iter(){
+			for (uid_j, weights_ij) in Weights::<T>::get( neuron.uid ).iter(){
 				weights[ *uid_j as usize ] = *weights_ij;
 			}
 			return weights
@@ -1627,9 +2863,7 @@ pub mod pallet {
 						sparse_weights.push( (uid_j, weight_ij) );
 					}
 				}
-				let mut neuron = Neurons::<T>::get(uid_i);
-				neuron.weights = sparse_weights;
-				Neurons::<T>::insert( uid_i, neuron );
+				Weights::<T>::insert( uid_i, sparse_weights );
 			}
 		}
 	
@@ -1681,6 +2915,18 @@ pub mod pallet {
 			return Neurons::<T>::get( uid );
 		}
 
+		// --- Returns the uids owned by the passed coldkey in ascending order.
+		pub fn get_uids_for_coldkey( coldkey: &T::AccountId ) -> Vec<u32> {
+			let mut uids: Vec<u32> = ColdkeyUids::<T>::iter_prefix( coldkey ).map( |(uid, _)| uid ).collect();
+			uids.sort_unstable();
+			return uids;
+		}
+
+		// --- Returns true if the passed coldkey owns the uid.
+		pub fn is_uid_owned_by_coldkey( coldkey: &T::AccountId, uid: u32 ) -> bool {
+			return ColdkeyUids::<T>::contains_key( coldkey, uid );
+		}
+
 		// --- Returns the neuron associated with the passed hotkey.
 		// The function makes a double mapping from hotkey -> uid -> neuron.
 		pub fn get_neuron_for_hotkey(hotkey_id: &T::AccountId) -> NeuronMetadataOf<T> {
@@ -1704,11 +2950,6 @@ pub mod pallet {
 			uid
 		}
 
-		// --- Returns a vanilla transaction fee for transactions as rao.
-		pub fn calculate_transaction_fee(len: u64) -> u64 {
-			return len * 100;
-		}
-
 		// --- Returns the transaction priority for setting weights.
 		pub fn get_priority_set_weights( hotkey: &T::AccountId, len: u64 ) -> u64 {
 			if Hotkeys::<T>::contains_key( hotkey ) {
@@ -1730,14 +2971,52 @@ pub mod pallet {
 	}
 }
 
+/************************************************************
+	Serde helpers
+************************************************************/
+/// Serializes u128 values as decimal strings. Json numbers cannot hold
+/// the full u128 range (ipv6 addresses) without losing precision in most clients.
+#[cfg(feature = "std")]
+pub mod serde_u128 {
+	use serde::{Deserialize, Deserializer, Serializer};
+
+	pub fn serialize<S: Serializer>(value: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&value.to_string())
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+		let s = String::deserialize(deserializer)?;
+		s.parse::<u128>().map_err(serde::de::Error::custom)
+	}
+
+	pub mod vec {
+		use serde::{Deserialize, Deserializer, Serializer, ser::SerializeSeq};
+
+		pub fn serialize<S: Serializer>(values: &[u128], serializer: S) -> Result<S::Ok, S::Error> {
+			let mut seq = serializer.serialize_seq(Some(values.len()))?;
+			for value in values {
+				seq.serialize_element(&value.to_string())?;
+			}
+			seq.end()
+		}
+
+		pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u128>, D::Error> {
+			let strings = Vec::<String>::deserialize(deserializer)?;
+			strings.iter().map(|s| s.parse::<u128>().map_err(serde::de::Error::custom)).collect()
+		}
+	}
+}
+
 /************************************************************
 	CallType definition
 ************************************************************/
-#[derive(Debug, PartialEq)]
+#[derive(Encode, Decode, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum CallType {
     SetWeights,
     AddStake,
     RemoveStake,
+    MoveStake,
+    TransferStake,
     Register,
     Serve,
 	Other,
@@ -1748,6 +3027,14 @@ impl Default for CallType {
     }
 }
 
+/// Custom InvalidTransaction code of calls rejected in the pool for exceeding the rate limit of
+/// their call type, the dispatch counterpart is Error::TxRateLimitExceeded.
+pub const TX_RATE_LIMIT_EXCEEDED: u8 = 1;
+
+/// Custom InvalidTransaction code of register calls rejected in the pool because the hotkey
+/// is already registered, the dispatch counterpart is Error::AlreadyRegistered.
+pub const ALREADY_REGISTERED: u8 = 2;
+
 /************************************************************
 	SubtensorSignedExtension definition
 ************************************************************/
@@ -1762,6 +3049,19 @@ impl<T: Config + Send + Sync> SubtensorSignedExtension<T> where
     pub fn new() -> Self {
         Self(Default::default())
 	}
+    pub fn get_priority_register( work_blocks_remaining: u64 ) -> u64 {
+        // Registrations rank just below the vanilla priority, the ones whose
+        // work goes stale first are included first.
+        return Self::get_priority_vanilla().saturating_sub( work_blocks_remaining );
+    }
+    pub fn registration_error_to_invalid_transaction( error: Error<T> ) -> InvalidTransaction {
+        match error {
+            Error::<T>::InvalidWorkBlock => InvalidTransaction::Stale,
+            Error::<T>::InvalidDifficulty | Error::<T>::InvalidSeal => InvalidTransaction::BadProof,
+            Error::<T>::AlreadyRegistered => InvalidTransaction::Custom(ALREADY_REGISTERED),
+            _ => InvalidTransaction::Call,
+        }
+    }
     pub fn get_priority_vanilla() -> u64 {
         // Just return a rediculously high priority. This means that all extrinsics except
         // the set_weights function will have a priority over the set_weights calls.
@@ -1771,6 +3071,19 @@ impl<T: Config + Send + Sync> SubtensorSignedExtension<T> where
 		// Return the non vanilla priority for a set weights call.
         return Pallet::<T>::get_priority_set_weights( who, len );
     }
+	pub fn get_call_type( call: &<T as frame_system::Config>::Call ) -> CallType {
+		// Return the call type the transaction fee is charged for.
+        match call.is_sub_type() {
+            Some(Call::add_stake(..)) => CallType::AddStake,
+            Some(Call::remove_stake(..)) => CallType::RemoveStake,
+            Some(Call::move_stake(..)) => CallType::MoveStake,
+            Some(Call::transfer_stake(..)) => CallType::TransferStake,
+			Some(Call::set_weights(..)) => CallType::SetWeights,
+			Some(Call::register(..)) => CallType::Register,
+            Some(Call::serve_axon(..)) => CallType::Serve,
+            _ => CallType::Other,
+        }
+    }
 }
 
 impl<T: Config + Send + Sync> sp_std::fmt::Debug for SubtensorSignedExtension<T> {
@@ -1790,7 +3103,7 @@ impl<T: Config + Send + Sync> SignedExtension for SubtensorSignedExtension<T>
     type Call = T::Call;
     //<T as frame_system::Trait>::Call;
     type AdditionalSigned = ();
-    type Pre = (CallType, u64, Self::AccountId);
+    type Pre = (CallType, u64, Self::AccountId, Option<u32>);
     fn additional_signed(&self) -> Result<Self::AdditionalSigned, TransactionValidityError> { Ok(()) }
 
     fn validate(
@@ -1800,6 +3113,14 @@ impl<T: Config + Send + Sync> SignedExtension for SubtensorSignedExtension<T>
         _info: &DispatchInfoOf<Self::Call>,
         len: usize,
     ) -> TransactionValidity {
+        // The signer must be able to pay the fee of the call, see fn charge_transaction_fee.
+        if !Pallet::<T>::can_pay_transaction_fee(who, &Self::get_call_type(call), len as u64) {
+            return InvalidTransaction::Payment.into();
+        }
+        // Rate limited calls never enter the pool, see fn exceeds_tx_rate_limit.
+        if Pallet::<T>::exceeds_tx_rate_limit(who, &Self::get_call_type(call)) {
+            return InvalidTransaction::Custom(TX_RATE_LIMIT_EXCEEDED).into();
+        }
         match call.is_sub_type() {
             Some(Call::set_weights(..)) => {
 				let priority: u64 = Self::get_priority_set_weights(who, len as u64);
@@ -1821,9 +3142,17 @@ impl<T: Config + Send + Sync> SignedExtension for SubtensorSignedExtension<T>
                     ..Default::default()
                 })
             }
-            Some(Call::register(..)) => {
+            Some(Call::register(block_number, nonce, work, hotkey, _)) => {
+                // Bad registrations are rejected here, before they enter the pool.
+                if let Err(e) = Pallet::<T>::check_registration(*block_number, *nonce, work, hotkey) {
+                    return Self::registration_error_to_invalid_transaction(e).into();
+                }
+                // The work is stale once no blocks remain, the pool keeps one registration per hotkey.
+                let work_blocks_remaining: u64 = Pallet::<T>::get_work_blocks_remaining(*block_number);
                 Ok(ValidTransaction {
-                    priority: Self::get_priority_vanilla(),
+                    priority: Self::get_priority_register(work_blocks_remaining),
+                    provides: vec![("register", hotkey).encode()],
+                    longevity: work_blocks_remaining,
                     ..Default::default()
                 })
             }
@@ -1836,43 +3165,28 @@ impl<T: Config + Send + Sync> SignedExtension for SubtensorSignedExtension<T>
         }
     }
 
-    // NOTE: Add later when we put in a pre and post dispatch step.
+    // Checks the call as validate does, the state may have changed since it entered the pool, and
+    // withdraws the transaction fee from the signer. A neuron hotkey with fee-less calls left uses
+    // up one of them instead. A rate limited call is let through to fail in dispatch, where its
+    // fee is burnt, so including it is never free.
     fn pre_dispatch(
         self,
         who: &Self::AccountId,
         call: &Self::Call,
-        _info: &DispatchInfoOf<Self::Call>,
-        _len: usize,
+        info: &DispatchInfoOf<Self::Call>,
+        len: usize,
     ) -> Result<Self::Pre, TransactionValidityError> {
-
-        match call.is_sub_type() {
-            Some(Call::add_stake(..)) => {
-				let transaction_fee = 0;
-                Ok((CallType::AddStake, transaction_fee, who.clone()))
-            }
-            Some(Call::remove_stake(..)) => {
-				let transaction_fee = 0;
-                Ok((CallType::RemoveStake, transaction_fee, who.clone()))
-            }
-			Some(Call::set_weights(..)) => {
-				let transaction_fee = 0;
-                Ok((CallType::SetWeights, transaction_fee, who.clone())) 
-            }
-			Some(Call::register(..)) => {
-                let transaction_fee = 0;
-                Ok((CallType::Register, transaction_fee, who.clone()))
-            }
-            Some(Call::serve_axon(..)) => {
-                let transaction_fee = 0;
-                Ok((CallType::Serve, transaction_fee, who.clone()))
-            }
-            _ => {
-				let transaction_fee = 0;
-                Ok((CallType::Other, transaction_fee, who.clone()))
-            }
+        match self.validate(who, call, info, len) {
+            Ok(_) | Err(TransactionValidityError::Invalid(InvalidTransaction::Custom(TX_RATE_LIMIT_EXCEEDED))) => {},
+            Err(e) => return Err(e),
         }
+        let call_type = Self::get_call_type(call);
+        let (transaction_fee, feeless_uid) = Pallet::<T>::charge_transaction_fee(who, &call_type, len as u64)?;
+        Ok((call_type, transaction_fee, who.clone(), feeless_uid))
     }
 
+    // Burns the transaction fee when the call succeeded or failed on an error the signer could have
+    // checked for, and refunds it, or the fee-less call, otherwise.
     fn post_dispatch(
         pre: Self::Pre,
         _info: &DispatchInfoOf<Self::Call>,
@@ -1880,28 +3194,8 @@ impl<T: Config + Send + Sync> SignedExtension for SubtensorSignedExtension<T>
         _len: usize,
         result: &dispatch::DispatchResult,
     ) -> Result<(), TransactionValidityError> {
-		let call_type = pre.0;
-        match result {
-            Ok(_) => {
-                match call_type {
-                    CallType::SetWeights => {
-                        Ok(Default::default())
-                    }
-                    CallType::AddStake => {
-                        Ok(Default::default())
-                    }
-                    CallType::RemoveStake => {
-                        Ok(Default::default())
-                    }
-                    CallType::Register => {
-                        Ok(Default::default())
-                    }
-                    _ => {
-						Ok(Default::default())
-                    }
-                }
-            }
-            Err(_) => Ok(Default::default())
-        }
+		let (_call_type, transaction_fee, who, feeless_uid) = pre;
+        Pallet::<T>::settle_transaction_fee(&who, transaction_fee, feeless_uid, result);
+        Ok(Default::default())
     }
 }
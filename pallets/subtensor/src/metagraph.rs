@@ -0,0 +1,66 @@
+use super::*;
+
+impl<T: Config> Pallet<T> {
+    /// Returns the metagraph as columns for all neurons with uids in [start, end).
+    /// Unset bounds default to the full graph, ranges past the neuron count are clamped,
+    /// which lets large graphs be fetched in pages.
+    pub fn get_metagraph( start: Option<u32>, end: Option<u32> ) -> MetagraphOf<T> {
+        let n = Self::get_neuron_count();
+        let start = start.unwrap_or( 0 );
+        let end = end.unwrap_or( n ).min( n );
+
+        let mut metagraph = MetagraphOf::<T> {
+            n,
+            block: Self::get_current_block_as_u64(),
+            ..Default::default()
+        };
+        for uid in start..end {
+            if !Self::is_uid_active( uid ) {
+                continue;
+            }
+            let neuron = Self::get_neuron_for_uid( uid );
+            metagraph.uids.push( neuron.uid );
+            metagraph.versions.push( neuron.version );
+            metagraph.ips.push( neuron.ip );
+            metagraph.ports.push( neuron.port );
+            metagraph.ip_types.push( neuron.ip_type );
+            metagraph.modalities.push( neuron.modality );
+            metagraph.hotkeys.push( neuron.hotkey );
+            metagraph.coldkeys.push( neuron.coldkey );
+            metagraph.active.push( neuron.active );
+            metagraph.last_update.push( neuron.last_update );
+            metagraph.priority.push( neuron.priority );
+            metagraph.stake.push( neuron.stake );
+            metagraph.ranks.push( neuron.rank );
+            metagraph.trust.push( neuron.trust );
+            metagraph.consensus.push( neuron.consensus );
+            metagraph.incentive.push( neuron.incentive );
+            metagraph.dividends.push( neuron.dividends );
+            metagraph.emission.push( neuron.emission );
+            metagraph.bonds.push( Bonds::<T>::get( uid ) );
+            metagraph.weights.push( Weights::<T>::get( uid ) );
+        }
+        metagraph
+    }
+
+    /// Returns the neuron registered under this uid or None if the uid is not in use.
+    pub fn get_neuron_by_uid( uid: u32 ) -> Option<NeuronMetadataOf<T>> {
+        if !Self::is_uid_active( uid ) {
+            return None;
+        }
+        Some( Self::get_neuron_for_uid( uid ) )
+    }
+
+    /// Returns the neuron registered with this hotkey or None if the hotkey is not registered.
+    pub fn get_neuron_by_hotkey( hotkey: &T::AccountId ) -> Option<NeuronMetadataOf<T>> {
+        if !Self::is_hotkey_active( hotkey ) {
+            return None;
+        }
+        Self::get_neuron_by_uid( Self::get_uid_for_hotkey( hotkey ) )
+    }
+
+    /// Returns all neurons whose coldkey is the passed account, ordered by uid.
+    pub fn get_neurons_by_coldkey( coldkey: &T::AccountId ) -> Vec<NeuronMetadataOf<T>> {
+        Self::get_uids_for_coldkey( coldkey ).into_iter().map( |uid| Self::get_neuron_for_uid( uid ) ).collect()
+    }
+}
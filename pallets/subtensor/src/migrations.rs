@@ -0,0 +1,186 @@
+use super::*;
+use frame_support::weights::Weight;
+use frame_support::traits::Get;
+use frame_support::storage::unhashed;
+use codec::DecodeAll;
+
+/// Runs the pending migrations in release order. Each migration only runs on the release it
+/// migrates from and moves the chain on to the next one, so a chain at any release ends at the
+/// latest and running the migrations again is a no-op. New migrations are appended here.
+pub fn migrate<T: Config>() -> Weight {
+    migrate_to_v2::<T>()
+        .saturating_add( migrate_to_v3::<T>() )
+}
+
+/// Log target of the migrations.
+const LOG_TARGET: &str = "runtime::subtensor";
+
+/// Returns the raw value stored under each uid below N, uids without an entry are skipped.
+fn raw_neurons<T: Config>() -> Vec<(u32, Vec<u8>)> {
+    ( 0..N::<T>::get() )
+        .filter_map( |uid_i| unhashed::get_raw( &Neurons::<T>::hashed_key_for( uid_i ) ).map( |raw| ( uid_i, raw ) ) )
+        .collect()
+}
+
+/// Logs the number of neurons a migration could not decode. Those are left in storage as they
+/// are, for post_migrate_checks to fail on, rather than dropped.
+fn log_undecodable( migration: &str, undecodable: u32 ) {
+    if undecodable > 0 {
+        log::error!( target: LOG_TARGET, "{}: {} neurons could not be decoded and were left as is", migration, undecodable );
+    }
+}
+
+/// Backfills the ColdkeyUids index from the neurons already stored on chain, decoded in the
+/// V1 layout. Chains created before the index existed are at Releases::V1_0_0, the migration
+/// is a no-op for any later version.
+pub fn migrate_to_v2<T: Config>() -> Weight {
+    let mut reads: u64 = 1;
+    let mut writes: u64 = 0;
+    if StorageVersion::<T>::get() != Releases::V1_0_0 {
+        return T::DbWeight::get().reads( reads );
+    }
+
+    let neurons: Vec<(u32, Vec<u8>)> = raw_neurons::<T>();
+    reads += 1 + neurons.len() as u64;
+    let mut undecodable: u32 = 0;
+    for ( uid_i, raw ) in neurons {
+        match OldNeuronMetadata::<T::AccountId>::decode_all( &raw ) {
+            Ok( old ) => {
+                ColdkeyUids::<T>::insert( &old.coldkey, uid_i, () );
+                writes += 1;
+            },
+            Err( _ ) => {
+                log::error!( target: LOG_TARGET, "migrate_to_v2: neuron {} does not decode in the V1 layout", uid_i );
+                undecodable += 1;
+            },
+        }
+    }
+    log_undecodable( "migrate_to_v2", undecodable );
+
+    StorageVersion::<T>::put( Releases::V2_0_0 );
+    writes += 1;
+    T::DbWeight::get().reads_writes( reads, writes )
+}
+
+/// The NeuronMetadata layout before V3_0_0, which held the weights and bonds of the neuron.
+/// The V3 layout only drops the two trailing vectors.
+#[derive(Encode, Decode)]
+pub struct OldNeuronMetadata<AccountId> {
+    pub version: u32,
+    pub ip: u128,
+    pub port: u16,
+    pub ip_type: u8,
+    pub uid: u32,
+    pub modality: u8,
+    pub hotkey: AccountId,
+    pub coldkey: AccountId,
+    pub active: u32,
+    pub last_update: u64,
+    pub priority: u64,
+    pub stake: u64,
+    pub rank: u64,
+    pub trust: u64,
+    pub consensus: u64,
+    pub incentive: u64,
+    pub dividends: u64,
+    pub emission: u64,
+    pub bonds: Vec<(u32,u64)>,
+    pub weights: Vec<(u32,u32)>,
+}
+
+/// Moves the weights and bonds of every neuron out of NeuronMetadata into the Weights and
+/// Bonds maps. Incoming bonds to uids still waiting in NeuronsToPruneAtNextEpoch are dropped
+/// here since the step no longer filters them. Entries already in the V3 layout are left as is,
+/// entries decoding in neither layout are logged and counted.
+pub fn migrate_to_v3<T: Config>() -> Weight {
+    let mut reads: u64 = 1;
+    let mut writes: u64 = 0;
+    if StorageVersion::<T>::get() != Releases::V2_0_0 {
+        return T::DbWeight::get().reads( reads );
+    }
+
+    let neurons: Vec<(u32, Vec<u8>)> = raw_neurons::<T>();
+    reads += 1 + neurons.len() as u64;
+    let mut undecodable: u32 = 0;
+    for ( uid_i, raw ) in neurons {
+        let old: OldNeuronMetadata<T::AccountId> = match OldNeuronMetadata::<T::AccountId>::decode_all( &raw ) {
+            Ok( old ) => old,
+            Err( _ ) if NeuronMetadataOf::<T>::decode_all( &raw ).is_ok() => continue,
+            Err( _ ) => {
+                log::error!( target: LOG_TARGET, "migrate_to_v3: neuron {} decodes in neither the V2 nor the V3 layout", uid_i );
+                undecodable += 1;
+                continue;
+            },
+        };
+        let bonds: Vec<(u32,u64)> = old.bonds.into_iter().filter( |(uid_j, _)| !NeuronsToPruneAtNextEpoch::<T>::contains_key( uid_j ) ).collect();
+        let neuron = NeuronMetadataOf::<T> {
+            version: old.version,
+            ip: old.ip,
+            port: old.port,
+            ip_type: old.ip_type,
+            uid: old.uid,
+            modality: old.modality,
+            hotkey: old.hotkey,
+            coldkey: old.coldkey,
+            active: old.active,
+            last_update: old.last_update,
+            priority: old.priority,
+            stake: old.stake,
+            rank: old.rank,
+            trust: old.trust,
+            consensus: old.consensus,
+            incentive: old.incentive,
+            dividends: old.dividends,
+            emission: old.emission,
+        };
+        Weights::<T>::insert( uid_i, old.weights );
+        Bonds::<T>::insert( uid_i, bonds );
+        Neurons::<T>::insert( uid_i, neuron );
+        writes += 3;
+    }
+    log_undecodable( "migrate_to_v3", undecodable );
+
+    StorageVersion::<T>::put( Releases::V3_0_0 );
+    writes += 1;
+    T::DbWeight::get().reads_writes( reads, writes )
+}
+
+/// Checks every stored neuron decodes in the layout of the stored release, so the pending
+/// migrations can read all of them. Run by try-runtime before the upgrade.
+pub fn pre_migrate_checks<T: Config>() -> Result<(), &'static str> {
+    let release: Releases = StorageVersion::<T>::get();
+    let undecodable: usize = raw_neurons::<T>().iter().filter( |( _, raw )| match release {
+        Releases::V1_0_0 | Releases::V2_0_0 => OldNeuronMetadata::<T::AccountId>::decode_all( raw ).is_err(),
+        Releases::V3_0_0 => NeuronMetadataOf::<T>::decode_all( raw ).is_err(),
+    }).count();
+    if undecodable > 0 {
+        log::error!( target: LOG_TARGET, "{} neurons do not decode in the layout of {:?}", undecodable, release );
+        return Err( "neurons do not decode in the layout of the stored release" );
+    }
+    Ok(())
+}
+
+/// Checks the chain is at the latest release, every stored neuron decodes in the latest layout
+/// and is indexed under its uid by Hotkeys and ColdkeyUids. Neurons the migrations could not
+/// decode are still stored in their old bytes and fail here. Run by try-runtime after the upgrade.
+pub fn post_migrate_checks<T: Config>() -> Result<(), &'static str> {
+    ensure!( StorageVersion::<T>::get() == Releases::V3_0_0, "the storage version is not the latest release" );
+    let mut undecodable: u32 = 0;
+    for ( uid_i, raw ) in raw_neurons::<T>() {
+        let neuron: NeuronMetadataOf<T> = match NeuronMetadataOf::<T>::decode_all( &raw ) {
+            Ok( neuron ) => neuron,
+            Err( _ ) => {
+                undecodable += 1;
+                continue;
+            },
+        };
+        ensure!( neuron.uid == uid_i, "a neuron is stored under another uid" );
+        ensure!( Hotkeys::<T>::get( &neuron.hotkey ) == uid_i, "a neuron is missing from Hotkeys" );
+        ensure!( ColdkeyUids::<T>::contains_key( &neuron.coldkey, uid_i ), "a neuron is missing from ColdkeyUids" );
+    }
+    if undecodable > 0 {
+        log::error!( target: LOG_TARGET, "{} neurons do not decode in the latest layout", undecodable );
+        return Err( "neurons were left undecodable by the migrations" );
+    }
+    Ok(())
+}
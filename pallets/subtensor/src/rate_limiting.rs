@@ -0,0 +1,55 @@
+use super::*;
+
+impl<T: Config> Pallet<T> {
+
+    /// Returns the number of blocks a hotkey has to wait between two calls of call_type. Without a
+    /// limit set, set_weights and serve_axon use their initial limit and all other calls are unlimited.
+    pub fn get_tx_rate_limit( call_type: &CallType ) -> u64 {
+        match TxRateLimit::<T>::get( call_type ) {
+            Some( tx_rate_limit ) => tx_rate_limit,
+            None => match call_type {
+                CallType::SetWeights => T::InitialWeightsRateLimit::get(),
+                CallType::Serve => T::InitialServingRateLimit::get(),
+                _ => 0,
+            }
+        }
+    }
+
+    /// Returns the block hotkey last made a call of call_type, None if it never made one.
+    pub fn get_last_tx_block( hotkey: &T::AccountId, call_type: &CallType ) -> Option<u64> {
+        LastTxBlock::<T>::get( call_type, hotkey )
+    }
+
+    /// Returns true if a call of call_type by hotkey in the current block would exceed the rate limit.
+    pub fn exceeds_tx_rate_limit( hotkey: &T::AccountId, call_type: &CallType ) -> bool {
+        let tx_rate_limit: u64 = Self::get_tx_rate_limit( call_type );
+        if tx_rate_limit == 0 {
+            return false;
+        }
+        match Self::get_last_tx_block( hotkey, call_type ) {
+            Some( last_tx_block ) => Self::get_current_block_as_u64().saturating_sub( last_tx_block ) < tx_rate_limit,
+            None => false,
+        }
+    }
+
+    /// Records a call of call_type by hotkey in the current block.
+    pub fn set_last_tx_block( hotkey: &T::AccountId, call_type: &CallType ) {
+        LastTxBlock::<T>::insert( call_type, hotkey, Self::get_current_block_as_u64() );
+    }
+
+    /// Moves the rate limited calls of old_hotkey over to new_hotkey, so a swap does not reset the limits.
+    pub fn swap_last_tx_blocks( old_hotkey: &T::AccountId, new_hotkey: &T::AccountId ) {
+        for call_type in [ CallType::SetWeights, CallType::Serve ].iter() {
+            if let Some( last_tx_block ) = LastTxBlock::<T>::take( call_type, old_hotkey ) {
+                LastTxBlock::<T>::insert( call_type, new_hotkey, last_tx_block );
+            }
+        }
+    }
+
+    /// Removes the rate limited calls of a pruned hotkey, so a later registration starts unlimited.
+    pub fn remove_last_tx_blocks( hotkey: &T::AccountId ) {
+        for call_type in [ CallType::SetWeights, CallType::Serve ].iter() {
+            LastTxBlock::<T>::remove( call_type, hotkey );
+        }
+    }
+}
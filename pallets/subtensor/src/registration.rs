@@ -7,8 +7,43 @@ use sp_core::{H256, U256};
 use sp_io::hashing::sha2_256;
 use frame_system::{ensure_signed};
 
+/// Number of blocks a proof of work stays valid for, counting from the block it was computed on.
+const WORK_VALIDITY: u64 = 3;
+
 impl<T: Config> Pallet<T> {
 
+    /// Checks that a registration is legitimate without registering. These checks are cheap
+    /// and run by SubtensorSignedExtension::validate, so bad registrations never enter the pool.
+    /// The per block registration cap is left to do_registration, the pool validates against the
+    /// state of the last imported block whose counter is only reset on the next block.
+    pub fn check_registration( block_number: u64, nonce: u64, work: &Vec<u8>, hotkey: &T::AccountId ) -> Result<(), Error<T>> {
+
+        // --- Check the hotkey.
+        ensure!( !Hotkeys::<T>::contains_key(hotkey), Error::<T>::AlreadyRegistered );  // Hotkey has already registered.
+
+        // --- Check block number validity.
+        let current_block_number: u64 = Self::get_current_block_as_u64_here();
+        ensure! ( current_block_number >= block_number, Error::<T>::InvalidWorkBlock ); // Can't work on future block.
+        ensure! ( current_block_number - block_number < WORK_VALIDITY, Error::<T>::InvalidWorkBlock ); // Work must have been done within 3 blocks ( stops repeat attacks ).
+
+        // --- Check difficulty.
+        ensure! ( work.len() == 32, Error::<T>::InvalidSeal ); // The work must be a 32 byte hash, vec_to_hash panics otherwise.
+        let difficulty: U256 = Self::get_difficulty();
+        let work_hash: H256 = Self::vec_to_hash( work.clone() );
+        ensure! ( Self::hash_meets_difficulty( &work_hash, difficulty ), Error::<T>::InvalidDifficulty ); // Check that the work meets difficulty.
+
+        // --- Check work.
+        let seal: H256 = Self::create_seal_hash( block_number, nonce );
+        ensure! ( seal == work_hash, Error::<T>::InvalidSeal ); // Check that this work matches hash and nonce.
+
+        Ok(())
+    }
+
+    /// Returns the number of blocks, starting with the current one, the work computed on block_number remains valid for.
+    pub fn get_work_blocks_remaining( block_number: u64 ) -> u64 {
+        block_number.saturating_add( WORK_VALIDITY ).saturating_sub( Self::get_current_block_as_u64_here() )
+    }
+
     pub fn do_registration ( 
         origin: T::Origin, 
         block_number: u64, 
@@ -21,27 +56,12 @@ impl<T: Config> Pallet<T> {
         // --- Check the callers hotkey signature.
         ensure_signed(origin)?;
 
-        // --- Check that registrations per block and hotkey.
+        // --- Check that registrations per block.
         let registrations_this_block: u64 = Self::get_registrations_this_block();
         ensure! ( registrations_this_block < Self::get_max_registratations_per_block(), Error::<T>::ToManyRegistrationsThisBlock ); // Number of registrations this block exceeded.
-        ensure!( !Hotkeys::<T>::contains_key(&hotkey), Error::<T>::AlreadyRegistered );  // Hotkey has already registered.
 
-        // --- Check block number validity.
-        let current_block_number: u64 = Self::get_current_block_as_u64_here();
-        ensure! ( current_block_number >= block_number, Error::<T>::InvalidWorkBlock ); // Can't work on future block.
-        ensure! ( current_block_number - block_number < 3, Error::<T>::InvalidWorkBlock ); // Work must have been done within 3 blocks ( stops repeat attacks ).
-
-        // --- Check difficulty.
-        let difficulty: U256 = Self::get_difficulty();
-        let work_hash: H256 = Self::vec_to_hash( work );
-        ensure! ( Self::hash_meets_difficulty( &work_hash, difficulty ), Error::<T>::InvalidDifficulty ); // Check that the work meets difficulty.
-
-        // --- Check work.
-        let seal: H256 = Self::create_seal_hash( block_number, nonce );
-        ensure! ( seal == work_hash, Error::<T>::InvalidSeal ); // Check that this work matches hash and nonce.
-        
-        // Check that the hotkey has not already been registered.
-        ensure!( !Hotkeys::<T>::contains_key(&hotkey), Error::<T>::AlreadyRegistered );
+        // --- Check the registration is legitimate, these checks are also run by the transaction pool.
+        Self::check_registration( block_number, nonce, &work, &hotkey )?;
         
         // Above this line all relevant checks that the registration is legitimate have been met. 
         // --- registration does not exceed limit.
@@ -59,6 +79,10 @@ impl<T: Config> Pallet<T> {
             // --- The metagraph is not full and we simply increment the uid.
             uid_to_set_in_metagraph = Self::get_next_uid();
         } else {
+            // --- A pending step holds results for the uids it started with, pruning one of them
+            // would hand those to the new neuron or drop them. The registration waits for the step.
+            ensure!( !Self::has_pending_step(), Error::<T>::StepInProgress );
+
             // TODO( const ): this should be a function and we should be able to purge peers down to a set number.
             // We iterate over neurons in memory and find min score.
             // Pruning score values have already been computed at the previous mechanism step.
@@ -112,23 +136,30 @@ impl<T: Config> Pallet<T> {
             let hotkey_to_prune = neuron_to_prune.hotkey;
 
             // Next we will add this prunned peer to NeuronsToPruneAtNextEpoch.
-            // We record this set because we need to remove all bonds owned in this uid.
-            // neuron.bonds records all bonds this peer owns which will be removed by default. 
-            // However there are other peers with bonds in this peer, these need to be cleared as well.
-            // NOTE(const): In further iterations it will be beneficial to build bonds as a double
-            // iterable set so that deletions become easier. 
+            // We record this set so that the next step can report which uids were replaced.
+            // Bonds[uid] records all bonds this peer owns which will be overwritten below.
+            // However there are other peers with bonds in this peer, these are cleared here as well.
             NeuronsToPruneAtNextEpoch::<T>::insert( uid_to_set_in_metagraph, uid_to_set_in_metagraph ); // Subtrate does not contain a set storage item.
+            Self::remove_bonds_in_uid( uid_to_set_in_metagraph );
             // Finally, we need to unstake all the funds that this peer had staked. 
-            // These funds are deposited back into the coldkey account so that no funds are destroyed. 
-            let stake_to_be_added_on_coldkey = Self::u64_to_balance( neuron_to_prune.stake );
+            // These funds are deposited back into the coldkey accounts so that no funds are destroyed. 
+            // Nominators get back their own stake, the owner gets the rest.
+            let nominator_stake: u64 = Self::refund_nominators( &hotkey_to_prune );
+            let stake_to_be_added_on_coldkey = Self::u64_to_balance( neuron_to_prune.stake.saturating_sub( nominator_stake ) );
             Self::add_balance_to_coldkey_account( &neuron_to_prune.coldkey, stake_to_be_added_on_coldkey.unwrap() );
             Self::decrease_total_stake( neuron_to_prune.stake );
 
             // Remove hotkey from hotkeys set, 
             // and to clean up and prune whatever extra hotkeys there are on top of the existing max_allowed_uids
             if Hotkeys::<T>::contains_key(&hotkey_to_prune) {
-                Hotkeys::<T>::remove( hotkey_to_prune );
+                Hotkeys::<T>::remove( &hotkey_to_prune );
             }
+
+            // Remove the rate limited calls of the pruned hotkey.
+            Self::remove_last_tx_blocks( &hotkey_to_prune );
+
+            // Remove the uid from the previous owner's coldkey index.
+            ColdkeyUids::<T>::remove( &neuron_to_prune.coldkey, uid_to_set_in_metagraph );
         }
 
         // --- Next we create a new entry in the table with the new metadata.
@@ -151,8 +182,6 @@ impl<T: Config> Pallet<T> {
             incentive: 0,
             emission: 0,
             dividends: 0,
-            bonds: vec![],
-            weights: vec![(uid_to_set_in_metagraph, u32::MAX)], // self weight set to 1.
         };
 
         // --- Update avg registrations per 1000 block.
@@ -162,12 +191,98 @@ impl<T: Config> Pallet<T> {
         // --- We deposit the neuron registered event.
         BlockAtRegistration::<T>::insert( uid_to_set_in_metagraph, current_block ); // Set immunity momment.
         Neurons::<T>::insert( uid_to_set_in_metagraph, neuron ); // Insert neuron info under uid.
+        Weights::<T>::insert( uid_to_set_in_metagraph, vec![(uid_to_set_in_metagraph, u32::MAX)] ); // self weight set to 1.
+        Bonds::<T>::remove( uid_to_set_in_metagraph ); // New neurons own no bonds.
+        RewardDestinations::<T>::remove( uid_to_set_in_metagraph ); // New neurons restake.
+        FeelessCalls::<T>::remove( uid_to_set_in_metagraph ); // New neurons start with a full fee-less quota.
         Hotkeys::<T>::insert( &hotkey, uid_to_set_in_metagraph ); // Add hotkey into hotkey set.
+        ColdkeyUids::<T>::insert( &coldkey, uid_to_set_in_metagraph, () ); // Add uid into the coldkey's owned set.
         Self::deposit_event(Event::NeuronRegistered( uid_to_set_in_metagraph ));
 
         Ok(())
     }
 
+    /// Registers a neuron from the genesis config under the next uid, with its stake, axon and a self weight.
+    /// The caller accounts for the stake in TotalStake and TotalIssuance.
+    pub fn register_genesis_neuron( genesis_neuron: &GenesisNeuronOf<T> ) -> u32 {
+        assert!( !Hotkeys::<T>::contains_key( &genesis_neuron.hotkey ), "genesis hotkey registered twice" );
+        let uid: u32 = Self::get_next_uid();
+        let neuron = NeuronMetadataOf::<T> {
+            version: genesis_neuron.version,
+            ip: genesis_neuron.ip,
+            port: genesis_neuron.port,
+            ip_type: genesis_neuron.ip_type,
+            uid: uid,
+            modality: genesis_neuron.modality,
+            hotkey: genesis_neuron.hotkey.clone(),
+            coldkey: genesis_neuron.coldkey.clone(),
+            active: 1,
+            last_update: 0, 
+            priority: 0,
+            stake: genesis_neuron.stake,
+            rank: 0,
+            trust: 0,
+            consensus: 0,
+            incentive: 0,
+            emission: 0,
+            dividends: 0,
+        };
+        BlockAtRegistration::<T>::insert( uid, 0 );
+        Neurons::<T>::insert( uid, neuron );
+        Weights::<T>::insert( uid, vec![(uid, u32::MAX)] );
+        Hotkeys::<T>::insert( &genesis_neuron.hotkey, uid );
+        ColdkeyUids::<T>::insert( &genesis_neuron.coldkey, uid, () );
+        uid
+    }
+
+    /// Replaces the hotkey of a registered neuron. The call is signed by the coldkey of the neuron.
+    /// The uid, stake, weights and bonds stay as they are, only the key they are reached through changes.
+    pub fn do_swap_hotkey( origin: T::Origin, old_hotkey: T::AccountId, new_hotkey: T::AccountId ) -> dispatch::DispatchResult {
+
+        // --- Check the callers coldkey signature.
+        let coldkey = ensure_signed( origin )?;
+
+        // --- Check the old hotkey is registered under the calling coldkey.
+        ensure!( Hotkeys::<T>::contains_key( &old_hotkey ), Error::<T>::NotRegistered );
+        let mut neuron = Self::get_neuron_for_hotkey( &old_hotkey );
+        ensure!( Self::neuron_belongs_to_coldkey( &neuron, &coldkey ), Error::<T>::NonAssociatedColdKey );
+
+        // --- Check the new hotkey is not registered, this also rejects swapping a hotkey with itself.
+        ensure!( !Hotkeys::<T>::contains_key( &new_hotkey ), Error::<T>::AlreadyRegistered );
+
+        // --- Point the uid at the new hotkey.
+        let uid: u32 = neuron.uid;
+        neuron.hotkey = new_hotkey.clone();
+        Neurons::<T>::insert( uid, neuron );
+        Hotkeys::<T>::remove( &old_hotkey );
+        Hotkeys::<T>::insert( &new_hotkey, uid );
+        Self::swap_delegate_hotkey( &old_hotkey, &new_hotkey );
+        Self::swap_last_tx_blocks( &old_hotkey, &new_hotkey );
+        Self::deposit_event( Event::HotkeySwapped( old_hotkey, new_hotkey ) );
+
+        Ok(())
+    }
+
+    /// Removes every bond held in uid_j by other neurons.
+    pub fn remove_bonds_in_uid( uid_j: u32 ) {
+        let rows: Vec<(u32, Vec<(u32,u64)>)> = <Bonds<T> as IterableStorageMap<u32, Vec<(u32,u64)>>>::iter()
+            .filter( |(_, bonds_i)| bonds_i.iter().any( |(uid_k, _)| *uid_k == uid_j ) )
+            .collect();
+        for ( uid_i, mut bonds_i ) in rows {
+            bonds_i.retain( |(uid_k, _)| *uid_k != uid_j );
+            Bonds::<T>::insert( uid_i, bonds_i );
+        }
+
+        // Bond rows staged by a step running across blocks are cleared as well.
+        let pending_rows: Vec<(u32, Vec<(u32,u64)>)> = <PendingBonds<T> as IterableStorageMap<u32, Vec<(u32,u64)>>>::iter()
+            .filter( |(_, bonds_i)| bonds_i.iter().any( |(uid_k, _)| *uid_k == uid_j ) )
+            .collect();
+        for ( uid_i, mut bonds_i ) in pending_rows {
+            bonds_i.retain( |(uid_k, _)| *uid_k != uid_j );
+            PendingBonds::<T>::insert( uid_i, bonds_i );
+        }
+    }
+
     pub fn get_current_block_as_u64_here( ) -> u64 {
         let block_as_u64: u64 = TryInto::try_into( system::Pallet::<T>::block_number() ).ok().expect("blockchain will not exceed 2^64 blocks; QED.");
         block_as_u64
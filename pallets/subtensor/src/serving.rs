@@ -11,6 +11,7 @@ impl<T: Config> Pallet<T> {
         ensure!( is_valid_modality(modality), Error::<T>::InvalidModality );
         ensure!( is_valid_ip_type(ip_type), Error::<T>::InvalidIpType );
         ensure!( is_valid_ip_address(ip_type, ip), Error::<T>::InvalidIpAddress );
+        ensure!( !Self::exceeds_tx_rate_limit(&hotkey_id, &CallType::Serve), Error::<T>::TxRateLimitExceeded );
   
         // --- We get the uid associated with this hotkey account.
         let uid = Self::get_uid_for_hotkey(&hotkey_id);
@@ -26,6 +27,7 @@ impl<T: Config> Pallet<T> {
 
         // --- We deposit the neuron updated event
         Neurons::<T>::insert(uid, neuron);
+        Self::set_last_tx_block(&hotkey_id, &CallType::Serve);
         Self::deposit_event(Event::AxonServed(uid));
         
         Ok(())
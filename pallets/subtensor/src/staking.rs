@@ -1,4 +1,7 @@
 use super::*;
+use sp_std::convert::TryInto;
+use frame_support::traits::Imbalance;
+use sp_runtime::traits::Zero;
 
 impl<T: Config> Pallet<T> {
     /***********************************************************
@@ -20,8 +23,9 @@ impl<T: Config> Pallet<T> {
         ensure!(Self::is_uid_active(neuron.uid), Error::<T>::NotRegistered);
 
         // ---- We check that the NeuronMetadata is linked to the calling
-        // cold key, otherwise throw a NonAssociatedColdKey error.
-        ensure!(Self::neuron_belongs_to_coldkey(&neuron, &coldkey), Error::<T>::NonAssociatedColdKey);
+        // cold key or that the hotkey accepts nominators, otherwise throw a NonAssociatedColdKey error.
+        let is_owner: bool = Self::neuron_belongs_to_coldkey(&neuron, &coldkey);
+        ensure!(is_owner || Self::is_delegate(&hotkey), Error::<T>::NonAssociatedColdKey);
 
         // ---- We check that the calling coldkey contains enough funds to
         // create the staking transaction.
@@ -32,6 +36,11 @@ impl<T: Config> Pallet<T> {
         ensure!(Self::remove_balance_from_coldkey_account(&coldkey, stake_as_balance.unwrap()) == true, Error::<T>::BalanceWithdrawalError);
         Self::add_stake_to_neuron_hotkey_account(neuron.uid, stake_to_be_added);
 
+        // ---- Stake from other coldkeys is tracked per nominator.
+        if !is_owner {
+            Self::add_nominator_stake(&hotkey, &coldkey, stake_to_be_added);
+        }
+
         // ---- Emit the staking event.
         Self::deposit_event(Event::StakeAdded(hotkey, stake_to_be_added));
 
@@ -52,8 +61,9 @@ impl<T: Config> Pallet<T> {
     ///
     /// It throws the following errors if there is something wrong
     /// - NotRegistered : The suplied hotkey is not in use. This ususally means a node that uses this key has not subscribed yet, or has unsubscribed
-    /// - NonAssociatedColdKey : The supplied hotkey account id is not subscribed using the supplied cold key
-    /// - NotEnoughStaketoWithdraw : The ammount of stake available in the hotkey account is lower than the requested amount
+    /// - NonAssociatedColdKey : The supplied hotkey account id is not subscribed using the supplied cold key,
+    /// and the cold key holds no nominator stake on it
+    /// - NotEnoughStaketoWithdraw : The ammount of stake the cold key holds on the hotkey account is lower than the requested amount
     /// - CouldNotConvertToBalance : A conversion error occured while converting stake from u64 to Balance
     ///
     pub fn do_remove_stake(origin: T::Origin, hotkey: T::AccountId, stake_to_be_removed: u64) -> dispatch::DispatchResult {
@@ -71,20 +81,26 @@ impl<T: Config> Pallet<T> {
         ensure!(Self::is_uid_active(neuron.uid), Error::<T>::NotRegistered);
 
         // ---- We check that the NeuronMetadata is linked to the calling
-        // cold key, otherwise throw a NonAssociatedColdKey error.
-        ensure!(Self::neuron_belongs_to_coldkey(&neuron, &coldkey), Error::<T>::NonAssociatedColdKey);
-
-        // ---- We check that the hotkey has enough stake to withdraw
-        // and then withdraw from the account.
-        ensure!(Self::has_enough_stake(&neuron, stake_to_be_removed), Error::<T>::NotEnoughStaketoWithdraw);
-        let stake_to_be_added_as_currency = Self::u64_to_balance(stake_to_be_removed);
-        ensure!(stake_to_be_added_as_currency.is_some(), Error::<T>::CouldNotConvertToBalance);
+        // cold key or that it nominated the hotkey, otherwise throw a NonAssociatedColdKey error.
+        // Owner and nominators can each only withdraw the stake they hold.
+        let is_owner: bool = Self::neuron_belongs_to_coldkey(&neuron, &coldkey);
+        if is_owner {
+            ensure!(Self::has_enough_stake(&neuron, stake_to_be_removed), Error::<T>::NotEnoughStaketoWithdraw);
+        } else {
+            ensure!(NominatorStake::<T>::contains_key(&hotkey, &coldkey), Error::<T>::NonAssociatedColdKey);
+            ensure!(Self::get_nominator_stake(&hotkey, &coldkey) >= stake_to_be_removed, Error::<T>::NotEnoughStaketoWithdraw);
+        }
 
-        // --- We perform the withdrawl by converting the stake to a u64 balance
-        // and deposit the balance into the coldkey account. If the coldkey account
-        // does not exist it is created.
-        Self::add_balance_to_coldkey_account(&coldkey, stake_to_be_added_as_currency.unwrap());
+        // --- We perform the withdrawl by unbonding the stake and removing it from the neuron, from here on it no longer
+        // counts in the mechanism. The balance is deposited into the coldkey account once the stake
+        // has unbonded, right away when the unbonding period is zero. If the coldkey account
+        // does not exist it is created. Unbonding fails before writing anything if the stake
+        // can not be converted to a balance.
+        Self::unbond_stake(&coldkey, stake_to_be_removed)?;
         Self::remove_stake_from_neuron_hotkey_account(neuron.uid, stake_to_be_removed);
+        if !is_owner {
+            Self::remove_nominator_stake(&hotkey, &coldkey, stake_to_be_removed);
+        }
 
         // ---- Emit the unstaking event.
         Self::deposit_event(Event::StakeRemoved(hotkey, stake_to_be_removed));
@@ -94,10 +110,256 @@ impl<T: Config> Pallet<T> {
     }
 
 
+    /// This function moves stake between two hotkeys registered under the calling coldkey,
+    /// without passing it through the coldkey balance. The total stake is unchanged.
+    ///
+    /// It throws the following errors if there is something wrong
+    /// - NotRegistered : One of the hotkeys is not in use.
+    /// - SameHotkey : The origin and destination hotkeys are the same.
+    /// - NonAssociatedColdKey : One of the hotkeys is not subscribed using the calling coldkey
+    /// - NotEnoughStaketoWithdraw : The origin hotkey holds less stake than the requested amount
+    ///
+    pub fn do_move_stake(origin: T::Origin, origin_hotkey: T::AccountId, destination_hotkey: T::AccountId, stake_to_be_moved: u64) -> dispatch::DispatchResult {
+
+        // ---- We check the transaction is signed by the caller
+        // and retrieve the T::AccountId pubkey information.
+        let coldkey = ensure_signed(origin)?;
+
+        // ---- We check both hotkeys are active and distinct.
+        let ( origin_neuron, destination_neuron ) = Self::get_stake_move_neurons(&origin_hotkey, &destination_hotkey)?;
+
+        // ---- We check that both neurons are linked to the calling
+        // cold key, otherwise throw a NonAssociatedColdKey error.
+        ensure!(Self::neuron_belongs_to_coldkey(&origin_neuron, &coldkey), Error::<T>::NonAssociatedColdKey);
+        ensure!(Self::neuron_belongs_to_coldkey(&destination_neuron, &coldkey), Error::<T>::NonAssociatedColdKey);
+
+        // ---- We check that the origin hotkey has enough stake to move.
+        ensure!(Self::has_enough_stake(&origin_neuron, stake_to_be_moved), Error::<T>::NotEnoughStaketoWithdraw);
+        Self::move_stake_between_uids(origin_neuron.uid, destination_neuron.uid, stake_to_be_moved);
+
+        // ---- Emit the move event.
+        Self::deposit_event(Event::StakeMoved(origin_hotkey, destination_hotkey, stake_to_be_moved));
+
+        // --- Done and ok.
+        Ok(())
+    }
+
+    /// This function transfers stake from a hotkey registered under the calling coldkey to
+    /// any other registered hotkey. The stake then belongs to the coldkey of the destination
+    /// neuron, which is the only one able to remove it. The total stake is unchanged.
+    ///
+    /// It throws the following errors if there is something wrong
+    /// - NotRegistered : One of the hotkeys is not in use.
+    /// - SameHotkey : The origin and destination hotkeys are the same.
+    /// - NonAssociatedColdKey : The origin hotkey is not subscribed using the calling coldkey
+    /// - NotEnoughStaketoWithdraw : The origin hotkey holds less stake than the requested amount
+    ///
+    pub fn do_transfer_stake(origin: T::Origin, origin_hotkey: T::AccountId, destination_hotkey: T::AccountId, stake_to_be_transferred: u64) -> dispatch::DispatchResult {
+
+        // ---- We check the transaction is signed by the caller
+        // and retrieve the T::AccountId pubkey information.
+        let coldkey = ensure_signed(origin)?;
+
+        // ---- We check both hotkeys are active and distinct.
+        let ( origin_neuron, destination_neuron ) = Self::get_stake_move_neurons(&origin_hotkey, &destination_hotkey)?;
+
+        // ---- Only the origin neuron needs to be linked to the calling cold key.
+        ensure!(Self::neuron_belongs_to_coldkey(&origin_neuron, &coldkey), Error::<T>::NonAssociatedColdKey);
+
+        // ---- We check that the origin hotkey has enough stake to transfer.
+        ensure!(Self::has_enough_stake(&origin_neuron, stake_to_be_transferred), Error::<T>::NotEnoughStaketoWithdraw);
+        Self::move_stake_between_uids(origin_neuron.uid, destination_neuron.uid, stake_to_be_transferred);
+
+        // ---- Emit the transfer event.
+        Self::deposit_event(Event::StakeTransferred(origin_hotkey, destination_hotkey, stake_to_be_transferred));
+
+        // --- Done and ok.
+        Ok(())
+    }
+
+    /// This function pays the stake the calling coldkey removed, and which has finished unbonding,
+    /// into the coldkey account. Chunks still unbonding stay queued.
+    ///
+    /// It throws the following errors if there is something wrong
+    /// - NoUnbondedStake : None of the stake removed by the coldkey has finished unbonding
+    ///
+    pub fn do_withdraw_unbonded(origin: T::Origin) -> dispatch::DispatchResult {
+
+        // ---- We check the transaction is signed by the caller
+        // and retrieve the T::AccountId pubkey information.
+        let coldkey = ensure_signed(origin)?;
+
+        // ---- We split off the chunks whose unlock block has passed.
+        let current_block: u64 = Self::get_current_block_as_u64();
+        let ( unbonded, unbonding ): ( Vec<(u64, u64)>, Vec<(u64, u64)> ) = Unbonding::<T>::get(&coldkey)
+            .into_iter()
+            .partition(|(_, unlock_block)| *unlock_block <= current_block);
+        ensure!(!unbonded.is_empty(), Error::<T>::NoUnbondedStake);
+        let amount: u64 = unbonded.iter().map(|(amount, _)| *amount).sum();
+        let amount_as_currency = Self::u64_to_balance(amount);
+        ensure!(amount_as_currency.is_some(), Error::<T>::CouldNotConvertToBalance);
+
+        // ---- We deposit the balance and keep what is still unbonding.
+        Self::add_balance_to_coldkey_account(&coldkey, amount_as_currency.unwrap());
+        if unbonding.is_empty() {
+            Unbonding::<T>::remove(&coldkey);
+        } else {
+            Unbonding::<T>::insert(&coldkey, unbonding);
+        }
+
+        // ---- Emit the withdrawl event.
+        Self::deposit_event(Event::UnbondedWithdrawn(coldkey, amount));
+
+        // --- Done and ok.
+        Ok(())
+    }
+
+    /// This function sets where the emission of the neuron behind the hotkey goes. It is called
+    /// through an extrinsic signed by the coldkey of the neuron.
+    ///
+    /// It throws the following errors if there is something wrong
+    /// - NotRegistered : The hotkey is not in use
+    /// - NonAssociatedColdKey : The hotkey is not subscribed using the calling cold key
+    ///
+    pub fn do_set_reward_destination(origin: T::Origin, hotkey: T::AccountId, destination: RewardDestinationOf<T>) -> dispatch::DispatchResult {
+
+        // ---- We check the transaction is signed by the caller
+        // and retrieve the T::AccountId pubkey information.
+        let coldkey = ensure_signed(origin)?;
+
+        // ---- We check the hotkey is registered under the calling coldkey.
+        ensure!(Self::is_hotkey_active(&hotkey), Error::<T>::NotRegistered);
+        let neuron = Self::get_neuron_for_hotkey(&hotkey);
+        ensure!(Self::neuron_belongs_to_coldkey(&neuron, &coldkey), Error::<T>::NonAssociatedColdKey);
+
+        // ---- Restaking is the default, it is not stored.
+        if destination == RewardDestination::Stake {
+            RewardDestinations::<T>::remove(neuron.uid);
+        } else {
+            RewardDestinations::<T>::insert(neuron.uid, destination.clone());
+        }
+
+        // ---- Emit the update event.
+        Self::deposit_event(Event::RewardDestinationSet(hotkey, destination));
+
+        // --- Done and ok.
+        Ok(())
+    }
+
     /********************************
     --==[[  Helper functions   ]]==--
     *********************************/
 
+    /// Pays the reward of a neuron according to its reward destination and returns the part of it
+    /// to be added onto the neuron stake. Rewards paid into an account are added to TotalIssuance here,
+    /// the caller adds the restaked part to TotalIssuance and TotalStake. A reward the account cannot
+    /// receive, e.g. below the existential deposit of a new account, is restaked instead.
+    ///
+    pub fn pay_reward(neuron: &NeuronMetadataOf<T>, reward: u64) -> u64 {
+        let account: T::AccountId = match RewardDestinations::<T>::get(neuron.uid) {
+            RewardDestination::Stake => return reward,
+            RewardDestination::Coldkey => neuron.coldkey.clone(),
+            RewardDestination::Account(account) => account,
+        };
+        if reward == 0 {
+            return 0;
+        }
+        let reward_as_balance = match Self::u64_to_balance(reward) {
+            Some(balance) => balance,
+            None => return reward,
+        };
+        let imbalance = T::Currency::deposit_creating(&account, reward_as_balance);
+        let paid: u64 = TryInto::<u64>::try_into(imbalance.peek()).ok().unwrap_or(0);
+        drop(imbalance);
+        if paid == 0 {
+            return reward;
+        }
+        TotalIssuance::<T>::mutate(|val| *val += paid);
+        Self::deposit_event(Event::RewardPaid(account, paid));
+        reward - paid
+    }
+
+    /// Returns the part of the reward pay_reward adds onto the neuron stake, without paying anything.
+    ///
+    pub fn get_restaked_reward(neuron: &NeuronMetadataOf<T>, reward: u64) -> u64 {
+        let account: T::AccountId = match RewardDestinations::<T>::get(neuron.uid) {
+            RewardDestination::Stake => return reward,
+            RewardDestination::Coldkey => neuron.coldkey.clone(),
+            RewardDestination::Account(account) => account,
+        };
+        if reward == 0 {
+            return 0;
+        }
+        match Self::u64_to_balance(reward) {
+            // A deposit creating the account must cover the existential deposit.
+            Some(balance) if !T::Currency::total_balance(&account).is_zero() || balance >= T::Currency::minimum_balance() => 0,
+            _ => reward,
+        }
+    }
+
+    /// Queues stake removed from a neuron until the unbonding period has passed. With a period of
+    /// zero the stake is deposited into the coldkey account right away.
+    /// Chunks unlocking at the same block are merged.
+    ///
+    /// It throws the following errors if there is something wrong
+    /// - CouldNotConvertToBalance : A conversion error occured while converting stake from u64 to Balance
+    /// - TooManyUnbondingChunks : The coldkey already has MaxUnbondingChunks chunks unbonding
+    ///
+    pub fn unbond_stake(coldkey: &T::AccountId, amount: u64) -> dispatch::DispatchResult {
+        let amount_as_currency = Self::u64_to_balance(amount);
+        ensure!(amount_as_currency.is_some(), Error::<T>::CouldNotConvertToBalance);
+        let unbonding_period: u64 = Self::get_unbonding_period();
+        if unbonding_period == 0 {
+            Self::add_balance_to_coldkey_account(coldkey, amount_as_currency.unwrap());
+            return Ok(());
+        }
+        let unlock_block: u64 = Self::get_current_block_as_u64().saturating_add(unbonding_period);
+        let mut chunks: Vec<(u64, u64)> = Unbonding::<T>::get(coldkey);
+        match chunks.last_mut() {
+            Some((chunk_amount, chunk_unlock_block)) if *chunk_unlock_block == unlock_block => *chunk_amount = chunk_amount.saturating_add(amount),
+            _ => {
+                ensure!(chunks.len() < T::MaxUnbondingChunks::get() as usize, Error::<T>::TooManyUnbondingChunks);
+                chunks.push((amount, unlock_block));
+            }
+        }
+        Unbonding::<T>::insert(coldkey, chunks);
+        Self::deposit_event(Event::StakeUnbonding(coldkey.clone(), amount, unlock_block));
+        Ok(())
+    }
+
+    /// Moves the stake old_coldkey has unbonding over to new_coldkey. Chunks are merged into the
+    /// ones of new_coldkey by unlock block, and kept ordered by it. Chunks over MaxUnbondingChunks
+    /// are merged into the last one, so they unlock later rather than earlier.
+    ///
+    pub fn swap_unbonding(old_coldkey: &T::AccountId, new_coldkey: &T::AccountId) {
+        let old_chunks: Vec<(u64, u64)> = Unbonding::<T>::take(old_coldkey);
+        if old_chunks.is_empty() {
+            return;
+        }
+        Unbonding::<T>::mutate(new_coldkey, |chunks| {
+            for (amount, unlock_block) in old_chunks {
+                match chunks.iter_mut().find(|(_, chunk_unlock_block)| *chunk_unlock_block == unlock_block) {
+                    Some((chunk_amount, _)) => *chunk_amount += amount,
+                    None => chunks.push((amount, unlock_block)),
+                }
+            }
+            chunks.sort_by_key(|(_, unlock_block)| *unlock_block);
+            let max_chunks: usize = (T::MaxUnbondingChunks::get() as usize).max(1);
+            while chunks.len() > max_chunks {
+                let (amount, _) = chunks.remove(chunks.len() - 2);
+                if let Some((last_amount, _)) = chunks.last_mut() {
+                    *last_amount = last_amount.saturating_add(amount);
+                }
+            }
+        });
+    }
+
+    /// Returns the stake the coldkey removed which has not been withdrawn yet, unbonded or not.
+    ///
+    pub fn get_unbonding_stake(coldkey: &T::AccountId) -> u64 {
+        Unbonding::<T>::get(coldkey).iter().map(|(amount, _)| *amount).sum()
+    }
+
     pub fn get_stake_of_neuron_hotkey_account_by_uid(uid: u32) -> u64 {
         return Self::get_neuron_for_uid(uid).stake
     }
@@ -177,6 +439,31 @@ impl<T: Config> Pallet<T> {
         Self::decrease_total_stake(amount);
     }
 
+    /// Returns the neurons of the origin and destination hotkeys of a stake move,
+    /// checking that both are registered and that they differ.
+    ///
+    pub fn get_stake_move_neurons(origin_hotkey: &T::AccountId, destination_hotkey: &T::AccountId) -> Result<(NeuronMetadataOf<T>, NeuronMetadataOf<T>), dispatch::DispatchError> {
+        ensure!(Self::is_hotkey_active(origin_hotkey), Error::<T>::NotRegistered);
+        ensure!(Self::is_hotkey_active(destination_hotkey), Error::<T>::NotRegistered);
+        ensure!(origin_hotkey != destination_hotkey, Error::<T>::SameHotkey);
+        let origin_neuron = Self::get_neuron_for_hotkey(origin_hotkey);
+        let destination_neuron = Self::get_neuron_for_hotkey(destination_hotkey);
+        ensure!(Self::is_uid_active(origin_neuron.uid), Error::<T>::NotRegistered);
+        ensure!(Self::is_uid_active(destination_neuron.uid), Error::<T>::NotRegistered);
+        Ok((origin_neuron, destination_neuron))
+    }
+
+    /// Moves stake from one neuron to another. The amount leaves and re-enters the total
+    /// stake within this call, so the total is the same afterwards.
+    ///
+    /// A check if there is enough stake in the origin account should have been performed
+    /// before this function is called.
+    ///
+    pub fn move_stake_between_uids(origin_uid: u32, destination_uid: u32, amount: u64) {
+        Self::remove_stake_from_neuron_hotkey_account(origin_uid, amount);
+        Self::add_stake_to_neuron_hotkey_account(destination_uid, amount);
+    }
+
     /// This adds stake (balance) to a cold key account. It takes the account id of the coldkey account and a Balance as parameters.
     /// The Balance parameter is a from u64 converted number. This is needed for T::Currency to work.
     /// Make sure stake is removed from another account before calling this method, otherwise you'll end up with double the value
@@ -229,11 +516,11 @@ impl<T: Config> Pallet<T> {
         return T::Currency::free_balance(&coldkey);
     }
 
-    /// Checks if the hotkey account of the specified account has enough stake to be able to withdraw
-    /// the requested amount.
+    /// Checks if the owner of the specified neuron has enough stake on it to be able to withdraw
+    /// the requested amount. Stake held by nominators of the hotkey does not count.
     ///
     pub fn has_enough_stake(neuron: &NeuronMetadataOf<T>, amount: u64) -> bool {
-        return neuron.stake >= amount;
+        return Self::get_owner_stake(neuron) >= amount;
     }
 
     /// Returns true if there is an entry for uid in the Stake map,
@@ -1,14 +1,15 @@
 use super::*;
 use sp_std::convert::TryInto;
 use substrate_fixed::types::I65F63;
-use substrate_fixed::transcendental::exp;
-use substrate_fixed::transcendental::log2;
 use frame_support::IterableStorageMap;
+use frame_support::weights::Weight;
+use frame_support::traits::{Get, Currency, Imbalance};
 use sp_std::if_std; // Import into scope the if_std! macro.
 
 impl<T: Config> Pallet<T> {
 
-    pub fn update_difficulty() {
+    /// Adjusts the registration difficulty and returns the weight consumed.
+    pub fn update_difficulty() -> Weight {
 
         // --- Set registrations per block to 0 after each block.
         RegistrationsThisBlock::<T>::set( 0 );
@@ -70,6 +71,8 @@ impl<T: Config> Pallet<T> {
             RegistrationsThisInterval::<T>::set( 0 );
         }
 
+        // Upper bound, assumes the adjustment interval was reached.
+        T::DbWeight::get().reads_writes( 10, 4 )
     }
 
     /// Block setup: Computation performed each block which updates the incentive mechanism and distributes new stake as dividends.
@@ -142,295 +145,522 @@ impl<T: Config> Pallet<T> {
     /// 
     /// Note, operations 1 and 2 are computed together. 
     ////
-    pub fn mechanism_step ( emission_this_step: u64 ) {
-
-        // The amount this mechanism step emits on this block.
-        let block_emission: I65F63 = I65F63::from_num( emission_this_step );
+    /// Returns the weight consumed, computed from the neuron count and the number of
+    /// non-zero weights and bonds read.
+    pub fn mechanism_step ( emission_this_step: u64 ) -> Weight {
         if_std! {
             println!( "step" );
         } 
 
-        // === Complete foundation distribution ===
-        //let foundation_distribution_per_hundred: u64 = Self::get_foundation_distribution();
-        //let foundation_distribution_percent: I65F63 = I65F63::from_num( foundation_distribution_per_hundred ) / I65F63::from_num( 100 );
-        //let foundation_distribution_as_float: I65F63 = block_emission_total * foundation_distribution_percent;
-
-        // Sink distribution into account.
-        //let foundation_account: T::AccountId = Self::get_foundation_account();
-        //let foundation_distribution_as_u64: u64 = foundation_distribution_as_float.to_num::<u64>();
-        //let foundation_distribution_as_balance: <<T as Config>::Currency as Currency<<T as system::Config>::AccountId>>::Balance = Self::u64_to_balance( foundation_distribution_as_u64 ).unwrap();
-        //T::Currency::deposit_creating( &foundation_account, foundation_distribution_as_balance );
-        
-        // The amount this mechanism step emits with the foundation amount removed.
-        //let block_emission: I65F63 = block_emission_total - foundation_distribution_as_float;
-      
+        // Pay the foundation its share first, the neurons share what is left.
+        let foundation_emission: u64 = Self::distribute_to_foundation( emission_this_step );
+        let emission_this_step: u64 = emission_this_step - foundation_emission;
+
+        // Compute the step against the current state, then write it back.
+        let ( step, weights_len, bonds_len ) = Self::compute_mechanism_step_with_sizes( emission_this_step );
+        let n: u32 = step.stake.len() as u32;
+        let mut bonds: Vec<Vec<(u32,u64)>> = step.bonds;
+        let mut nominators_len: u32 = 0;
+        let mut total_restaked: u64 = 0;
+        for ( uid_i, mut neuron_i ) in <Neurons<T> as IterableStorageMap<u32, NeuronMetadataOf<T>>>::iter() {
+            // Update table entry.
+            neuron_i.active = step.active[ uid_i as usize ];
+            neuron_i.priority = step.priority[ uid_i as usize ];
+            neuron_i.emission = step.emission[ uid_i as usize ];
+            neuron_i.rank = step.ranks[ uid_i as usize ];
+            neuron_i.trust = step.trust[ uid_i as usize ];
+            neuron_i.consensus = step.consensus[ uid_i as usize ];
+            neuron_i.incentive = step.incentive[ uid_i as usize ];
+            neuron_i.dividends = step.dividends[ uid_i as usize ];
+
+            // Pay out the emission, nominators of a delegate always restake.
+            let stake_i: u64 = step.stake[ uid_i as usize ] - step.emission[ uid_i as usize ];
+            let ( nominators_i, nominator_emission_i ) = Self::distribute_to_nominators( &neuron_i.hotkey, stake_i, neuron_i.emission );
+            let restaked_i: u64 = nominator_emission_i + Self::pay_reward( &neuron_i, neuron_i.emission - nominator_emission_i );
+            neuron_i.stake = stake_i + restaked_i;
+            nominators_len += nominators_i;
+            total_restaked += restaked_i;
+            Neurons::<T>::insert( neuron_i.uid, neuron_i );
+
+            // Only rows whose bonds changed are written back.
+            let bonds_i: Vec<(u32,u64)> = sp_std::mem::take( &mut bonds[ uid_i as usize ] );
+            if Bonds::<T>::get( uid_i ) != bonds_i {
+                Bonds::<T>::insert( uid_i, bonds_i );
+            }
+
+            // This where we remove the neurons to prune (clearing the table.)
+            if NeuronsToPruneAtNextEpoch::<T>::contains_key( uid_i ) {
+                NeuronsToPruneAtNextEpoch::<T>::remove ( uid_i );
+            } 
+        }
+
+        // Update totals. The foundation distribution and the rewards paid into accounts
+        // are already counted into the issuance.
+        TotalEmission::<T>::set( step.total_emission );
+        TotalBondsPurchased::<T>::set( step.total_bonds_purchased );
+        TotalIssuance::<T>::mutate( |val| *val += total_restaked );
+        TotalStake::<T>::mutate( |val| *val += total_restaked );
+        LastMechansimStepBlock::<T>::set( step.block );
+
+        T::WeightInfo::mechanism_step( n, weights_len, bonds_len )
+            .saturating_add( T::DbWeight::get().reads_writes( 3, 2 ) ) // Foundation distribution.
+            .saturating_add( T::DbWeight::get().reads_writes( nominators_len as Weight, nominators_len as Weight ) ) // Nominator payouts.
+    }
+
+    /// Computes the mechanism step against the current state without writing anything.
+    /// Returns the values mechanism_step would write into Neurons, the change in each
+    /// bond entry and the uids whose incoming bonds are cleared this step.
+    pub fn compute_mechanism_step ( emission_this_step: u64 ) -> MechanismStepResult {
+        let emission_this_step: u64 = emission_this_step - Self::get_foundation_emission( emission_this_step );
+        let ( mut step, _, _ ) = Self::compute_mechanism_step_with_sizes( emission_this_step );
+
+        // Pay out the emission as mechanism_step does, counting only what is restaked.
+        for ( uid_i, neuron_i ) in <Neurons<T> as IterableStorageMap<u32, NeuronMetadataOf<T>>>::iter() {
+            let emission_i: u64 = step.emission[ uid_i as usize ];
+            let stake_i: u64 = step.stake[ uid_i as usize ] - emission_i;
+            let nominator_emission_i: u64 = Self::get_nominator_payouts( &neuron_i.hotkey, stake_i, emission_i )
+                .iter()
+                .fold( 0, |total, ( _, _, paid )| total.saturating_add( *paid ) );
+            step.stake[ uid_i as usize ] = stake_i + nominator_emission_i + Self::get_restaked_reward( &neuron_i, emission_i - nominator_emission_i );
+        }
+        step
+    }
+
+    /// Returns the part of emission_this_step owed to the foundation, FoundationDistribution
+    /// percent of it, or zero while no foundation account is set.
+    pub fn get_foundation_emission( emission_this_step: u64 ) -> u64 {
+        if !FoundationAccount::<T>::exists() {
+            return 0;
+        }
+        let foundation_distribution: u64 = Self::get_foundation_distribution().min( 100 );
+        ( emission_this_step as u128 * foundation_distribution as u128 / 100 ) as u64
+    }
+
+    /// Deposits the foundation share of emission_this_step into the foundation account and
+    /// returns the amount deposited. The deposit adds to the currency issuance as its positive
+    /// imbalance is dropped, and is added to TotalIssuance here. A deposit that cannot create
+    /// the account pays nothing and the share stays with the neurons.
+    pub fn distribute_to_foundation( emission_this_step: u64 ) -> u64 {
+        let foundation_emission: u64 = Self::get_foundation_emission( emission_this_step );
+        if foundation_emission == 0 {
+            return 0;
+        }
+        let foundation_account: T::AccountId = Self::get_foundation_account();
+        let foundation_emission_as_balance = match Self::u64_to_balance( foundation_emission ) {
+            Some( balance ) => balance,
+            None => return 0,
+        };
+        let imbalance = T::Currency::deposit_creating( &foundation_account, foundation_emission_as_balance );
+        let paid: u64 = TryInto::<u64>::try_into( imbalance.peek() ).ok().unwrap_or( 0 );
+        drop( imbalance );
+        if paid == 0 {
+            return 0;
+        }
+        TotalIssuance::<T>::mutate( |val| *val += paid );
+        Self::deposit_event( Event::FoundationDistributed( foundation_account, paid ) );
+        paid
+    }
+
+    /// Computes the mechanism step, also returning the number of non-zero weights and bonds it read.
+    fn compute_mechanism_step_with_sizes ( emission_this_step: u64 ) -> ( MechanismStepResult, u32, u32 ) {
+
         // Number of peers.
         let n: usize = Self::get_neuron_count() as usize;
         let block: u64 = Self::get_current_block_as_u64();
-        
-        // Constants.
         let activity_cutoff: u64 = Self::get_activity_cutoff();
-        let bonds_moving_average:I65F63 = I65F63::from_num( Self::get_bonds_moving_average() ) / I65F63::from_num( 1_000_000 );
-        let u64_max: I65F63 = I65F63::from_num( u64::MAX );
-        let u32_max: I65F63 = I65F63::from_num( u32::MAX );
-        let one: I65F63 = I65F63::from_num( 1.0 );
-        let zero: I65F63 = I65F63::from_num( 0.0 );
-        let rho: I65F63 = I65F63::from_num( Self::get_rho() );
-        let kappa: I65F63 = one / I65F63::from_num( Self::get_kappa() );
-        let self_ownership: I65F63 = one / I65F63::from_num( Self::get_self_ownership()  );
-
-        // To be filled.
-        let mut uids: Vec<u32> = vec![];
-        let mut active: Vec<u32> = vec![0; n];
-        let mut priority: Vec<u64> = vec![0;n];
-        let mut bond_totals: Vec<u64> = vec![0; n];
-        let mut bonds: Vec<Vec<u64>> = vec![vec![0;n]; n];
-        let mut weights: Vec<Vec<(u32,u32)>> = vec![ vec![]; n ];
-        let mut total_stake: I65F63 = I65F63::from_num( 0.0 );
-        let mut total_active_stake: I65F63 = I65F63::from_num( 0.0 );
-        let mut total_normalized_active_stake: I65F63 = I65F63::from_num( 0.0 );
-        let mut stake: Vec<I65F63> = vec![ I65F63::from_num( 0.0 ) ; n];
+
+        // Read the graph out of storage.
+        let mut inputs = epoch::EpochInputs {
+            stake: vec![ 0; n ],
+            active: vec![ false; n ],
+            priority: vec![ 0; n ],
+            weights: vec![ vec![]; n ],
+            bonds: vec![ vec![]; n ],
+        };
+        let mut prev_bonds: Vec<Vec<(u32,u64)>> = vec![ vec![]; n ];
         for ( uid_i, neuron_i ) in <Neurons<T> as IterableStorageMap<u32, NeuronMetadataOf<T>>>::iter() {
+            let i: usize = uid_i as usize;
+            inputs.stake[ i ] = neuron_i.stake;
+            inputs.active[ i ] = epoch::is_active( block, neuron_i.last_update, activity_cutoff );
+            inputs.priority[ i ] = neuron_i.priority;
+            inputs.weights[ i ] = Weights::<T>::get( uid_i );
+            // Bonds held in pruned uids are already cleared at registration.
+            prev_bonds[ i ] = Bonds::<T>::get( uid_i );
+            inputs.bonds[ i ] = prev_bonds[ i ].clone();
+        }
+        let params = epoch::EpochParams {
+            emission: emission_this_step,
+            rho: Self::get_rho(),
+            kappa: Self::get_kappa(),
+            bonds_moving_average: Self::get_bonds_moving_average(),
+            self_ownership: Self::get_self_ownership(),
+        };
+        let weights_len: u32 = inputs.weights.iter().map( |weights_i| weights_i.len() as u32 ).sum();
+        let bonds_len: u32 = inputs.bonds.iter().map( |bonds_i| bonds_i.len() as u32 ).sum();
+        let stake: Vec<u64> = inputs.stake.clone();
+        let active: Vec<u32> = inputs.active.iter().map( |active_i| *active_i as u32 ).collect();
+
+        // Run the mechanism.
+        let output: epoch::EpochOutput = epoch::epoch( &params, inputs );
+
+        // Collect the values the step would write.
+        let mut pruned_uids: Vec<u32> = <NeuronsToPruneAtNextEpoch<T> as IterableStorageMap<u32, u32>>::iter().map( |(uid, _)| uid ).collect();
+        pruned_uids.sort_unstable();
+        let bond_deltas: Vec<Vec<(u32,i128)>> = prev_bonds.iter().zip( output.bonds.iter() ).map( |(prev_row, next_row)| Self::sparse_bond_deltas( prev_row, next_row ) ).collect();
+        let step = MechanismStepResult {
+            block,
+            active,
+            priority: output.priority,
+            stake: stake.iter().zip( output.emission.iter() ).map( |(stake_i, emission_i)| stake_i + emission_i ).collect(),
+            ranks: output.ranks,
+            trust: output.trust,
+            consensus: output.consensus,
+            incentive: output.incentive,
+            dividends: output.dividends,
+            emission: output.emission,
+            bonds: output.bonds,
+            bond_deltas,
+            pruned_uids,
+            total_emission: output.total_emission,
+            total_bonds_purchased: output.total_bonds_purchased,
+        };
+        ( step, weights_len, bonds_len )
+    }
 
-            // Append a set of uids.
-            uids.push( uid_i );
-            if block - neuron_i.last_update >= activity_cutoff {
-                active [ uid_i as usize ] = 0;
+    /// Returns ( uid_j, next_bonds_ij - prev_bonds_ij ) for every entry that changes
+    /// between two sparse bond rows, ordered by uid_j.
+    pub fn sparse_bond_deltas( prev_row: &[(u32,u64)], next_row: &[(u32,u64)] ) -> Vec<(u32,i128)> {
+        let mut prev_row: Vec<(u32,u64)> = prev_row.to_vec();
+        let mut next_row: Vec<(u32,u64)> = next_row.to_vec();
+        prev_row.sort_unstable_by_key( |(uid_j, _)| *uid_j );
+        next_row.sort_unstable_by_key( |(uid_j, _)| *uid_j );
+
+        // Merge the two sorted rows.
+        let mut deltas: Vec<(u32,i128)> = vec![];
+        let ( mut p, mut q ) = ( 0, 0 );
+        while p < prev_row.len() || q < next_row.len() {
+            let ( uid_j, prev_bonds_ij, next_bonds_ij ) = if q == next_row.len() || ( p < prev_row.len() && prev_row[ p ].0 < next_row[ q ].0 ) {
+                p += 1;
+                ( prev_row[ p - 1 ].0, prev_row[ p - 1 ].1, 0 )
+            } else if p == prev_row.len() || next_row[ q ].0 < prev_row[ p ].0 {
+                q += 1;
+                ( next_row[ q - 1 ].0, 0, next_row[ q - 1 ].1 )
             } else {
-                active [ uid_i as usize ] = 1;
-                total_active_stake += I65F63::from_num( neuron_i.stake );
+                p += 1;
+                q += 1;
+                ( prev_row[ p - 1 ].0, prev_row[ p - 1 ].1, next_row[ q - 1 ].1 )
+            };
+            if next_bonds_ij != prev_bonds_ij {
+                deltas.push( (uid_j, next_bonds_ij as i128 - prev_bonds_ij as i128) );
             }
-            total_stake += I65F63::from_num( neuron_i.stake );
-            stake [ uid_i as usize ] = I65F63::from_num( neuron_i.stake );
-
-            // Priority increments by the log of the stake and is drained everytime the account sets weights. 
-            let log_stake:I65F63 = log2( I65F63::from_num( neuron_i.stake + 1 ) ).expect( "stake + 1 is positive and greater than 1.");
-            priority [ uid_i as usize ] = neuron_i.priority + log_stake.to_num::<u64>();
-
-            weights [ uid_i as usize ] = neuron_i.weights;             
-            let mut bonds_row: Vec<u64> = vec![0; n];
-            for (uid_j, bonds_ij) in neuron_i.bonds.iter() {
-                
-                // Prunning occurs here. We simply to do fill this bonds matrix 
-                // with entries that contain the uids to prune. 
-                if !NeuronsToPruneAtNextEpoch::<T>::contains_key(uid_j) {
-                    // Otherwise, we add the entry into the stack based bonds array.
-                    bonds_row [ *uid_j as usize ] = *bonds_ij;
-                    bond_totals [ *uid_j as usize ] += *bonds_ij;
-                }
+        }
+        deltas
+    }
+    
+    /// Returns true if a mechanism step split across blocks is running.
+    pub fn has_pending_step() -> bool {
+        PendingStep::<T>::exists()
+    }
 
-            }
-            bonds[ uid_i as usize ] = bonds_row;
+    /// Starts a mechanism step that runs across blocks, get_step_chunk_size uids at a time.
+    /// The step computes the same values as mechanism_step, in four phases:
+    /// totals of stake and bonds, ranks and trust, dividends and finally the emission.
+    pub fn start_chunked_step( emission_this_step: u64 ) {
+        let foundation_emission: u64 = Self::distribute_to_foundation( emission_this_step );
+        let emission_this_step: u64 = emission_this_step - foundation_emission;
+        let n: u32 = Self::get_neuron_count();
+        let state = PendingStepState {
+            n,
+            block: Self::get_current_block_as_u64(),
+            emission_this_step,
+            rho: Self::get_rho(),
+            kappa: Self::get_kappa(),
+            bonds_moving_average: Self::get_bonds_moving_average(),
+            self_ownership: Self::get_self_ownership(),
+            stake: vec![ 0; n as usize ],
+            active: vec![ false; n as usize ],
+            bond_totals: vec![ 0; n as usize ],
+            ranks: vec![ 0; n as usize ],
+            trust: vec![ 0; n as usize ],
+            dividends: vec![ 0; n as usize ],
+            ..Default::default()
+        };
+        PendingStep::<T>::put( state );
+    }
+
+    /// Returns the number of uids of a graph of n the mechanism step runs over per block, 0 for the
+    /// whole step in one block. That is the step chunk size, lowered to max_step_rows so a block
+    /// never consumes more than T::MaxStepWeight. A chunk size of 0 is honoured only if the whole
+    /// step fits.
+    pub fn get_step_rows_per_block( n: u32 ) -> u64 {
+        let chunk_size: u64 = Self::get_step_chunk_size();
+        let max_rows: u32 = Self::max_step_rows( n, T::MaxStepWeight::get() );
+        if chunk_size == 0 {
+            if max_rows >= n { 0 } else { max_rows as u64 }
+        } else {
+            chunk_size.min( max_rows as u64 )
         }
-        // Normalize stake based on activity.
-        if total_active_stake != 0 {
-            for uid_i in uids.iter() {
-                let normalized_active_stake:I65F63 = stake[ *uid_i as usize ] / total_active_stake;
-                stake[ *uid_i as usize ] = normalized_active_stake;
-                if active[ *uid_i as usize ] == 1 {
-                    total_normalized_active_stake += normalized_active_stake;
-                }
-            }
-        } 
-        if Self::debug() && false { 
-            if_std! {
-                println!( "stake: {:?}", stake );
-            }
+    }
+
+    /// Returns the largest number of rows, at least 1, whose worst case weight fits in max_weight
+    /// on a graph of n uids. In the worst case every row holds a weight and a bond on each uid.
+    pub fn max_step_rows( n: u32, max_weight: Weight ) -> u32 {
+        let fits = |rows: u32| -> bool {
+            T::WeightInfo::mechanism_step( rows, rows.saturating_mul( n ), rows.saturating_mul( n ) ) <= max_weight
+        };
+        let ( mut low, mut high ): ( u32, u32 ) = ( 1, n.max( 1 ) );
+        while low < high {
+            let mid: u32 = low + ( high - low + 1 ) / 2;
+            if fits( mid ) { low = mid; } else { high = mid - 1; }
         }
+        low
+    }
 
-        // Computational aspect starts here.
-        
-        // Compute ranks and trust.
-        let mut total_bonds_purchased: u64 = 0;
-        let mut total_ranks: I65F63 = I65F63::from_num( 0.0 );
-        let mut total_trust: I65F63 = I65F63::from_num( 0.0 );
-        let mut ranks: Vec<I65F63> = vec![ I65F63::from_num( 0.0 ) ; n];
-        let mut trust: Vec<I65F63> = vec![ I65F63::from_num( 0.0 ) ; n];
-        for uid_i in uids.iter() {
-
-            // === Get vars for uid_i ===
-            let stake_i: I65F63 = stake[ *uid_i as usize ];
-            let weights_i: &Vec<(u32, u32)> = &weights[ *uid_i as usize ];
-            if stake_i == zero { continue } // Skip zeros stake.
-
-            // === Iterate over weights ===
-            for ( uid_j, weight_ij ) in weights_i.iter() {
-
-                if active[ *uid_i as usize ] != 1 { continue } // Skip non active.
-                if *uid_i == *uid_j { continue } // Skip self-weight.
-
-                // === Compute score increments ===
-                let weight_ij: I65F63 = I65F63::from_num( *weight_ij ) / u32_max; // Range( 0, 1 )
-                let trust_increment_ij: I65F63 = stake_i; // Range( 0, 1 )                
-                let rank_increment_ij: I65F63 = stake_i * weight_ij; // Range( 0, total_active_stake )
-                let bond_increment_ij: I65F63 = rank_increment_ij * block_emission; // Range( 0, block_emission )
-            
-                // === Increment neuron scores ===
-                ranks[ *uid_j as usize ] += rank_increment_ij;  // Range( 0, total_active_stake )
-                trust[ *uid_j as usize ] += trust_increment_ij;  // Range( 0, total_active_stake )
-                total_ranks += rank_increment_ij;  // Range( 0, total_active_stake )
-                total_trust += trust_increment_ij;  // Range( 0, total_active_stake )
-                
-                // === Compute bonding moving averages ===
-                let prev_bonds_ij: I65F63 = I65F63::from_num( bonds[ *uid_i as usize  ][ *uid_j as usize ] );
-                let moving_average_bonds_ij = bonds_moving_average * prev_bonds_ij + ( one - bonds_moving_average ) * bond_increment_ij;
-                bonds [ *uid_i as usize  ][ *uid_j as usize ] = moving_average_bonds_ij.to_num::<u64>(); // Range( 0, block_emission )
-
-                // === Update bond totals ===
-                if prev_bonds_ij >= moving_average_bonds_ij {
-                    bond_totals [ *uid_j as usize ] -= ( prev_bonds_ij - moving_average_bonds_ij ).to_num::<u64>(); // Range( 0, block_emission )
-                    total_bonds_purchased = ( prev_bonds_ij - moving_average_bonds_ij ).to_num::<u64>(); // Range( 0, block_emission )
-                } else {
-                    bond_totals [ *uid_j as usize ] += ( moving_average_bonds_ij - prev_bonds_ij ).to_num::<u64>(); // Range( 0, block_emission )
-                    total_bonds_purchased = ( moving_average_bonds_ij - prev_bonds_ij ).to_num::<u64>(); // Range( 0, block_emission )
+    /// Runs the pending step over the next get_step_rows_per_block uids and returns the weight consumed.
+    /// Finishing a phase does not count against the chunk, so the step finishes within the block
+    /// once its last phase is done. A chunk size of 0 runs whatever is left of the step.
+    pub fn continue_chunked_step() -> Weight {
+        let mut state: PendingStepState = match PendingStep::<T>::get() {
+            Some( state ) => state,
+            None => return T::DbWeight::get().reads( 1 ),
+        };
+        let chunk_size: u64 = Self::get_step_rows_per_block( state.n );
+        let mut budget: u32 = if chunk_size == 0 { u32::MAX } else { chunk_size.min( u32::MAX as u64 ) as u32 };
+        let ( mut rows, mut weights_len, mut bonds_len, mut nominators_len ): ( u32, u32, u32, u32 ) = ( 0, 0, 0, 0 );
+        loop {
+            if state.cursor >= state.n {
+                // --- The phase has covered every uid, finish it and move on.
+                state.cursor = 0;
+                match state.phase {
+                    StepPhase::Totals => {
+                        state.phase = StepPhase::Ranks;
+                    }
+                    StepPhase::Ranks => {
+                        Self::finish_ranks_phase( &mut state );
+                        state.phase = StepPhase::Dividends;
+                    }
+                    StepPhase::Dividends => {
+                        Self::finish_dividends_phase( &mut state );
+                        state.phase = StepPhase::Emission;
+                    }
+                    StepPhase::Emission => {
+                        Self::finish_chunked_step( &state );
+                        PendingStep::<T>::kill();
+                        return T::WeightInfo::mechanism_step( rows, weights_len, bonds_len )
+                            .saturating_add( T::DbWeight::get().reads_writes( nominators_len as Weight, nominators_len as Weight ) );
+                    }
                 }
+                continue;
             }
+            if budget == 0 { break; }
+
+            // --- Run the current phase over the next chunk of uids.
+            let end: u32 = state.n.min( state.cursor.saturating_add( budget ) );
+            let ( chunk_weights_len, chunk_bonds_len ) = match state.phase {
+                StepPhase::Totals => Self::run_totals_chunk( &mut state, end ),
+                StepPhase::Ranks => Self::run_ranks_chunk( &mut state, end ),
+                StepPhase::Dividends => Self::run_dividends_chunk( &mut state, end ),
+                StepPhase::Emission => {
+                    let ( chunk_bonds_len, chunk_nominators_len ) = Self::run_emission_chunk( &mut state, end );
+                    nominators_len += chunk_nominators_len;
+                    ( 0, chunk_bonds_len )
+                }
+            };
+            rows += end - state.cursor;
+            budget -= end - state.cursor;
+            weights_len += chunk_weights_len;
+            bonds_len += chunk_bonds_len;
+            state.cursor = end;
         }
-        // === Normalize ranks + trust ===
-        if total_trust > 0 && total_ranks > 0 {
-            for uid_i in uids.iter() {
-                ranks[ *uid_i as usize ] = ranks[ *uid_i as usize ] / total_ranks; // Vector will sum to u64_max
-                trust[ *uid_i as usize ] = trust[ *uid_i as usize ] / total_normalized_active_stake; // Vector will sum to u64_max
-            }
-        }
-        if Self::debug() && false { 
-            if_std! {
-                println!("ranks: {:?}", ranks );
-                println!("trust: {:?}", trust );
-                println!("bonds: {:?}, {:?}, {:?}", bonds, bond_totals, total_bonds_purchased);
-            }
-        }
+        PendingStep::<T>::put( state );
+        T::WeightInfo::mechanism_step( rows, weights_len, bonds_len )
+            .saturating_add( T::DbWeight::get().reads_writes( nominators_len as Weight, nominators_len as Weight ) )
+    }
 
-        // Compute consensus, incentive.
-        let mut total_incentive: I65F63 = I65F63::from_num( 0.0 );
-        let mut consensus: Vec<I65F63> = vec![ I65F63::from_num( 0.0 ) ; n];
-        let mut incentive: Vec<I65F63> = vec![ I65F63::from_num( 0.0 ) ; n];
-        if total_ranks != 0 && total_trust != 0 {
-            for uid_i in uids.iter() {                    
-                // Get exponentiated trust score.
-                let trust_i: I65F63 = trust[ *uid_i as usize ];
-                let shifted_trust: I65F63 = trust_i - kappa; // Range( -kappa, 1 - kappa )
-                let temperatured_trust: I65F63 = shifted_trust * rho; // Range( -rho * kappa, rho ( 1 - kappa ) )
-                let exponentiated_trust: I65F63 = exp( -temperatured_trust ).expect( "temperatured_trust is on range( -rho * kappa, rho ( 1 - kappa ) )"); // Range( exp(-rho * kappa), exp(rho ( 1 - kappa )) )
-                  
-                // Compute consensus.
-                let ranks_i: I65F63 = ranks[ *uid_i as usize ];
-                let consensus_i: I65F63 = one / (one + exponentiated_trust); // Range( 0, 1 )
-                let incentive_i: I65F63 = ranks_i * consensus_i; // Range( 0, 1 )
-                consensus[ *uid_i as usize ] = consensus_i; // Range( 0, 1 )
-                incentive[ *uid_i as usize ] = incentive_i; // Range( 0, 1 )
-                total_incentive += incentive_i;
-            }
-        }
-        // Normalize Incentive.
-        if total_incentive > 0 {
-            for uid_i in uids.iter() {
-                incentive[ *uid_i as usize ] = incentive[ *uid_i as usize ] / total_incentive; // Vector will sum to u64_max
+    /// Reads stake and activity and sums the bonds held in each uid.
+    fn run_totals_chunk( state: &mut PendingStepState, end: u32 ) -> ( u32, u32 ) {
+        let activity_cutoff: u64 = Self::get_activity_cutoff();
+        let mut total_active_stake: I65F63 = I65F63::from_bits( state.total_active_stake );
+        let mut bonds_len: u32 = 0;
+        for uid_i in state.cursor..end {
+            let i: usize = uid_i as usize;
+            let neuron_i = Neurons::<T>::get( uid_i );
+            state.stake[ i ] = neuron_i.stake;
+            // Weights set since the step started count as active.
+            state.active[ i ] = epoch::is_active( state.block, neuron_i.last_update, activity_cutoff );
+            if state.active[ i ] {
+                total_active_stake += I65F63::from_num( neuron_i.stake );
             }
-        }
-        if Self::debug() && false { 
-            if_std! {
-                println!("incentive: {:?} ", incentive);
-                println!("consensus: {:?} ", consensus);
+            for ( uid_j, bonds_ij ) in Bonds::<T>::get( uid_i ).iter() {
+                if *uid_j < state.n {
+                    state.bond_totals[ *uid_j as usize ] += *bonds_ij;
+                }
+                bonds_len += 1;
             }
         }
+        state.total_active_stake = total_active_stake.to_bits();
+        ( 0, bonds_len )
+    }
 
-        // Compute dividends.
-        let mut total_dividends: I65F63 = I65F63::from_num( 0.0 );
-        let mut dividends: Vec<I65F63> = vec![ I65F63::from_num( 0.0 ) ; n];
-        let mut sparse_bonds: Vec<Vec<(u32,u64)>> = vec![vec![]; n];
-        for uid_i in uids.iter() {
-
-            // To be filled: Sparsified bonds.
-            let mut sparse_bonds_row: Vec<(u32, u64)> = vec![];
-
-            // Distribute dividends from self-ownership.
-            let incentive_i: I65F63 = incentive[ *uid_i as usize ];
-            let total_bonds_i: u64 = bond_totals[ *uid_i as usize ]; // Range( 0, total_emission );
-            let mut dividends_ii: I65F63 = incentive_i * self_ownership;
-            if total_bonds_i == 0 {
-                dividends_ii += incentive_i * ( one - self_ownership ); // Add the other half.
-            }
-            dividends[ *uid_i as usize ] += dividends_ii; // Range( 0, block_emission / 2 );
-            total_dividends += dividends_ii; // Range( 0, block_emission / 2 );
-
-            // Distribute dividends from other-ownership.
-            for uid_j in uids.iter() {
-                
-                // Get i -> j bonds.
-                let bonds_ij: u64 = bonds[ *uid_i as usize ][ *uid_j as usize ]; // Range( 0, total_emission );
-                let total_bonds_j: u64 = bond_totals[ *uid_j as usize ]; // Range( 0, total_emission );
-                if total_bonds_j == 0 { continue; } // No bond ownership in this neuron.
-                if bonds_ij == 0 { continue; } // No need to distribute dividends for zero bonds.
-
-                // Compute bond fraction.
-                let bond_fraction_ij: I65F63 = I65F63::from_num( bonds_ij ) / I65F63::from_num( total_bonds_j ); // Range( 0, 1 );
-
-                // Compute incentive owenership fraction.
-                let mut ownership_ji: I65F63 = one - self_ownership; // Range( 0, 1 );
-                ownership_ji = ownership_ji * bond_fraction_ij; // Range( 0, 1 );
-
-                // Compute dividends
-                let dividends_ji: I65F63 = incentive[ *uid_j as usize ] * ownership_ji; // Range( 0, 1 );
-                dividends[ *uid_i as usize ] += dividends_ji; // Range( 0, block_emission / 2 );
-                total_dividends += dividends_ji; // Range( 0, block_emission / 2 );
-                sparse_bonds_row.push( (*uid_j as u32, bonds_ij) );
-            }
-            sparse_bonds[ *uid_i as usize ] = sparse_bonds_row;
-        }
-        // Normalize dividends. Sanity check.
-        let mut total_emission: u64 = 0;
-        let mut emission: Vec<u64> = vec![ 0; n];
-        if total_dividends != 0 {
-            for uid_i in uids.iter() {
-                let dividends_i: I65F63 = dividends[ *uid_i as usize ] / total_dividends;
-                let emission_i: u64 = (block_emission * dividends_i).to_num::<u64>();
-                dividends[ *uid_i as usize ] = dividends_i;
-                emission[ *uid_i as usize ] = emission_i;
-                total_emission += emission_i;
+    /// Accumulates ranks and trust and stages the moved bond rows into PendingBonds.
+    fn run_ranks_chunk( state: &mut PendingStepState, end: u32 ) -> ( u32, u32 ) {
+        let n: u32 = state.n;
+        let total_active_stake: I65F63 = I65F63::from_bits( state.total_active_stake );
+        let mut total_normalized_active_stake: I65F63 = I65F63::from_bits( state.total_normalized_active_stake );
+        let mut accumulator: epoch::RankAccumulator = Self::take_rank_accumulator( state );
+        let ( mut weights_len, mut bonds_len ): ( u32, u32 ) = ( 0, 0 );
+        for uid_i in state.cursor..end {
+            let i: usize = uid_i as usize;
+
+            // Stake normalized by the total active stake, as in epoch::normalize_stake.
+            let mut stake_i: I65F63 = I65F63::from_num( state.stake[ i ] );
+            if total_active_stake != 0 {
+                stake_i = stake_i / total_active_stake;
+                if state.active[ i ] {
+                    total_normalized_active_stake += stake_i;
+                }
             }
+
+            // Entries in uids registered since the step started are left out.
+            let mut weights_i: Vec<(u32,u32)> = Weights::<T>::get( uid_i );
+            let mut bonds_i: Vec<(u32,u64)> = Bonds::<T>::get( uid_i );
+            weights_len += weights_i.len() as u32;
+            bonds_len += bonds_i.len() as u32;
+            weights_i.retain( |(uid_j, _)| *uid_j < n );
+            bonds_i.retain( |(uid_j, _)| *uid_j < n );
+            bonds_i.sort_unstable_by_key( |(uid_j, _)| *uid_j );
+            accumulator.accumulate_row( uid_i, stake_i, state.active[ i ], &weights_i, &mut bonds_i );
+            PendingBonds::<T>::insert( uid_i, bonds_i );
         }
-        if Self::debug() && false { 
-            if_std! {
-                println!( "dividends: {:?}", dividends );
-                println!( "emission: {:?}", emission );
-            }
+        state.total_normalized_active_stake = total_normalized_active_stake.to_bits();
+        Self::put_rank_accumulator( state, accumulator );
+        ( weights_len, bonds_len )
+    }
+
+    /// Normalizes ranks and trust and computes consensus and incentive.
+    fn finish_ranks_phase( state: &mut PendingStepState ) {
+        let mut accumulator: epoch::RankAccumulator = Self::take_rank_accumulator( state );
+        accumulator.normalize( I65F63::from_bits( state.total_normalized_active_stake ) );
+        let ( consensus, incentive ) = epoch::consensus_and_incentive( &accumulator.ranks, &accumulator.trust, accumulator.total_ranks, accumulator.total_trust, state.rho, state.kappa );
+        state.consensus = to_bits( &consensus );
+        state.incentive = to_bits( &incentive );
+        Self::put_rank_accumulator( state, accumulator );
+    }
+
+    /// Computes dividends and keeps the bond entries that earn them in PendingBonds.
+    fn run_dividends_chunk( state: &mut PendingStepState, end: u32 ) -> ( u32, u32 ) {
+        let self_ownership: I65F63 = I65F63::from_num( 1.0 ) / I65F63::from_num( state.self_ownership );
+        let incentive: Vec<I65F63> = from_bits( &state.incentive );
+        let mut total_dividends: I65F63 = I65F63::from_bits( state.total_dividends );
+        let mut bonds_len: u32 = 0;
+        for uid_i in state.cursor..end {
+            let bonds_i: Vec<(u32,u64)> = PendingBonds::<T>::get( uid_i );
+            bonds_len += bonds_i.len() as u32;
+            let ( dividends_i, sparse_bonds_i ) = epoch::dividends_row( uid_i, &incentive, &bonds_i, &state.bond_totals, self_ownership );
+            state.dividends[ uid_i as usize ] = dividends_i.to_bits();
+            total_dividends += dividends_i;
+            PendingBonds::<T>::insert( uid_i, sparse_bonds_i );
         }
+        state.total_dividends = total_dividends.to_bits();
+        ( 0, bonds_len )
+    }
+
+    /// Normalizes dividends and splits the step emission along them.
+    fn finish_dividends_phase( state: &mut PendingStepState ) {
+        let mut dividends: Vec<I65F63> = from_bits( &state.dividends );
+        let ( emission, _ ) = epoch::distribute_emission( &mut dividends, I65F63::from_bits( state.total_dividends ), I65F63::from_num( state.emission_this_step ) );
+        state.dividends = to_bits( &dividends );
+        state.emission = emission;
+    }
+
+    /// Writes the step back into Neurons and Bonds and pays out the emission.
+    /// Returns the number of bonds written and of nominators paid.
+    fn run_emission_chunk( state: &mut PendingStepState, end: u32 ) -> ( u32, u32 ) {
+        let u64_max: I65F63 = I65F63::from_num( u64::MAX );
+        let to_u64 = |bits: i128| -> u64 { ( I65F63::from_bits( bits ) * u64_max ).to_num::<u64>() };
+        let mut bonds_len: u32 = 0;
+        let mut nominators_len: u32 = 0;
+        for uid_i in state.cursor..end {
+            let i: usize = uid_i as usize;
+            // No uid of the step is pruned while it runs, see fn do_registration.
+            let bonds_i: Vec<(u32,u64)> = PendingBonds::<T>::take( uid_i );
 
-        for ( uid_i, mut neuron_i ) in <Neurons<T> as IterableStorageMap<u32, NeuronMetadataOf<T>>>::iter() {
             // Update table entry.
-            neuron_i.active = active[ uid_i as usize ];
-            neuron_i.priority = priority[ uid_i as usize ];
-            neuron_i.emission = emission[ uid_i as usize ];
-            neuron_i.stake = neuron_i.stake + emission[ uid_i as usize ];
-            neuron_i.rank = (ranks[ uid_i as usize ] * u64_max).to_num::<u64>();
-            neuron_i.trust = (trust[ uid_i as usize ] * u64_max).to_num::<u64>();
-            neuron_i.consensus = (consensus[ uid_i as usize ] * u64_max).to_num::<u64>();
-            neuron_i.incentive = (incentive[ uid_i as usize ] * u64_max).to_num::<u64>();
-            neuron_i.dividends = (dividends[ uid_i as usize ] * u64_max).to_num::<u64>();
-            neuron_i.bonds = sparse_bonds[ uid_i as usize ].clone();
-            Neurons::<T>::insert( neuron_i.uid, neuron_i );
+            let mut neuron_i = Neurons::<T>::get( uid_i );
+            neuron_i.active = state.active[ i ] as u32;
+            neuron_i.priority = epoch::next_priority( neuron_i.priority, state.stake[ i ] );
+            neuron_i.emission = state.emission[ i ];
+
+            // Pay out the emission, nominators of a delegate always restake.
+            let ( nominators_i, nominator_emission_i ) = Self::distribute_to_nominators( &neuron_i.hotkey, neuron_i.stake, neuron_i.emission );
+            let restaked_i: u64 = nominator_emission_i + Self::pay_reward( &neuron_i, neuron_i.emission - nominator_emission_i );
+            neuron_i.stake += restaked_i;
+            nominators_len += nominators_i;
+            state.total_restaked += restaked_i;
+            neuron_i.rank = to_u64( state.ranks[ i ] );
+            neuron_i.trust = to_u64( state.trust[ i ] );
+            neuron_i.consensus = to_u64( state.consensus[ i ] );
+            neuron_i.incentive = to_u64( state.incentive[ i ] );
+            neuron_i.dividends = to_u64( state.dividends[ i ] );
+            Neurons::<T>::insert( uid_i, neuron_i );
+            state.total_emission += state.emission[ i ];
+
+            // Only rows whose bonds changed are written back.
+            bonds_len += bonds_i.len() as u32;
+            if Bonds::<T>::get( uid_i ) != bonds_i {
+                Bonds::<T>::insert( uid_i, bonds_i );
+            }
 
             // This where we remove the neurons to prune (clearing the table.)
-            if NeuronsToPruneAtNextEpoch::<T>::contains_key( uid_i ) {
-                NeuronsToPruneAtNextEpoch::<T>::remove ( uid_i );
-            } 
+            NeuronsToPruneAtNextEpoch::<T>::remove( uid_i );
         }
+        ( bonds_len, nominators_len )
+    }
 
-        // Amount distributed through mechanism in conjunction with amount distributed to foudation.
-        let total_new_issuance:u64 = total_emission; // + foundation_distribution_as_float.to_num::<u64>();
+    /// Updates the totals once every uid has been written.
+    /// Rewards paid into accounts are already counted into the issuance.
+    fn finish_chunked_step( state: &PendingStepState ) {
+        TotalEmission::<T>::set( state.total_emission );
+        TotalBondsPurchased::<T>::set( state.total_bonds_purchased );
+        TotalIssuance::<T>::mutate( |val| *val += state.total_restaked );
+        TotalStake::<T>::mutate( |val| *val += state.total_restaked );
+        LastMechansimStepBlock::<T>::set( state.block );
+    }
 
-        // Update totals.
-        TotalEmission::<T>::set( total_emission );
-        TotalBondsPurchased::<T>::set( total_bonds_purchased );
-        TotalIssuance::<T>::mutate( |val| *val += total_new_issuance );
-        TotalStake::<T>::mutate( |val| *val += total_emission );
-        LastMechansimStepBlock::<T>::set( block );
+    /// Moves the rank accumulator out of the pending state.
+    fn take_rank_accumulator( state: &mut PendingStepState ) -> epoch::RankAccumulator {
+        let bonds_moving_average: I65F63 = I65F63::from_num( state.bonds_moving_average ) / I65F63::from_num( 1_000_000 );
+        let bond_totals: Vec<u64> = sp_std::mem::take( &mut state.bond_totals );
+        let mut accumulator = epoch::RankAccumulator::new( bond_totals, I65F63::from_num( state.emission_this_step ), bonds_moving_average );
+        accumulator.ranks = from_bits( &state.ranks );
+        accumulator.trust = from_bits( &state.trust );
+        accumulator.total_ranks = I65F63::from_bits( state.total_ranks );
+        accumulator.total_trust = I65F63::from_bits( state.total_trust );
+        accumulator.total_bonds_purchased = state.total_bonds_purchased;
+        accumulator
     }
-    
+
+    /// Moves the rank accumulator back into the pending state.
+    fn put_rank_accumulator( state: &mut PendingStepState, accumulator: epoch::RankAccumulator ) {
+        state.ranks = to_bits( &accumulator.ranks );
+        state.trust = to_bits( &accumulator.trust );
+        state.total_ranks = accumulator.total_ranks.to_bits();
+        state.total_trust = accumulator.total_trust.to_bits();
+        state.total_bonds_purchased = accumulator.total_bonds_purchased;
+        state.bond_totals = accumulator.bond_totals;
+    }
+
     pub fn get_current_block_as_u64( ) -> u64 {
         let block_as_u64: u64 = TryInto::try_into( system::Pallet::<T>::block_number() ).ok().expect("blockchain will not exceed 2^64 blocks; QED.");
         block_as_u64
     }
 
-}
\ No newline at end of file
+}
+
+fn to_bits( values: &[I65F63] ) -> Vec<i128> {
+    values.iter().map( |value| value.to_bits() ).collect()
+}
+
+fn from_bits( bits: &[i128] ) -> Vec<I65F63> {
+    bits.iter().map( |bits_i| I65F63::from_bits( *bits_i ) ).collect()
+}
@@ -28,18 +28,22 @@ impl<T: Config> Pallet<T> {
         // --- We check if the weights have an allowed max min multiple.
         ensure!( Self::min_is_allowed_multiple_of_max(&normalized_values), Error::<T>::MaxAllowedMaxMinRatioExceeded );
 
+        // --- We check the hotkey has waited out the rate limit since it last set weights.
+        ensure!( !Self::exceeds_tx_rate_limit(&hotkey_id, &CallType::SetWeights), Error::<T>::TxRateLimitExceeded );
+
         // Zip weights.
         let mut zipped_weights: Vec<(u32,u32)> = vec![];
         for (uid, val) in uids.iter().zip(normalized_values.iter()) {
             zipped_weights.push((*uid, *val))
         }
-        neuron.weights = zipped_weights;
         neuron.active = 1; // Set activity back to 1.
         neuron.priority = 0; // Priority is drained.
         neuron.last_update = Self::get_current_block_as_u64();
 
         // Sink update.
+        Weights::<T>::insert(neuron.uid, zipped_weights);
         Neurons::<T>::insert(neuron.uid, neuron);
+        Self::set_last_tx_block(&hotkey_id, &CallType::SetWeights);
 
         // ---- Emit the staking event.
         Self::deposit_event(Event::WeightsSet(hotkey_id));
@@ -48,6 +52,19 @@ impl<T: Config> Pallet<T> {
         Ok(())
     }
 
+    /// Sets the weights of a genesis neuron, normalized as in do_set_weights. Without weights
+    /// the neuron keeps the self weight it was registered with.
+    pub fn set_genesis_weights(uid: u32, weights: &Vec<(u32, u32)>) {
+        if weights.is_empty() {
+            return;
+        }
+        let (uids, values): (Vec<u32>, Vec<u32>) = weights.iter().cloned().unzip();
+        assert!(!has_duplicate_uids(&uids), "genesis weights contain duplicate uids");
+        assert!(!Self::contains_invalid_uids(&uids), "genesis weights point at an unregistered uid");
+        let zipped_weights: Vec<(u32,u32)> = uids.into_iter().zip(normalize(values).into_iter()).collect();
+        Weights::<T>::insert(uid, zipped_weights);
+    }
+
     /********************************
     --==[[  Helper functions   ]]==--
    *********************************/
@@ -0,0 +1,266 @@
+use frame_support::{assert_ok};
+use frame_system::Config;
+mod mock;
+use mock::*;
+use frame_support::sp_runtime::DispatchError;
+use frame_support::dispatch::{GetDispatchInfo, DispatchInfo};
+use frame_support::weights::{DispatchClass, Pays};
+use pallet_subtensor::{Error, RewardDestination, WeightInfo};
+
+/********************************************
+	coldkey_swap::announce_coldkey_swap() tests
+*********************************************/
+#[test]
+fn test_announce_coldkey_swap_dispatch_info_ok() {
+	new_test_ext().execute_with(|| {
+		let call = Call::Subtensor(SubtensorCall::announce_coldkey_swap(1));
+		assert_eq!(call.get_dispatch_info(), DispatchInfo {
+			weight: <() as WeightInfo>::announce_coldkey_swap(),
+			class: DispatchClass::Normal,
+			pays_fee: Pays::No
+		});
+	});
+}
+
+#[test]
+fn test_announce_coldkey_swap_ok() {
+	new_test_ext().execute_with(|| {
+		register_ok_neuron( 1, 10 );
+		assert_ok!(Subtensor::announce_coldkey_swap(<<Test as Config>::Origin>::signed(10), 20));
+		let execution_block: u64 = Subtensor::get_current_block_as_u64() + Subtensor::get_coldkey_swap_delay();
+		assert_eq!( Subtensor::get_coldkey_swap_announcement( &10 ), Some( (20, execution_block) ) );
+
+		// Nothing changes hands before the swap is finalized.
+		assert_eq!( Subtensor::get_neuron_for_hotkey( &1 ).coldkey, 10 );
+	});
+}
+
+#[test]
+fn test_announce_coldkey_swap_err_signature() {
+	new_test_ext().execute_with(|| {
+		let result = Subtensor::announce_coldkey_swap(<<Test as Config>::Origin>::none(), 20);
+		assert_eq!(result, DispatchError::BadOrigin.into());
+	});
+}
+
+#[test]
+fn test_announce_coldkey_swap_err_not_registered() {
+	new_test_ext().execute_with(|| {
+		let result = Subtensor::announce_coldkey_swap(<<Test as Config>::Origin>::signed(10), 20);
+		assert_eq!(result, Err(Error::<Test>::NotRegistered.into()));
+		assert_eq!( Subtensor::get_coldkey_swap_announcement( &10 ), None );
+	});
+}
+
+#[test]
+fn test_announce_coldkey_swap_err_same_coldkey() {
+	new_test_ext().execute_with(|| {
+		register_ok_neuron( 1, 10 );
+		let result = Subtensor::announce_coldkey_swap(<<Test as Config>::Origin>::signed(10), 10);
+		assert_eq!(result, Err(Error::<Test>::SameColdkey.into()));
+	});
+}
+
+#[test]
+fn test_announce_coldkey_swap_err_already_announced() {
+	new_test_ext().execute_with(|| {
+		register_ok_neuron( 1, 10 );
+		assert_ok!(Subtensor::announce_coldkey_swap(<<Test as Config>::Origin>::signed(10), 20));
+		let result = Subtensor::announce_coldkey_swap(<<Test as Config>::Origin>::signed(10), 30);
+		assert_eq!(result, Err(Error::<Test>::ColdkeySwapAlreadyAnnounced.into()));
+		assert_eq!( Subtensor::get_coldkey_swap_announcement( &10 ).unwrap().0, 20 );
+	});
+}
+
+/********************************************
+	coldkey_swap::finalize_coldkey_swap() tests
+*********************************************/
+#[test]
+fn test_finalize_coldkey_swap_dispatch_info_ok() {
+	new_test_ext().execute_with(|| {
+		let call = Call::Subtensor(SubtensorCall::finalize_coldkey_swap(1));
+		assert_eq!(call.get_dispatch_info(), DispatchInfo {
+			weight: <() as WeightInfo>::finalize_coldkey_swap( Subtensor::get_max_allowed_uids() as u32 ),
+			class: DispatchClass::Normal,
+			pays_fee: Pays::No
+		});
+	});
+}
+
+#[test]
+fn test_finalize_coldkey_swap_ok() {
+	new_test_ext().execute_with(|| {
+		let neuron0 = register_ok_neuron( 0, 10 );
+		let neuron1 = register_ok_neuron( 1, 10 );
+		register_ok_neuron( 2, 11 );
+		Subtensor::add_stake_to_neuron_hotkey_account( neuron0.uid, 1_000 );
+		assert_ok!(Subtensor::announce_coldkey_swap(<<Test as Config>::Origin>::signed(10), 20));
+
+		step_block( Subtensor::get_coldkey_swap_delay() );
+		assert_ok!(Subtensor::finalize_coldkey_swap(<<Test as Config>::Origin>::signed(20), 10));
+
+		// Both neurons, and the stake on them, now belong to the new coldkey.
+		assert_eq!( Subtensor::get_neuron_for_hotkey( &0 ).coldkey, 20 );
+		assert_eq!( Subtensor::get_neuron_for_hotkey( &1 ).coldkey, 20 );
+		assert_eq!( Subtensor::get_neuron_for_hotkey( &2 ).coldkey, 11 );
+		assert_eq!( Subtensor::get_neuron_for_hotkey( &0 ).stake, 1_000 );
+		assert_eq!( Subtensor::get_uids_for_coldkey( &20 ), vec![ neuron0.uid, neuron1.uid ] );
+		assert!( Subtensor::get_uids_for_coldkey( &10 ).is_empty() );
+		assert_eq!( Subtensor::get_coldkey_swap_announcement( &10 ), None );
+
+		// Only the new coldkey can manage the stake.
+		assert_eq!( Subtensor::remove_stake(<<Test as Config>::Origin>::signed(10), 0, 1_000), Err(Error::<Test>::NonAssociatedColdKey.into()) );
+		assert_ok!(Subtensor::remove_stake(<<Test as Config>::Origin>::signed(20), 0, 1_000));
+	});
+}
+
+#[test]
+fn test_finalize_coldkey_swap_ok_by_old_coldkey() {
+	new_test_ext().execute_with(|| {
+		register_ok_neuron( 1, 10 );
+		assert_ok!(Subtensor::announce_coldkey_swap(<<Test as Config>::Origin>::signed(10), 20));
+		step_block( Subtensor::get_coldkey_swap_delay() );
+		assert_ok!(Subtensor::finalize_coldkey_swap(<<Test as Config>::Origin>::signed(10), 10));
+		assert_eq!( Subtensor::get_neuron_for_hotkey( &1 ).coldkey, 20 );
+	});
+}
+
+#[test]
+fn test_finalize_coldkey_swap_moves_unbonding_and_nominations() {
+	new_test_ext().execute_with(|| {
+		let neuron = register_ok_neuron( 1, 10 );
+		register_ok_neuron( 2, 11 );
+
+		// Coldkey 10 nominates the delegate of coldkey 11 and has stake unbonding from its own neuron.
+		assert_ok!(Subtensor::set_delegate_take(<<Test as Config>::Origin>::signed(11), 2, 10));
+		Subtensor::add_balance_to_coldkey_account( &10, 3_000 );
+		assert_ok!(Subtensor::add_stake(<<Test as Config>::Origin>::signed(10), 2, 3_000));
+		Subtensor::set_unbonding_period( 100 );
+		Subtensor::add_stake_to_neuron_hotkey_account( neuron.uid, 1_000 );
+		let unlock_block: u64 = Subtensor::get_current_block_as_u64() + 100;
+		assert_ok!(Subtensor::remove_stake(<<Test as Config>::Origin>::signed(10), 1, 1_000));
+		assert_ok!(Subtensor::set_reward_destination(<<Test as Config>::Origin>::signed(10), 1, RewardDestination::Account(10)));
+
+		assert_ok!(Subtensor::announce_coldkey_swap(<<Test as Config>::Origin>::signed(10), 20));
+		step_block( Subtensor::get_coldkey_swap_delay() );
+		assert_ok!(Subtensor::finalize_coldkey_swap(<<Test as Config>::Origin>::signed(20), 10));
+
+		// Nothing is left with the old coldkey.
+		assert_eq!( Subtensor::unbonding( 10 ), vec![] );
+		assert_eq!( Subtensor::unbonding( 20 ), vec![ (1_000, unlock_block) ] );
+		assert_eq!( Subtensor::get_nominator_stake( &2, &10 ), 0 );
+		assert_eq!( Subtensor::get_nominator_stake( &2, &20 ), 3_000 );
+		assert_eq!( Subtensor::reward_destination( neuron.uid ), RewardDestination::Account(20) );
+
+		// The nomination is managed by the new coldkey.
+		assert_eq!( Subtensor::remove_stake(<<Test as Config>::Origin>::signed(10), 2, 3_000), Err(Error::<Test>::NonAssociatedColdKey.into()) );
+		assert_ok!(Subtensor::remove_stake(<<Test as Config>::Origin>::signed(20), 2, 3_000));
+	});
+}
+
+#[test]
+fn test_finalize_coldkey_swap_folds_nominations_on_own_hotkeys() {
+	new_test_ext().execute_with(|| {
+		let neuron = register_ok_neuron( 1, 10 );
+
+		// The new coldkey nominates the delegate it is handed over.
+		assert_ok!(Subtensor::set_delegate_take(<<Test as Config>::Origin>::signed(10), 1, 10));
+		Subtensor::add_balance_to_coldkey_account( &20, 3_000 );
+		assert_ok!(Subtensor::add_stake(<<Test as Config>::Origin>::signed(20), 1, 3_000));
+
+		assert_ok!(Subtensor::announce_coldkey_swap(<<Test as Config>::Origin>::signed(10), 20));
+		step_block( Subtensor::get_coldkey_swap_delay() );
+		assert_ok!(Subtensor::finalize_coldkey_swap(<<Test as Config>::Origin>::signed(20), 10));
+
+		// The nomination became owner stake, which the new coldkey can withdraw in full.
+		assert_eq!( Subtensor::get_nominator_stake( &1, &20 ), 0 );
+		let neuron = Subtensor::get_neuron_for_uid( neuron.uid );
+		assert_eq!( Subtensor::get_owner_stake( &neuron ), 3_000 );
+		assert_ok!(Subtensor::remove_stake(<<Test as Config>::Origin>::signed(20), 1, 3_000));
+		assert_eq!( Subtensor::get_neuron_for_uid( neuron.uid ).stake, 0 );
+	});
+}
+
+#[test]
+fn test_finalize_coldkey_swap_err_delay_not_elapsed() {
+	new_test_ext().execute_with(|| {
+		register_ok_neuron( 1, 10 );
+		assert_ok!(Subtensor::announce_coldkey_swap(<<Test as Config>::Origin>::signed(10), 20));
+		step_block( Subtensor::get_coldkey_swap_delay() - 1 );
+		let result = Subtensor::finalize_coldkey_swap(<<Test as Config>::Origin>::signed(20), 10);
+		assert_eq!(result, Err(Error::<Test>::ColdkeySwapDelayNotElapsed.into()));
+		assert_eq!( Subtensor::get_neuron_for_hotkey( &1 ).coldkey, 10 );
+	});
+}
+
+#[test]
+fn test_finalize_coldkey_swap_err_not_announced() {
+	new_test_ext().execute_with(|| {
+		register_ok_neuron( 1, 10 );
+		let result = Subtensor::finalize_coldkey_swap(<<Test as Config>::Origin>::signed(10), 10);
+		assert_eq!(result, Err(Error::<Test>::NoColdkeySwapAnnounced.into()));
+	});
+}
+
+#[test]
+fn test_finalize_coldkey_swap_err_not_associated_coldkey() {
+	new_test_ext().execute_with(|| {
+		register_ok_neuron( 1, 10 );
+		assert_ok!(Subtensor::announce_coldkey_swap(<<Test as Config>::Origin>::signed(10), 20));
+		step_block( Subtensor::get_coldkey_swap_delay() );
+		let result = Subtensor::finalize_coldkey_swap(<<Test as Config>::Origin>::signed(30), 10);
+		assert_eq!(result, Err(Error::<Test>::NonAssociatedColdKey.into()));
+		assert_eq!( Subtensor::get_neuron_for_hotkey( &1 ).coldkey, 10 );
+	});
+}
+
+/********************************************
+	coldkey_swap::cancel_coldkey_swap() tests
+*********************************************/
+#[test]
+fn test_cancel_coldkey_swap_dispatch_info_ok() {
+	new_test_ext().execute_with(|| {
+		let call = Call::Subtensor(SubtensorCall::cancel_coldkey_swap());
+		assert_eq!(call.get_dispatch_info(), DispatchInfo {
+			weight: <() as WeightInfo>::cancel_coldkey_swap(),
+			class: DispatchClass::Normal,
+			pays_fee: Pays::No
+		});
+	});
+}
+
+#[test]
+fn test_cancel_coldkey_swap_ok() {
+	new_test_ext().execute_with(|| {
+		register_ok_neuron( 1, 10 );
+		assert_ok!(Subtensor::announce_coldkey_swap(<<Test as Config>::Origin>::signed(10), 20));
+		assert_ok!(Subtensor::cancel_coldkey_swap(<<Test as Config>::Origin>::signed(10)));
+		assert_eq!( Subtensor::get_coldkey_swap_announcement( &10 ), None );
+
+		// The cancelled swap can no longer be finalized, a new one can be announced.
+		step_block( Subtensor::get_coldkey_swap_delay() );
+		let result = Subtensor::finalize_coldkey_swap(<<Test as Config>::Origin>::signed(20), 10);
+		assert_eq!(result, Err(Error::<Test>::NoColdkeySwapAnnounced.into()));
+		assert_eq!( Subtensor::get_neuron_for_hotkey( &1 ).coldkey, 10 );
+		assert_ok!(Subtensor::announce_coldkey_swap(<<Test as Config>::Origin>::signed(10), 30));
+	});
+}
+
+#[test]
+fn test_cancel_coldkey_swap_err_not_announced() {
+	new_test_ext().execute_with(|| {
+		let result = Subtensor::cancel_coldkey_swap(<<Test as Config>::Origin>::signed(10));
+		assert_eq!(result, Err(Error::<Test>::NoColdkeySwapAnnounced.into()));
+	});
+}
+
+#[test]
+fn test_cancel_coldkey_swap_err_new_coldkey() {
+	new_test_ext().execute_with(|| {
+		register_ok_neuron( 1, 10 );
+		assert_ok!(Subtensor::announce_coldkey_swap(<<Test as Config>::Origin>::signed(10), 20));
+		let result = Subtensor::cancel_coldkey_swap(<<Test as Config>::Origin>::signed(20));
+		assert_eq!(result, Err(Error::<Test>::NoColdkeySwapAnnounced.into()));
+		assert!( Subtensor::get_coldkey_swap_announcement( &10 ).is_some() );
+	});
+}
@@ -0,0 +1,204 @@
+use frame_support::{assert_ok};
+use frame_system::Config;
+mod mock;
+use mock::*;
+use frame_support::sp_runtime::DispatchError;
+use frame_support::dispatch::{GetDispatchInfo, DispatchInfo};
+use frame_support::weights::{DispatchClass, Pays};
+use pallet_subtensor::{Error, WeightInfo};
+
+// Registers a delegate with 1000 owner stake and a nominator with 3000 stake on it.
+fn setup_delegate( hotkey: u64, coldkey: u64, nominator: u64, take: u64 ) -> u32 {
+	let neuron = register_ok_neuron( hotkey, coldkey );
+	Subtensor::add_stake_to_neuron_hotkey_account( neuron.uid, 1000 );
+	assert_ok!(Subtensor::set_delegate_take(<<Test as Config>::Origin>::signed(coldkey), hotkey, take));
+	Subtensor::add_balance_to_coldkey_account( &nominator, 3000 );
+	assert_ok!(Subtensor::add_stake(<<Test as Config>::Origin>::signed(nominator), hotkey, 3000));
+	neuron.uid
+}
+
+/********************************************
+	delegation::set_delegate_take() tests
+*********************************************/
+#[test]
+fn test_set_delegate_take_dispatch_info_ok() {
+	new_test_ext().execute_with(|| {
+		let call = Call::Subtensor(SubtensorCall::set_delegate_take(1, 18));
+		assert_eq!(call.get_dispatch_info(), DispatchInfo {
+			weight: <() as WeightInfo>::set_delegate_take(),
+			class: DispatchClass::Normal,
+			pays_fee: Pays::No
+		});
+	});
+}
+
+#[test]
+fn test_set_delegate_take_ok() {
+	new_test_ext().execute_with(|| {
+		register_ok_neuron( 1, 10 );
+		assert!( !Subtensor::is_delegate( &1 ) );
+		assert_ok!(Subtensor::set_delegate_take(<<Test as Config>::Origin>::signed(10), 1, 18));
+		assert!( Subtensor::is_delegate( &1 ) );
+		assert_eq!( Subtensor::get_delegate_take( &1 ), 18 );
+		assert_ok!(Subtensor::set_delegate_take(<<Test as Config>::Origin>::signed(10), 1, 0));
+		assert_eq!( Subtensor::get_delegate_take( &1 ), 0 );
+	});
+}
+
+#[test]
+fn test_set_delegate_take_err_signature() {
+	new_test_ext().execute_with(|| {
+		let result = Subtensor::set_delegate_take(<<Test as Config>::Origin>::none(), 1, 18);
+		assert_eq!(result, DispatchError::BadOrigin.into());
+	});
+}
+
+#[test]
+fn test_set_delegate_take_err_not_registered() {
+	new_test_ext().execute_with(|| {
+		let result = Subtensor::set_delegate_take(<<Test as Config>::Origin>::signed(10), 1, 18);
+		assert_eq!(result, Err(Error::<Test>::NotRegistered.into()));
+	});
+}
+
+#[test]
+fn test_set_delegate_take_err_not_associated_coldkey() {
+	new_test_ext().execute_with(|| {
+		register_ok_neuron( 1, 10 );
+		let result = Subtensor::set_delegate_take(<<Test as Config>::Origin>::signed(11), 1, 18);
+		assert_eq!(result, Err(Error::<Test>::NonAssociatedColdKey.into()));
+		assert!( !Subtensor::is_delegate( &1 ) );
+	});
+}
+
+#[test]
+fn test_set_delegate_take_err_invalid_take() {
+	new_test_ext().execute_with(|| {
+		register_ok_neuron( 1, 10 );
+		let result = Subtensor::set_delegate_take(<<Test as Config>::Origin>::signed(10), 1, 101);
+		assert_eq!(result, Err(Error::<Test>::InvalidDelegateTake.into()));
+		assert!( !Subtensor::is_delegate( &1 ) );
+	});
+}
+
+/********************************************
+	nominating through add_stake() and remove_stake()
+*********************************************/
+#[test]
+fn test_nominate_delegate_ok() {
+	new_test_ext().execute_with(|| {
+		let uid = setup_delegate( 1, 10, 20, 18 );
+		assert_eq!( Subtensor::get_stake_of_neuron_hotkey_account_by_uid( uid ), 4000 );
+		assert_eq!( Subtensor::get_nominator_stake( &1, &20 ), 3000 );
+		assert_eq!( Subtensor::get_owner_stake( &Subtensor::get_neuron_for_uid( uid ) ), 1000 );
+		assert_eq!( Subtensor::get_coldkey_balance( &20 ), 0 );
+		assert_eq!( Subtensor::get_total_stake(), 4000 );
+	});
+}
+
+#[test]
+fn test_nominate_err_not_delegate() {
+	new_test_ext().execute_with(|| {
+		register_ok_neuron( 1, 10 );
+		Subtensor::add_balance_to_coldkey_account( &20, 3000 );
+		let result = Subtensor::add_stake(<<Test as Config>::Origin>::signed(20), 1, 3000);
+		assert_eq!(result, Err(Error::<Test>::NonAssociatedColdKey.into()));
+		assert_eq!( Subtensor::get_coldkey_balance( &20 ), 3000 );
+	});
+}
+
+#[test]
+fn test_remove_nominator_stake_ok() {
+	new_test_ext().execute_with(|| {
+		let uid = setup_delegate( 1, 10, 20, 18 );
+		assert_ok!(Subtensor::remove_stake(<<Test as Config>::Origin>::signed(20), 1, 1000));
+		assert_eq!( Subtensor::get_nominator_stake( &1, &20 ), 2000 );
+		assert_eq!( Subtensor::get_coldkey_balance( &20 ), 1000 );
+		assert_eq!( Subtensor::get_stake_of_neuron_hotkey_account_by_uid( uid ), 3000 );
+
+		// The owner stake is untouched.
+		assert_eq!( Subtensor::get_owner_stake( &Subtensor::get_neuron_for_uid( uid ) ), 1000 );
+	});
+}
+
+#[test]
+fn test_remove_stake_only_withdraws_own_share() {
+	new_test_ext().execute_with(|| {
+		setup_delegate( 1, 10, 20, 18 );
+
+		// Neither the owner nor the nominator can withdraw the stake of the other.
+		let result = Subtensor::remove_stake(<<Test as Config>::Origin>::signed(10), 1, 1001);
+		assert_eq!(result, Err(Error::<Test>::NotEnoughStaketoWithdraw.into()));
+		let result = Subtensor::remove_stake(<<Test as Config>::Origin>::signed(20), 1, 3001);
+		assert_eq!(result, Err(Error::<Test>::NotEnoughStaketoWithdraw.into()));
+		let result = Subtensor::remove_stake(<<Test as Config>::Origin>::signed(30), 1, 1);
+		assert_eq!(result, Err(Error::<Test>::NonAssociatedColdKey.into()));
+		assert_ok!(Subtensor::remove_stake(<<Test as Config>::Origin>::signed(10), 1, 1000));
+		assert_eq!( Subtensor::get_nominator_stake( &1, &20 ), 3000 );
+	});
+}
+
+/********************************************
+	dividends split between owner and nominators
+*********************************************/
+#[test]
+fn test_delegate_emission_split_pro_rata() {
+	new_test_ext().execute_with(|| {
+		let uid = setup_delegate( 1, 10, 20, 20 );
+		Subtensor::mechanism_step( Subtensor::get_block_emission() );
+		let neuron = Subtensor::get_neuron_for_uid( uid );
+		assert!( neuron.emission > 0 );
+		assert_eq!( neuron.stake, 4000 + neuron.emission );
+
+		// The owner keeps 20 percent, the rest is shared 1000:3000.
+		let nominator_emission: u64 = ( neuron.emission as u128 * 80 / 100 * 3000 / 4000 ) as u64;
+		assert_eq!( Subtensor::get_nominator_stake( &1, &20 ), 3000 + nominator_emission );
+		assert_eq!( Subtensor::get_owner_stake( &neuron ), 1000 + neuron.emission - nominator_emission );
+	});
+}
+
+#[test]
+fn test_delegate_emission_split_in_chunked_step() {
+	new_test_ext().execute_with(|| {
+		let uid = setup_delegate( 1, 10, 20, 20 );
+		Subtensor::set_step_chunk_size( 1 );
+		Subtensor::start_chunked_step( Subtensor::get_block_emission() );
+		while Subtensor::has_pending_step() {
+			Subtensor::continue_chunked_step();
+		}
+		let neuron = Subtensor::get_neuron_for_uid( uid );
+		assert!( neuron.emission > 0 );
+		let nominator_emission: u64 = ( neuron.emission as u128 * 80 / 100 * 3000 / 4000 ) as u64;
+		assert_eq!( Subtensor::get_nominator_stake( &1, &20 ), 3000 + nominator_emission );
+	});
+}
+
+/********************************************
+	nominators follow the neuron
+*********************************************/
+#[test]
+fn test_pruned_delegate_refunds_nominators() {
+	new_test_ext().execute_with(|| {
+		setup_delegate( 1, 10, 20, 18 );
+		Subtensor::set_max_allowed_uids( 1 );
+		register_ok_neuron( 2, 11 );
+		assert_eq!( Subtensor::get_coldkey_balance( &20 ), 3000 );
+		assert_eq!( Subtensor::get_coldkey_balance( &10 ), 1000 );
+		assert_eq!( Subtensor::get_nominator_stake( &1, &20 ), 0 );
+		assert!( !Subtensor::is_delegate( &1 ) );
+		assert_eq!( Subtensor::get_total_stake(), 0 );
+	});
+}
+
+#[test]
+fn test_swap_hotkey_keeps_nominators() {
+	new_test_ext().execute_with(|| {
+		let uid = setup_delegate( 1, 10, 20, 18 );
+		assert_ok!(Subtensor::swap_hotkey(<<Test as Config>::Origin>::signed(10), 1, 100));
+		assert!( Subtensor::is_delegate( &100 ) );
+		assert_eq!( Subtensor::get_delegate_take( &100 ), 18 );
+		assert_eq!( Subtensor::get_nominator_stake( &100, &20 ), 3000 );
+		assert_eq!( Subtensor::get_owner_stake( &Subtensor::get_neuron_for_uid( uid ) ), 1000 );
+		assert_ok!(Subtensor::remove_stake(<<Test as Config>::Origin>::signed(20), 100, 3000));
+	});
+}
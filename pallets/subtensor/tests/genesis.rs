@@ -0,0 +1,104 @@
+use pallet_subtensor::{GenesisConfig, GenesisNeuron};
+mod mock;
+use mock::*;
+
+fn genesis_neuron( hotkey: u64, coldkey: u64, stake: u64 ) -> GenesisNeuron<u64> {
+	GenesisNeuron {
+		hotkey: hotkey,
+		coldkey: coldkey,
+		stake: stake,
+		..Default::default()
+	}
+}
+
+#[test]
+fn test_genesis_registers_neurons() {
+	let mut served = genesis_neuron( 1, 10, 1_000 );
+	served.ip = ipv4(8,8,8,8);
+	served.ip_type = 4;
+	served.port = 8091;
+	let genesis = GenesisConfig::<Test> {
+		neurons: vec![ served, genesis_neuron( 2, 10, 3_000 ) ],
+		..Default::default()
+	};
+	test_ext_with_genesis( genesis ).execute_with(|| {
+		assert_eq!( Subtensor::get_neuron_count(), 2 );
+		let neuron = Subtensor::get_neuron_for_hotkey( &1 );
+		assert_eq!( neuron.uid, 0 );
+		assert_eq!( neuron.coldkey, 10 );
+		assert_eq!( neuron.stake, 1_000 );
+		assert_eq!( neuron.ip, ipv4(8,8,8,8) );
+		assert_eq!( neuron.port, 8091 );
+		assert_eq!( Subtensor::get_neuron_for_hotkey( &2 ).uid, 1 );
+		assert_eq!( Subtensor::get_uids_for_coldkey( &10 ), vec![0, 1] );
+
+		// Without weights every neuron keeps its self weight.
+		assert_eq!( Subtensor::weights( 1 ), vec![(1, u32::MAX)] );
+	});
+}
+
+#[test]
+fn test_genesis_stake_is_minted() {
+	let genesis = GenesisConfig::<Test> {
+		neurons: vec![ genesis_neuron( 1, 10, 1_000 ), genesis_neuron( 2, 20, 3_000 ) ],
+		..Default::default()
+	};
+	test_ext_with_genesis( genesis ).execute_with(|| {
+		assert_eq!( Subtensor::get_total_stake(), 4_000 );
+		assert_eq!( Subtensor::get_total_issuance(), InitialIssuance::get() + 4_000 );
+	});
+}
+
+#[test]
+fn test_genesis_weights_are_normalized() {
+	let mut validator = genesis_neuron( 1, 10, 1_000 );
+	validator.weights = vec![ (0, 1), (1, 3) ];
+	let genesis = GenesisConfig::<Test> {
+		neurons: vec![ validator, genesis_neuron( 2, 20, 0 ) ],
+		..Default::default()
+	};
+	test_ext_with_genesis( genesis ).execute_with(|| {
+		assert_eq!( Subtensor::weights( 0 ), vec![(0, u32::MAX / 4), (1, (3 * u32::MAX as u64 / 4) as u32)] );
+	});
+}
+
+#[test]
+fn test_genesis_overrides_hyperparameters() {
+	let genesis = GenesisConfig::<Test> {
+		rho: Some( 7 ),
+		difficulty: Some( 1 ),
+		max_allowed_uids: Some( 2 ),
+		blocks_per_step: Some( 3 ),
+		..Default::default()
+	};
+	test_ext_with_genesis( genesis ).execute_with(|| {
+		assert_eq!( Subtensor::get_rho(), 7 );
+		assert_eq!( Subtensor::get_difficulty_as_u64(), 1 );
+		assert_eq!( Subtensor::get_max_allowed_uids(), 2 );
+		assert_eq!( Subtensor::get_blocks_per_step(), 3 );
+
+		// Hyperparameters left out keep their initial value.
+		assert_eq!( Subtensor::get_kappa(), InitialKappa::get() );
+	});
+}
+
+#[test]
+#[should_panic(expected = "more genesis neurons than MaxAllowedUids")]
+fn test_genesis_rejects_too_many_neurons() {
+	let genesis = GenesisConfig::<Test> {
+		neurons: vec![ genesis_neuron( 1, 10, 0 ), genesis_neuron( 2, 10, 0 ) ],
+		max_allowed_uids: Some( 1 ),
+		..Default::default()
+	};
+	test_ext_with_genesis( genesis );
+}
+
+#[test]
+#[should_panic(expected = "genesis hotkey registered twice")]
+fn test_genesis_rejects_duplicate_hotkeys() {
+	let genesis = GenesisConfig::<Test> {
+		neurons: vec![ genesis_neuron( 1, 10, 0 ), genesis_neuron( 1, 20, 0 ) ],
+		..Default::default()
+	};
+	test_ext_with_genesis( genesis );
+}
@@ -1,4 +1,4 @@
-use pallet_subtensor::{SubtensorSignedExtension, CallType};
+use pallet_subtensor::{SubtensorSignedExtension, CallType, Error, TX_RATE_LIMIT_EXCEEDED};
 use frame_support::{assert_ok};
 
 mod mock;
@@ -9,7 +9,8 @@ use frame_support::weights::{DispatchInfo};
 use frame_support::weights::PostDispatchInfo;
 use sp_std::marker::PhantomData;
 use sp_runtime::traits::SignedExtension;
-use sp_runtime::transaction_validity::{ValidTransaction};
+use sp_runtime::transaction_validity::{ValidTransaction, InvalidTransaction};
+use sp_runtime::DispatchError;
 use frame_support::dispatch::GetDispatchInfo;
 
 #[test]
@@ -297,4 +298,164 @@ fn test_sudo_call_does_not_pay_transaction_fee() {
         let result = mock::Executive::apply_extrinsic(xt);
         assert_ok!(result);
     });
-}
\ No newline at end of file
+}
+
+
+#[test]
+fn test_transaction_fee_is_charged_and_burnt() {
+    let hotkey_account_id = 1;
+    let len = 100;
+
+    test_ext_with_balances(vec![(hotkey_account_id, 10_000)]).execute_with(|| {
+        Subtensor::set_transaction_byte_fee(CallType::Serve, 2);
+        let call = SubtensorCall::serve_axon(0, ipv4(8,8,8,8), 8883, 4, 0).into();
+        let info = DispatchInfo::default();
+        let total_issuance = Subtensor::get_total_issuance();
+
+        assert!(SubtensorSignedExtension::<Test>(PhantomData).validate(&hotkey_account_id, &call, &info, len).is_ok());
+        let pre = SubtensorSignedExtension::<Test>(PhantomData).pre_dispatch(&hotkey_account_id, &call, &info, len).unwrap();
+        assert_eq!(pre.1, 200);
+        assert_eq!(Subtensor::get_coldkey_balance(&hotkey_account_id), 9_800);
+
+        assert_ok!(SubtensorSignedExtension::<Test>::post_dispatch(pre, &info, &PostDispatchInfo {actual_weight: Some(0), pays_fee: Default::default()}, len, &Ok(())));
+        assert_eq!(Subtensor::get_coldkey_balance(&hotkey_account_id), 9_800);
+        assert!(Subtensor::get_total_issuance() <= total_issuance);
+    });
+}
+
+#[test]
+fn test_transaction_fee_is_refunded_on_failure() {
+    let hotkey_account_id = 1;
+    let len = 100;
+
+    test_ext_with_balances(vec![(hotkey_account_id, 10_000)]).execute_with(|| {
+        Subtensor::set_transaction_byte_fee(CallType::Serve, 2);
+        let call = SubtensorCall::serve_axon(0, ipv4(8,8,8,8), 8883, 4, 0).into();
+        let info = DispatchInfo::default();
+
+        let pre = SubtensorSignedExtension::<Test>(PhantomData).pre_dispatch(&hotkey_account_id, &call, &info, len).unwrap();
+        assert_eq!(Subtensor::get_coldkey_balance(&hotkey_account_id), 9_800);
+
+        assert_ok!(SubtensorSignedExtension::<Test>::post_dispatch(pre, &info, &PostDispatchInfo {actual_weight: Some(0), pays_fee: Default::default()}, len, &Err(DispatchError::BadOrigin)));
+        assert_eq!(Subtensor::get_coldkey_balance(&hotkey_account_id), 10_000);
+    });
+}
+
+#[test]
+fn test_feeless_call_is_restored_on_failure() {
+    let hotkey_account_id = 1;
+    let coldkey_account_id = 667;
+    let len = 100;
+
+    test_ext_with_balances(vec![(hotkey_account_id, 10_000)]).execute_with(|| {
+        let block_number: u64 = 0;
+        let (nonce, work): (u64, Vec<u8>) = Subtensor::create_work_for_block_number( block_number );
+        assert_ok!(Subtensor::register(<<Test as Config>::Origin>::signed(hotkey_account_id), block_number, nonce, work, hotkey_account_id, coldkey_account_id));
+
+        Subtensor::set_transaction_byte_fee(CallType::Serve, 2);
+        Subtensor::set_feeless_quota(1);
+        Subtensor::set_feeless_quota_interval(100);
+        let call = SubtensorCall::serve_axon(0, ipv4(8,8,8,8), 8883, 4, 0).into();
+        let info = DispatchInfo::default();
+
+        // A failed fee-less call gives its slot back, like a failed paid call gets its fee back.
+        let pre = SubtensorSignedExtension::<Test>(PhantomData).pre_dispatch(&hotkey_account_id, &call, &info, len).unwrap();
+        assert_eq!(pre.1, 0);
+        assert_eq!(Subtensor::get_feeless_uid(&hotkey_account_id), None);
+        assert_ok!(SubtensorSignedExtension::<Test>::post_dispatch(pre, &info, &PostDispatchInfo {actual_weight: Some(0), pays_fee: Default::default()}, len, &Err(DispatchError::BadOrigin)));
+        assert_eq!(Subtensor::get_feeless_uid(&hotkey_account_id), Some(0));
+
+        // A successful one keeps it used up.
+        let pre = SubtensorSignedExtension::<Test>(PhantomData).pre_dispatch(&hotkey_account_id, &call, &info, len).unwrap();
+        assert_eq!(pre.1, 0);
+        assert_ok!(SubtensorSignedExtension::<Test>::post_dispatch(pre, &info, &PostDispatchInfo {actual_weight: Some(0), pays_fee: Default::default()}, len, &Ok(())));
+        assert_eq!(Subtensor::get_feeless_uid(&hotkey_account_id), None);
+        assert_eq!(Subtensor::get_coldkey_balance(&hotkey_account_id), 10_000);
+    });
+}
+
+#[test]
+fn test_transaction_fee_rejects_unfunded_signer() {
+    let hotkey_account_id = 1;
+    let len = 100;
+
+    test_ext_with_balances(vec![(hotkey_account_id, 100)]).execute_with(|| {
+        Subtensor::set_transaction_byte_fee(CallType::Serve, 2);
+        let call = SubtensorCall::serve_axon(0, ipv4(8,8,8,8), 8883, 4, 0).into();
+        let info = DispatchInfo::default();
+
+        assert_eq!(SubtensorSignedExtension::<Test>(PhantomData).validate(&hotkey_account_id, &call, &info, len), Err(InvalidTransaction::Payment.into()));
+        assert_eq!(SubtensorSignedExtension::<Test>(PhantomData).pre_dispatch(&hotkey_account_id, &call, &info, len), Err(InvalidTransaction::Payment.into()));
+        assert_eq!(Subtensor::get_coldkey_balance(&hotkey_account_id), 100);
+    });
+}
+
+#[test]
+fn test_feeless_quota_resets_every_interval() {
+    let hotkey_account_id = 1;
+    let coldkey_account_id = 667;
+    let len = 100;
+
+    test_ext_with_balances(vec![(hotkey_account_id, 10_000)]).execute_with(|| {
+        let block_number: u64 = 0;
+        let (nonce, work): (u64, Vec<u8>) = Subtensor::create_work_for_block_number( block_number );
+        assert_ok!(Subtensor::register(<<Test as Config>::Origin>::signed(hotkey_account_id), block_number, nonce, work, hotkey_account_id, coldkey_account_id));
+
+        Subtensor::set_transaction_byte_fee(CallType::Serve, 2);
+        Subtensor::set_feeless_quota(1);
+        Subtensor::set_feeless_quota_interval(100);
+        let call = SubtensorCall::serve_axon(0, ipv4(8,8,8,8), 8883, 4, 0).into();
+        let info = DispatchInfo::default();
+
+        // The first call of the interval is fee-less, the second one is not.
+        let pre = SubtensorSignedExtension::<Test>(PhantomData).pre_dispatch(&hotkey_account_id, &call, &info, len).unwrap();
+        assert_eq!(pre.1, 0);
+        let pre = SubtensorSignedExtension::<Test>(PhantomData).pre_dispatch(&hotkey_account_id, &call, &info, len).unwrap();
+        assert_eq!(pre.1, 200);
+        assert_eq!(Subtensor::get_coldkey_balance(&hotkey_account_id), 9_800);
+
+        // The quota is restored in the next interval.
+        run_to_block(100);
+        let pre = SubtensorSignedExtension::<Test>(PhantomData).pre_dispatch(&hotkey_account_id, &call, &info, len).unwrap();
+        assert_eq!(pre.1, 0);
+        assert_eq!(Subtensor::get_coldkey_balance(&hotkey_account_id), 9_800);
+    });
+}
+
+#[test]
+fn test_rate_limited_call_is_rejected_from_pool() {
+    new_test_ext().execute_with(|| {
+        let hotkey_account_id = 1;
+        let neuron = register_ok_neuron(hotkey_account_id, 667);
+        Subtensor::set_tx_rate_limit(CallType::SetWeights, 10);
+        let call = SubtensorCall::set_weights(vec![neuron.uid], vec![u32::MAX]).into();
+        let info = DispatchInfo::default();
+        let len = 10;
+
+        assert!(SubtensorSignedExtension::<Test>(PhantomData).validate(&hotkey_account_id, &call, &info, len).is_ok());
+        assert_ok!(Subtensor::set_weights(Origin::signed(hotkey_account_id), vec![neuron.uid], vec![u32::MAX]));
+        assert_eq!(SubtensorSignedExtension::<Test>(PhantomData).validate(&hotkey_account_id, &call, &info, len), Err(InvalidTransaction::Custom(TX_RATE_LIMIT_EXCEEDED).into()));
+
+        step_block(10);
+        assert!(SubtensorSignedExtension::<Test>(PhantomData).validate(&hotkey_account_id, &call, &info, len).is_ok());
+    });
+}
+
+#[test]
+fn test_rate_limited_call_included_in_block_burns_its_fee() {
+    let hotkey_account_id = 1;
+    let balance = 10_000;
+
+    test_ext_with_balances(vec![(hotkey_account_id, balance)]).execute_with(|| {
+        let neuron = register_ok_neuron(hotkey_account_id, 667);
+        Subtensor::set_tx_rate_limit(CallType::SetWeights, 10);
+        Subtensor::set_transaction_byte_fee(CallType::SetWeights, 2);
+        assert_ok!(Subtensor::set_weights(Origin::signed(hotkey_account_id), vec![neuron.uid], vec![u32::MAX]));
+
+        // A block author includes a second call within the rate limit, it fails and pays for it.
+        let call = Call::Subtensor(SubtensorCall::set_weights(vec![neuron.uid], vec![u32::MAX]));
+        let xt = TestXt::new(call, mock::sign_extra(hotkey_account_id, 0));
+        assert_eq!(mock::Executive::apply_extrinsic(xt), Ok(Err(Error::<Test>::TxRateLimitExceeded.into())));
+        assert!(Subtensor::get_coldkey_balance(&hotkey_account_id) < balance);
+    });
+}
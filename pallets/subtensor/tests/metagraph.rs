@@ -0,0 +1,110 @@
+mod mock;
+use mock::*;
+
+/***********************************************************
+	metagraph::get_metagraph() tests
+************************************************************/
+
+// Tests the metagraph without a neuron in the graph.
+#[test]
+fn test_get_metagraph_empty() {
+	new_test_ext().execute_with(|| {
+		let metagraph = Subtensor::get_metagraph( None, None );
+		assert_eq!( metagraph.n, 0 );
+		assert_eq!( metagraph.uids.len(), 0 );
+		assert_eq!( metagraph.hotkeys.len(), 0 );
+		assert_eq!( metagraph.weights.len(), 0 );
+	});
+}
+
+// Tests that every column matches the stored neuron.
+#[test]
+fn test_get_metagraph_columns_match_neurons() {
+	new_test_ext().execute_with(|| {
+		Subtensor::set_max_registratations_per_block( 100 );
+		for i in 0..3 {
+			register_ok_neuron( i as u64, 10 + i as u64 );
+		}
+		serve_axon( 1, 2, ipv4(8,8,8,8), 8883, 4, 0 );
+		Subtensor::set_stake_from_vector( vec![ 1, 2, 3 ] );
+
+		let metagraph = Subtensor::get_metagraph( None, None );
+		assert_eq!( metagraph.n, 3 );
+		assert_eq!( metagraph.block, Subtensor::get_current_block_as_u64() );
+		assert_eq!( metagraph.uids, vec![ 0, 1, 2 ] );
+		assert_eq!( metagraph.hotkeys, vec![ 0, 1, 2 ] );
+		assert_eq!( metagraph.coldkeys, vec![ 10, 11, 12 ] );
+		assert_eq!( metagraph.stake, Subtensor::get_stake() );
+		assert_eq!( metagraph.ranks, Subtensor::get_ranks() );
+		assert_eq!( metagraph.active, Subtensor::get_active() );
+		assert_eq!( metagraph.versions, vec![ 0, 2, 0 ] );
+		assert_eq!( metagraph.ips, vec![ 0, ipv4(8,8,8,8), 0 ] );
+		assert_eq!( metagraph.ports, vec![ 0, 8883, 0 ] );
+		assert_eq!( metagraph.ip_types, vec![ 0, 4, 0 ] );
+		for uid in 0..3 {
+			assert_eq!( metagraph.weights[ uid as usize ], Subtensor::weights( uid ) );
+			assert_eq!( metagraph.bonds[ uid as usize ], Subtensor::bonds( uid ) );
+		}
+	});
+}
+
+// Tests paging through the graph with uid ranges.
+#[test]
+fn test_get_metagraph_ranges() {
+	new_test_ext().execute_with(|| {
+		Subtensor::set_max_registratations_per_block( 100 );
+		for i in 0..5 {
+			register_ok_neuron( i as u64, i as u64 );
+		}
+		let first_page = Subtensor::get_metagraph( Some(0), Some(2) );
+		let second_page = Subtensor::get_metagraph( Some(2), Some(4) );
+		let last_page = Subtensor::get_metagraph( Some(4), None );
+		assert_eq!( first_page.n, 5 );
+		assert_eq!( first_page.uids, vec![ 0, 1 ] );
+		assert_eq!( second_page.uids, vec![ 2, 3 ] );
+		assert_eq!( last_page.uids, vec![ 4 ] );
+		assert_eq!( last_page.hotkeys, vec![ 4 ] );
+
+		// Ranges past the end of the graph are clamped.
+		assert_eq!( Subtensor::get_metagraph( Some(3), Some(100) ).uids, vec![ 3, 4 ] );
+		assert_eq!( Subtensor::get_metagraph( Some(10), Some(20) ).uids.len(), 0 );
+		assert_eq!( Subtensor::get_metagraph( Some(3), Some(1) ).uids.len(), 0 );
+	});
+}
+
+/***********************************************************
+	metagraph::get_neuron_by_*() tests
+************************************************************/
+
+#[test]
+fn test_get_neuron_by_uid() {
+	new_test_ext().execute_with(|| {
+		assert_eq!( Subtensor::get_neuron_by_uid( 0 ), None );
+		let neuron = register_ok_neuron( 1, 2 );
+		assert_eq!( Subtensor::get_neuron_by_uid( neuron.uid ), Some( neuron ) );
+		assert_eq!( Subtensor::get_neuron_by_uid( 1 ), None );
+	});
+}
+
+#[test]
+fn test_get_neuron_by_hotkey() {
+	new_test_ext().execute_with(|| {
+		assert_eq!( Subtensor::get_neuron_by_hotkey( &1 ), None );
+		let neuron = register_ok_neuron( 1, 2 );
+		assert_eq!( Subtensor::get_neuron_by_hotkey( &1 ), Some( neuron ) );
+		assert_eq!( Subtensor::get_neuron_by_hotkey( &2 ), None );
+	});
+}
+
+#[test]
+fn test_get_neurons_by_coldkey() {
+	new_test_ext().execute_with(|| {
+		Subtensor::set_max_registratations_per_block( 100 );
+		let neuron_a = register_ok_neuron( 1, 10 );
+		let neuron_b = register_ok_neuron( 2, 20 );
+		let neuron_c = register_ok_neuron( 3, 10 );
+		assert_eq!( Subtensor::get_neurons_by_coldkey( &10 ), vec![ neuron_a, neuron_c ] );
+		assert_eq!( Subtensor::get_neurons_by_coldkey( &20 ), vec![ neuron_b ] );
+		assert_eq!( Subtensor::get_neurons_by_coldkey( &30 ), vec![] );
+	});
+}
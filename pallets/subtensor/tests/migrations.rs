@@ -0,0 +1,230 @@
+mod mock;
+use mock::*;
+use frame_support::storage::migration::{get_storage_value, put_storage_value, remove_storage_prefix};
+use frame_support::traits::OnRuntimeUpgrade;
+use pallet_subtensor::{migrations, NeuronMetadata, Releases};
+use codec::{Encode, Decode};
+
+/***********************************************************
+	migrations::migrate_to_v2() tests
+************************************************************/
+
+// Rewinds the pallet to the V1 layout: the coldkey index does not exist yet.
+fn rewind_to_v1() {
+	remove_storage_prefix( b"Subtensor", b"ColdkeyUids", &[] );
+	put_storage_value( b"Subtensor", b"StorageVersion", &[], Releases::V1_0_0 );
+}
+
+#[test]
+fn test_migrate_to_v2_backfills_coldkey_index() {
+	new_test_ext().execute_with(|| {
+		Subtensor::set_max_registratations_per_block( 100 );
+		register_ok_neuron( 0, 10 );
+		register_ok_neuron( 1, 11 );
+		register_ok_neuron( 2, 10 );
+		rewind_to_v1();
+		assert_eq!( Subtensor::get_uids_for_coldkey( &10 ), Vec::<u32>::new() );
+
+		Subtensor::on_runtime_upgrade();
+		assert_eq!( Subtensor::get_uids_for_coldkey( &10 ), vec![ 0, 2 ] );
+		assert_eq!( Subtensor::get_uids_for_coldkey( &11 ), vec![ 1 ] );
+	});
+}
+
+#[test]
+fn test_migrate_to_v2_runs_once() {
+	new_test_ext().execute_with(|| {
+		register_ok_neuron( 0, 10 );
+		rewind_to_v1();
+		Subtensor::on_runtime_upgrade();
+		assert_eq!( Subtensor::get_uids_for_coldkey( &10 ), vec![ 0 ] );
+
+		// Once at V2 the index is left untouched.
+		remove_storage_prefix( b"Subtensor", b"ColdkeyUids", &[] );
+		Subtensor::on_runtime_upgrade();
+		assert_eq!( Subtensor::get_uids_for_coldkey( &10 ), Vec::<u32>::new() );
+	});
+}
+
+/***********************************************************
+	migrations::migrate_to_v3() tests
+************************************************************/
+
+// The NeuronMetadata layout before V3, weights and bonds are stored inline.
+#[derive(Encode, Decode)]
+struct OldNeuronMetadata {
+	version: u32,
+	ip: u128,
+	port: u16,
+	ip_type: u8,
+	uid: u32,
+	modality: u8,
+	hotkey: u64,
+	coldkey: u64,
+	active: u32,
+	last_update: u64,
+	priority: u64,
+	stake: u64,
+	rank: u64,
+	trust: u64,
+	consensus: u64,
+	incentive: u64,
+	dividends: u64,
+	emission: u64,
+	bonds: Vec<(u32,u64)>,
+	weights: Vec<(u32,u32)>,
+}
+
+// Rewrites a neuron in the V2 layout with the passed weights and bonds inline.
+fn put_v2_neuron( neuron: &NeuronMetadata<u64>, weights: Vec<(u32,u32)>, bonds: Vec<(u32,u64)> ) {
+	let old = OldNeuronMetadata {
+		version: neuron.version,
+		ip: neuron.ip,
+		port: neuron.port,
+		ip_type: neuron.ip_type,
+		uid: neuron.uid,
+		modality: neuron.modality,
+		hotkey: neuron.hotkey,
+		coldkey: neuron.coldkey,
+		active: neuron.active,
+		last_update: neuron.last_update,
+		priority: neuron.priority,
+		stake: neuron.stake,
+		rank: neuron.rank,
+		trust: neuron.trust,
+		consensus: neuron.consensus,
+		incentive: neuron.incentive,
+		dividends: neuron.dividends,
+		emission: neuron.emission,
+		bonds,
+		weights,
+	};
+	put_storage_value( b"Subtensor", b"Neurons", &neuron.uid.encode(), old );
+}
+
+// Rewinds the pallet to the V2 layout: the weights and bonds maps do not exist yet.
+fn rewind_to_v2() {
+	remove_storage_prefix( b"Subtensor", b"Weights", &[] );
+	remove_storage_prefix( b"Subtensor", b"Bonds", &[] );
+	put_storage_value( b"Subtensor", b"StorageVersion", &[], Releases::V2_0_0 );
+}
+
+#[test]
+fn test_migrate_to_v3_moves_weights_and_bonds() {
+	new_test_ext().execute_with(|| {
+		Subtensor::set_max_registratations_per_block( 100 );
+		let neuron0 = register_ok_neuron( 0, 10 );
+		let neuron1 = register_ok_neuron( 1, 11 );
+		let neuron2 = register_ok_neuron( 2, 12 );
+		rewind_to_v2();
+		put_v2_neuron( &neuron0, vec![ (1, u32::MAX) ], vec![ (1, 5), (2, 6) ] );
+		put_v2_neuron( &neuron1, vec![ (0, 7), (2, 8) ], vec![ (0, 9) ] );
+		put_v2_neuron( &neuron2, vec![], vec![] );
+
+		// Uid 2 was pruned, bonds held in it are dropped by the migration.
+		put_storage_value( b"Subtensor", b"NeuronsToPruneAtNextEpoch", &2u32.encode(), 2u32 );
+
+		Subtensor::on_runtime_upgrade();
+		assert_eq!( get_storage_value::<Releases>( b"Subtensor", b"StorageVersion", &[] ), Some( Releases::V3_0_0 ) );
+		assert_eq!( Subtensor::weights( 0 ), vec![ (1, u32::MAX) ] );
+		assert_eq!( Subtensor::weights( 1 ), vec![ (0, 7), (2, 8) ] );
+		assert_eq!( Subtensor::weights( 2 ), Vec::<(u32,u32)>::new() );
+		assert_eq!( Subtensor::bonds( 0 ), vec![ (1, 5) ] );
+		assert_eq!( Subtensor::bonds( 1 ), vec![ (0, 9) ] );
+		assert_eq!( Subtensor::bonds( 2 ), Vec::<(u32,u64)>::new() );
+
+		// Neuron metadata is decoded in the new layout.
+		assert_eq!( Subtensor::get_neuron_for_uid( 0 ), neuron0 );
+		assert_eq!( Subtensor::get_neuron_for_uid( 1 ), neuron1 );
+		assert_eq!( Subtensor::get_neuron_for_hotkey( &2 ).coldkey, 12 );
+	});
+}
+
+/***********************************************************
+	migrations::migrate() tests
+************************************************************/
+
+// Registers three neurons and rewrites them in the V1 layout, uid 0 and 1 holding weights and bonds.
+fn setup_v1_neurons() -> Vec<NeuronMetadata<u64>> {
+	Subtensor::set_max_registratations_per_block( 100 );
+	let neurons = vec![ register_ok_neuron( 0, 10 ), register_ok_neuron( 1, 11 ), register_ok_neuron( 2, 10 ) ];
+	rewind_to_v2();
+	rewind_to_v1();
+	put_v2_neuron( &neurons[0], vec![ (1, u32::MAX) ], vec![ (1, 5) ] );
+	put_v2_neuron( &neurons[1], vec![ (0, 7), (2, 8) ], vec![ (0, 9) ] );
+	put_v2_neuron( &neurons[2], vec![], vec![] );
+	neurons
+}
+
+#[test]
+fn test_migrate_from_v1_runs_every_migration_in_order() {
+	new_test_ext().execute_with(|| {
+		let neurons = setup_v1_neurons();
+
+		Subtensor::on_runtime_upgrade();
+		assert_eq!( get_storage_value::<Releases>( b"Subtensor", b"StorageVersion", &[] ), Some( Releases::V3_0_0 ) );
+		assert_eq!( Subtensor::get_uids_for_coldkey( &10 ), vec![ 0, 2 ] );
+		assert_eq!( Subtensor::get_uids_for_coldkey( &11 ), vec![ 1 ] );
+		assert_eq!( Subtensor::weights( 1 ), vec![ (0, 7), (2, 8) ] );
+		assert_eq!( Subtensor::bonds( 0 ), vec![ (1, 5) ] );
+		for neuron in neurons.iter() {
+			assert_eq!( &Subtensor::get_neuron_for_uid( neuron.uid ), neuron );
+		}
+	});
+}
+
+#[test]
+fn test_migrate_is_idempotent() {
+	new_test_ext().execute_with(|| {
+		setup_v1_neurons();
+		Subtensor::on_runtime_upgrade();
+		let neurons: Vec<NeuronMetadata<u64>> = (0..3).map( |uid| Subtensor::get_neuron_for_uid( uid ) ).collect();
+
+		// A second upgrade leaves the migrated state as is.
+		Subtensor::on_runtime_upgrade();
+		for neuron in neurons.iter() {
+			assert_eq!( &Subtensor::get_neuron_for_uid( neuron.uid ), neuron );
+		}
+		assert_eq!( Subtensor::weights( 0 ), vec![ (1, u32::MAX) ] );
+		assert_eq!( Subtensor::bonds( 1 ), vec![ (0, 9) ] );
+		assert_eq!( Subtensor::get_uids_for_coldkey( &10 ), vec![ 0, 2 ] );
+	});
+}
+
+#[test]
+fn test_migrate_checks_pass_around_an_upgrade() {
+	new_test_ext().execute_with(|| {
+		setup_v1_neurons();
+		assert_eq!( migrations::pre_migrate_checks::<Test>(), Ok(()) );
+		Subtensor::on_runtime_upgrade();
+		assert_eq!( migrations::post_migrate_checks::<Test>(), Ok(()) );
+	});
+}
+
+#[test]
+fn test_migrate_checks_catch_a_mismatched_layout() {
+	new_test_ext().execute_with(|| {
+		setup_v1_neurons();
+
+		// Old layout neurons do not pass the checks of the latest release.
+		put_storage_value( b"Subtensor", b"StorageVersion", &[], Releases::V3_0_0 );
+		assert!( migrations::pre_migrate_checks::<Test>().is_err() );
+		assert!( migrations::post_migrate_checks::<Test>().is_err() );
+	});
+}
+
+#[test]
+fn test_migrate_keeps_undecodable_neurons_for_the_checks() {
+	new_test_ext().execute_with(|| {
+		setup_v1_neurons();
+		put_storage_value( b"Subtensor", b"Neurons", &1u32.encode(), vec![ 1u8, 2, 3 ] );
+		assert!( migrations::pre_migrate_checks::<Test>().is_err() );
+
+		// The other neurons are migrated, the undecodable one is kept as is and fails the checks.
+		Subtensor::on_runtime_upgrade();
+		assert_eq!( Subtensor::weights( 0 ), vec![ (1, u32::MAX) ] );
+		assert_eq!( Subtensor::get_uids_for_coldkey( &10 ), vec![ 0, 2 ] );
+		assert_eq!( get_storage_value::<Vec<u8>>( b"Subtensor", b"Neurons", &1u32.encode() ), Some( vec![ 1u8, 2, 3 ] ) );
+		assert!( migrations::post_migrate_checks::<Test>().is_err() );
+	});
+}
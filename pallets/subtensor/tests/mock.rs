@@ -1,5 +1,5 @@
 
-use frame_support::{assert_ok, parameter_types, traits::{EnsureInherentsAreFirst, Hooks, OnRuntimeUpgrade, StorageMapShim}, weights::{Weight, IdentityFee, GetDispatchInfo, DispatchInfo}};
+use frame_support::{assert_ok, parameter_types, traits::{EnsureInherentsAreFirst, Hooks, OnRuntimeUpgrade, StorageMapShim}, weights::{Weight, IdentityFee, GetDispatchInfo, DispatchInfo, constants::WEIGHT_PER_SECOND}};
 
 use pallet_transaction_payment::{CurrencyAdapter};
 use sp_runtime::{
@@ -56,7 +56,7 @@ frame_support::construct_runtime!(
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
 		Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>},
 		Balances: pallet_balances::{Pallet, Call, Config<T>, Storage, Event<T>},
-		Subtensor: pallet_subtensor::{Pallet, Call, Config, Storage, Event<T>},
+		Subtensor: pallet_subtensor::{Pallet, Call, Config<T>, Storage, Event<T>},
 	}
 );
 
@@ -77,7 +77,8 @@ pub type TestRuntimeCall = frame_system::Call<Test>;
 
 parameter_types! {
 	pub const BlockHashCount: BlockNumber = 640;
-	pub BlockWeights: limits::BlockWeights = limits::BlockWeights::simple_max(1024);
+	/// Room for the weight of the pallet calls applied through Executive.
+	pub BlockWeights: limits::BlockWeights = limits::BlockWeights::simple_max(2 * WEIGHT_PER_SECOND);
 	pub const ExistentialDeposit: Balance = 1;
 	pub const TransactionByteFee: Balance = 100;
 	pub const SDebug:u64 = 1;
@@ -99,6 +100,8 @@ parameter_types! {
 	pub const InitialMinAllowedWeights: u64 = 0;
 	pub const InitialMaxAllowedMaxMinRatio: u64 = 0;
 	pub const InitialBlocksPerStep: u64 = 1;
+	pub const InitialStepChunkSize: u64 = 0;
+	pub const MaxStepWeight: Weight = Weight::MAX;
 	pub const InitialIssuance: u64 = 548833985028256;
 	pub const InitialDifficulty: u64 = 10000;
 	pub const MinimumDifficulty: u64 = 10000;
@@ -107,6 +110,14 @@ parameter_types! {
 	pub const InitialAdjustmentInterval: u64 = 100;
 	pub const InitialMaxRegistrationsPerBlock: u64 = 2;
 	pub const InitialTargetRegistrationsPerInterval: u64 = 2;
+	pub const InitialColdkeySwapDelay: u64 = 5;
+	pub const InitialUnbondingPeriod: u64 = 0;
+	pub const MaxUnbondingChunks: u32 = 4;
+	pub const InitialTransactionByteFee: u64 = 0;
+	pub const InitialFeelessQuota: u64 = 0;
+	pub const InitialFeelessQuotaInterval: u64 = 100;
+	pub const InitialWeightsRateLimit: u64 = 0;
+	pub const InitialServingRateLimit: u64 = 0;
 }
 
 thread_local!{
@@ -171,7 +182,7 @@ impl pallet_balances::Config for Test {
 impl pallet_subtensor::Config for Test {
 	type Event = ();
 	type Currency = Balances;
-	type TransactionByteFee = TransactionByteFee;
+	type WeightInfo = ();
 	type SDebug = SDebug;
 	type InitialRho = InitialRho;
 	type InitialKappa = InitialKappa;
@@ -195,10 +206,20 @@ impl pallet_subtensor::Config for Test {
 	type MinimumDifficulty = MinimumDifficulty;
 	type MaximumDifficulty = MaximumDifficulty;
 	type InitialBlocksPerStep = InitialBlocksPerStep;
+	type InitialStepChunkSize = InitialStepChunkSize;
+	type MaxStepWeight = MaxStepWeight;
 	type InitialActivityCutoff = InitialActivityCutoff;
 	type InitialAdjustmentInterval = InitialAdjustmentInterval;
 	type InitialMaxRegistrationsPerBlock = InitialMaxRegistrationsPerBlock;
 	type InitialTargetRegistrationsPerInterval = InitialTargetRegistrationsPerInterval;
+	type InitialColdkeySwapDelay = InitialColdkeySwapDelay;
+	type InitialUnbondingPeriod = InitialUnbondingPeriod;
+	type MaxUnbondingChunks = MaxUnbondingChunks;
+	type InitialTransactionByteFee = InitialTransactionByteFee;
+	type InitialFeelessQuota = InitialFeelessQuota;
+	type InitialFeelessQuotaInterval = InitialFeelessQuotaInterval;
+	type InitialWeightsRateLimit = InitialWeightsRateLimit;
+	type InitialServingRateLimit = InitialServingRateLimit;
 }
 
 impl pallet_sudo::Config for Test {
@@ -295,17 +316,18 @@ pub fn test_ext_with_balances(balances : Vec<(u64, u128)>) -> sp_io::TestExterna
 }
 
 
-// #[allow(dead_code)]
-// pub fn test_ext_with_stake(stake : Vec<(u64, u64)>) -> sp_io::TestExternalities {
-// 	let mut t = frame_system::GenesisConfig::default()
-// 	.build_storage::<Test>()
-// 	.unwrap();
-// 	pallet_subtensor::GenesisConfig {
-// 		stake: vec![]
-// 	}.assimilate_storage::<Test>(&mut t)
-// 		.unwrap();
-// 	t.into()
-// }
+#[allow(dead_code)]
+pub fn test_ext_with_genesis(genesis: pallet_subtensor::GenesisConfig<Test>) -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default()
+		.build_storage::<Test>()
+		.unwrap();
+
+	genesis
+		.assimilate_storage(&mut t)
+		.unwrap();
+
+	t.into()
+}
 
 #[allow(dead_code)]
 pub fn register_ok_neuron( hotkey_account_id: u64, coldkey_account_id: u64) -> NeuronMetadata<u64> {
@@ -1,4 +1,8 @@
-use pallet_subtensor::{Error};
+use pallet_subtensor::{Error, WeightInfo, SubtensorSignedExtension, CallType, ALREADY_REGISTERED};
+use sp_std::marker::PhantomData;
+use sp_runtime::traits::SignedExtension;
+use sp_runtime::transaction_validity::InvalidTransaction;
+use codec::Encode;
 use frame_support::{assert_ok};
 use frame_system::Config;
 mod mock;
@@ -20,7 +24,7 @@ fn test_subscribe_ok_dispatch_info_ok() {
 		let coldkey: u64 = 0;
         let call = Call::Subtensor(SubtensorCall::register( block_number, nonce, work, hotkey, coldkey ));
 		assert_eq!(call.get_dispatch_info(), DispatchInfo {
-			weight: 0,
+			weight: <() as WeightInfo>::register_prune( Subtensor::get_max_allowed_uids() as u32 ),
 			class: DispatchClass::Normal,
 			pays_fee: Pays::No
 		});
@@ -382,3 +386,282 @@ fn test_get_next_uid() {
 	});
 }
 
+
+/********************************************
+	registration coldkey index tests
+*********************************************/
+#[test]
+fn test_registration_indexes_coldkey() {
+	new_test_ext().execute_with(|| {
+		Subtensor::set_max_registratations_per_block( 100 );
+		let neuron0 = register_ok_neuron( 0, 10 );
+		let neuron1 = register_ok_neuron( 1, 11 );
+		let neuron2 = register_ok_neuron( 2, 10 );
+		assert_eq!( Subtensor::get_uids_for_coldkey( &10 ), vec![ neuron0.uid, neuron2.uid ] );
+		assert_eq!( Subtensor::get_uids_for_coldkey( &11 ), vec![ neuron1.uid ] );
+		assert_eq!( Subtensor::get_uids_for_coldkey( &12 ), Vec::<u32>::new() );
+		assert!( Subtensor::is_uid_owned_by_coldkey( &10, neuron2.uid ) );
+		assert!( !Subtensor::is_uid_owned_by_coldkey( &11, neuron2.uid ) );
+	});
+}
+
+#[test]
+fn test_pruning_removes_coldkey_index() {
+	new_test_ext().execute_with(|| {
+		Subtensor::set_max_allowed_uids ( 1 );
+		let neuron0 = register_ok_neuron( 0, 10 );
+		assert_eq!( Subtensor::get_uids_for_coldkey( &10 ), vec![ neuron0.uid ] );
+
+		// The graph is full, the next registration replaces uid 0.
+		step_block ( 1 );
+		let neuron1 = register_ok_neuron( 1, 11 );
+		assert_eq!( neuron1.uid, neuron0.uid );
+		assert_eq!( Subtensor::get_uids_for_coldkey( &10 ), Vec::<u32>::new() );
+		assert_eq!( Subtensor::get_uids_for_coldkey( &11 ), vec![ neuron1.uid ] );
+	});
+}
+
+#[test]
+fn test_pruning_removes_last_tx_blocks() {
+	new_test_ext().execute_with(|| {
+		Subtensor::set_max_allowed_uids ( 1 );
+		let neuron0 = register_ok_neuron( 0, 10 );
+		Subtensor::set_last_tx_block( &0, &CallType::SetWeights );
+		Subtensor::set_last_tx_block( &0, &CallType::Serve );
+		assert!( Subtensor::get_last_tx_block( &0, &CallType::SetWeights ).is_some() );
+
+		// The graph is full, the next registration replaces uid 0.
+		step_block ( 1 );
+		let neuron1 = register_ok_neuron( 1, 11 );
+		assert_eq!( neuron1.uid, neuron0.uid );
+		assert_eq!( Subtensor::get_last_tx_block( &0, &CallType::SetWeights ), None );
+		assert_eq!( Subtensor::get_last_tx_block( &0, &CallType::Serve ), None );
+	});
+}
+
+/********************************************
+	registration bonds tests
+*********************************************/
+#[test]
+fn test_pruning_clears_bonds_in_pruned_uid() {
+	new_test_ext().execute_with(|| {
+		Subtensor::set_max_registratations_per_block( 100 );
+		for i in 0..3 {
+			register_ok_neuron( i as u64, i as u64 );
+		}
+		Subtensor::set_stake_from_vector( vec![ 1_000_000_000; 3 ] );
+		Subtensor::set_weights_from_matrix( vec! [
+			vec! [0, u32::max_value(), 0 ],
+			vec! [0, 0, u32::max_value() ],
+			vec! [u32::max_value(), 0, 0 ],
+		]);
+		Subtensor::mechanism_step( Subtensor::get_block_emission() );
+		for uid in 0..3 {
+			assert_eq!( Subtensor::bonds( uid ).len(), 1 );
+		}
+
+		// The graph is full, the next registration replaces one of the uids.
+		Subtensor::set_max_allowed_uids( 3 );
+		let neuron3 = register_ok_neuron( 3, 3 );
+		let pruned: u32 = neuron3.uid;
+		assert_eq!( Subtensor::bonds( pruned ), Vec::<(u32,u64)>::new() );
+		assert_eq!( Subtensor::weights( pruned ), vec![ (pruned, u32::MAX) ] );
+		for uid in 0..3 {
+			assert!( Subtensor::bonds( uid ).iter().all( |(uid_j, _)| *uid_j != pruned ) );
+		}
+	});
+}
+
+/********************************************
+	registration::swap_hotkey() tests
+*********************************************/
+#[test]
+fn test_swap_hotkey_dispatch_info_ok() {
+	new_test_ext().execute_with(|| {
+		let call = Call::Subtensor(SubtensorCall::swap_hotkey(0, 1));
+		assert_eq!(call.get_dispatch_info(), DispatchInfo {
+			weight: <() as WeightInfo>::swap_hotkey(),
+			class: DispatchClass::Normal,
+			pays_fee: Pays::No
+		});
+	});
+}
+
+#[test]
+fn test_swap_hotkey_ok() {
+	new_test_ext().execute_with(|| {
+		Subtensor::set_max_registratations_per_block( 100 );
+		for i in 0..3 {
+			register_ok_neuron( i as u64, 10 + i as u64 );
+		}
+		Subtensor::set_stake_from_vector( vec![ 1_000_000_000; 3 ] );
+		Subtensor::set_weights_from_matrix( vec! [
+			vec! [0, u32::max_value(), 0 ],
+			vec! [0, 0, u32::max_value() ],
+			vec! [u32::max_value(), 0, 0 ],
+		]);
+		Subtensor::mechanism_step( Subtensor::get_block_emission() );
+		let neuron = Subtensor::get_neuron_for_hotkey( &1 );
+		let weights = Subtensor::weights( neuron.uid );
+		let bonds = Subtensor::bonds( neuron.uid );
+
+		assert_ok!(Subtensor::swap_hotkey(<<Test as Config>::Origin>::signed(11), 1, 100));
+
+		// The uid and everything attached to it is kept under the new hotkey.
+		assert!( !Subtensor::is_hotkey_active( &1 ) );
+		assert!( Subtensor::is_hotkey_active( &100 ) );
+		assert_eq!( Subtensor::get_uid_for_hotkey( &100 ), neuron.uid );
+		let swapped = Subtensor::get_neuron_for_hotkey( &100 );
+		assert_eq!( swapped.hotkey, 100 );
+		assert_eq!( swapped.coldkey, 11 );
+		assert_eq!( swapped.stake, neuron.stake );
+		assert_eq!( Subtensor::weights( neuron.uid ), weights );
+		assert_eq!( Subtensor::bonds( neuron.uid ), bonds );
+		assert_eq!( Subtensor::get_uids_for_coldkey( &11 ), vec![ neuron.uid ] );
+		assert_eq!( Subtensor::get_neuron_count(), 3 );
+
+		// The new hotkey controls the neuron, the old one can register again.
+		assert_ok!(Subtensor::set_weights(<<Test as Config>::Origin>::signed(100), vec![ neuron.uid ], vec![ u32::max_value() ]));
+		assert_ne!( Subtensor::weights( neuron.uid ), weights );
+		assert_eq!( Subtensor::set_weights(<<Test as Config>::Origin>::signed(1), vec![ neuron.uid ], vec![ u32::max_value() ]), Err(Error::<Test>::NotRegistered.into()) );
+		let neuron3 = register_ok_neuron( 1, 11 );
+		assert_eq!( neuron3.uid, 3 );
+	});
+}
+
+#[test]
+fn test_swap_hotkey_err_signature() {
+	new_test_ext().execute_with(|| {
+		let result = Subtensor::swap_hotkey(<<Test as Config>::Origin>::none(), 1, 100);
+		assert_eq!(result, DispatchError::BadOrigin.into());
+	});
+}
+
+#[test]
+fn test_swap_hotkey_err_not_registered() {
+	new_test_ext().execute_with(|| {
+		let result = Subtensor::swap_hotkey(<<Test as Config>::Origin>::signed(10), 1, 100);
+		assert_eq!(result, Err(Error::<Test>::NotRegistered.into()));
+	});
+}
+
+#[test]
+fn test_swap_hotkey_err_not_associated_coldkey() {
+	new_test_ext().execute_with(|| {
+		register_ok_neuron( 1, 10 );
+		let result = Subtensor::swap_hotkey(<<Test as Config>::Origin>::signed(11), 1, 100);
+		assert_eq!(result, Err(Error::<Test>::NonAssociatedColdKey.into()));
+		assert!( Subtensor::is_hotkey_active( &1 ) );
+		assert!( !Subtensor::is_hotkey_active( &100 ) );
+	});
+}
+
+#[test]
+fn test_swap_hotkey_err_new_hotkey_already_registered() {
+	new_test_ext().execute_with(|| {
+		let neuron0 = register_ok_neuron( 0, 10 );
+		let neuron1 = register_ok_neuron( 1, 10 );
+		let result = Subtensor::swap_hotkey(<<Test as Config>::Origin>::signed(10), 0, 1);
+		assert_eq!(result, Err(Error::<Test>::AlreadyRegistered.into()));
+		let result = Subtensor::swap_hotkey(<<Test as Config>::Origin>::signed(10), 0, 0);
+		assert_eq!(result, Err(Error::<Test>::AlreadyRegistered.into()));
+		assert_eq!( Subtensor::get_uid_for_hotkey( &0 ), neuron0.uid );
+		assert_eq!( Subtensor::get_uid_for_hotkey( &1 ), neuron1.uid );
+	});
+}
+
+#[test]
+fn test_validate_registration_ok() {
+	new_test_ext().execute_with(|| {
+		let block_number: u64 = 0;
+		let (nonce, work): (u64, Vec<u8>) = Subtensor::create_work_for_block_number( block_number );
+		let hotkey_account_id: u64 = 1;
+		let coldkey_account_id: u64 = 667;
+		let call = Call::Subtensor(SubtensorCall::register( block_number, nonce, work, hotkey_account_id, coldkey_account_id ));
+		let info = call.get_dispatch_info();
+
+		let valid = SubtensorSignedExtension::<Test>(PhantomData).validate(&hotkey_account_id, &call, &info, 10).unwrap();
+		assert_eq!( valid.priority, u64::MAX - 3 );
+		assert_eq!( valid.longevity, 3 );
+		assert_eq!( valid.provides, vec![("register", hotkey_account_id).encode()] );
+	});
+}
+
+#[test]
+fn test_validate_registration_rejects_bad_work() {
+	new_test_ext().execute_with(|| {
+		let hotkey_account_id: u64 = 1;
+		let coldkey_account_id: u64 = 667;
+
+		// Work for another block than the one claimed.
+		let (nonce, work): (u64, Vec<u8>) = Subtensor::create_work_for_block_number( 1 );
+		let call = Call::Subtensor(SubtensorCall::register( 0, nonce, work, hotkey_account_id, coldkey_account_id ));
+		let info = call.get_dispatch_info();
+		assert_eq!( SubtensorSignedExtension::<Test>(PhantomData).validate(&hotkey_account_id, &call, &info, 10), Err(InvalidTransaction::BadProof.into()) );
+
+		// Work that does not meet the difficulty.
+		let call = Call::Subtensor(SubtensorCall::register( 0, 0, vec![255;32], hotkey_account_id, coldkey_account_id ));
+		assert_eq!( SubtensorSignedExtension::<Test>(PhantomData).validate(&hotkey_account_id, &call, &info, 10), Err(InvalidTransaction::BadProof.into()) );
+
+		// Work that is not a 32 byte hash.
+		let call = Call::Subtensor(SubtensorCall::register( 0, 0, vec![0;4], hotkey_account_id, coldkey_account_id ));
+		assert_eq!( SubtensorSignedExtension::<Test>(PhantomData).validate(&hotkey_account_id, &call, &info, 10), Err(InvalidTransaction::BadProof.into()) );
+	});
+}
+
+#[test]
+fn test_validate_registration_rejects_stale_work() {
+	new_test_ext().execute_with(|| {
+		let hotkey_account_id: u64 = 1;
+		let coldkey_account_id: u64 = 667;
+
+		// Work on a future block.
+		let (nonce, work): (u64, Vec<u8>) = Subtensor::create_work_for_block_number( 1 );
+		let call = Call::Subtensor(SubtensorCall::register( 1, nonce, work, hotkey_account_id, coldkey_account_id ));
+		let info = call.get_dispatch_info();
+		assert_eq!( SubtensorSignedExtension::<Test>(PhantomData).validate(&hotkey_account_id, &call, &info, 10), Err(InvalidTransaction::Stale.into()) );
+
+		// Work older than 3 blocks.
+		let (nonce, work): (u64, Vec<u8>) = Subtensor::create_work_for_block_number( 0 );
+		let call = Call::Subtensor(SubtensorCall::register( 0, nonce, work, hotkey_account_id, coldkey_account_id ));
+		step_block(2);
+		let valid = SubtensorSignedExtension::<Test>(PhantomData).validate(&hotkey_account_id, &call, &info, 10).unwrap();
+		assert_eq!( valid.longevity, 1 );
+		step_block(1);
+		assert_eq!( SubtensorSignedExtension::<Test>(PhantomData).validate(&hotkey_account_id, &call, &info, 10), Err(InvalidTransaction::Stale.into()) );
+	});
+}
+
+#[test]
+fn test_validate_registration_rejects_registered_hotkey() {
+	new_test_ext().execute_with(|| {
+		let block_number: u64 = 0;
+		let hotkey_account_id: u64 = 1;
+		let coldkey_account_id: u64 = 667;
+		register_ok_neuron( hotkey_account_id, coldkey_account_id );
+
+		let (nonce, work): (u64, Vec<u8>) = Subtensor::create_work_for_block_number( block_number );
+		let call = Call::Subtensor(SubtensorCall::register( block_number, nonce, work, hotkey_account_id, coldkey_account_id ));
+		let info = call.get_dispatch_info();
+		assert_eq!( SubtensorSignedExtension::<Test>(PhantomData).validate(&hotkey_account_id, &call, &info, 10), Err(InvalidTransaction::Custom(ALREADY_REGISTERED).into()) );
+	});
+}
+
+#[test]
+fn test_validate_registration_ignores_registrations_this_block() {
+	new_test_ext().execute_with(|| {
+		let block_number: u64 = 0;
+		Subtensor::set_max_registratations_per_block( 1 );
+		register_ok_neuron( 0, 0 );
+
+		// The pool validates against the counter of the last block, the cap is left to dispatch.
+		let hotkey_account_id: u64 = 1;
+		let coldkey_account_id: u64 = 667;
+		let (nonce, work): (u64, Vec<u8>) = Subtensor::create_work_for_block_number( block_number );
+		let call = Call::Subtensor(SubtensorCall::register( block_number, nonce, work.clone(), hotkey_account_id, coldkey_account_id ));
+		let info = call.get_dispatch_info();
+		assert!( SubtensorSignedExtension::<Test>(PhantomData).validate(&hotkey_account_id, &call, &info, 10).is_ok() );
+		let result = Subtensor::register(<<Test as Config>::Origin>::signed(hotkey_account_id), block_number, nonce, work, hotkey_account_id, coldkey_account_id);
+		assert_eq!( result, Err(Error::<Test>::ToManyRegistrationsThisBlock.into()) );
+	});
+}
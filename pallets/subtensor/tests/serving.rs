@@ -1,4 +1,4 @@
-use pallet_subtensor::{Error};
+use pallet_subtensor::{Error, WeightInfo, CallType};
 use frame_support::{assert_ok};
 use frame_system::Config;
 mod mock;
@@ -19,7 +19,7 @@ fn test_serve_ok_dispatch_info_ok() {
         let modality = 0;
         let call = Call::Subtensor(SubtensorCall::serve_axon(version, ip, port, ip_type, modality));
 		assert_eq!(call.get_dispatch_info(), DispatchInfo {
-			weight: 0,
+			weight: <() as WeightInfo>::serve_axon(),
 			class: DispatchClass::Normal,
 			pays_fee: Pays::No
 		});
@@ -221,3 +221,46 @@ fn test_serve_success_with_update() {
 
 	});
 }
+
+#[test]
+fn test_serve_rate_limit() {
+	new_test_ext().execute_with(|| {
+		let version = 0;
+		let ip = ipv4(8,8,8,8);
+		let ip_type = 4;
+		let port = 1337;
+		let modality = 0;
+		let hotkey: u64 = 0;
+		let coldkey: u64 = 0;
+
+		register_ok_neuron(hotkey, coldkey);
+		Subtensor::set_tx_rate_limit(CallType::Serve, 5);
+
+		assert_ok!(Subtensor::serve_axon(<<Test as Config>::Origin>::signed(hotkey), version, ip, port, ip_type, modality ));
+		let result = Subtensor::serve_axon(<<Test as Config>::Origin>::signed(hotkey), version, ip, port + 1, ip_type, modality );
+		assert_eq!(result, Err(Error::<Test>::TxRateLimitExceeded.into()));
+		assert_eq!(Subtensor::get_neuron_for_hotkey(&hotkey).port, port);
+
+		step_block(5);
+		assert_ok!(Subtensor::serve_axon(<<Test as Config>::Origin>::signed(hotkey), version, ip, port + 1, ip_type, modality ));
+		assert_eq!(Subtensor::get_neuron_for_hotkey(&hotkey).port, port + 1);
+	});
+}
+
+#[test]
+fn test_serve_rate_limit_survives_hotkey_swap() {
+	new_test_ext().execute_with(|| {
+		let ip = ipv4(8,8,8,8);
+		let old_hotkey: u64 = 0;
+		let new_hotkey: u64 = 1;
+		let coldkey: u64 = 2;
+
+		register_ok_neuron(old_hotkey, coldkey);
+		Subtensor::set_tx_rate_limit(CallType::Serve, 5);
+		assert_ok!(Subtensor::serve_axon(<<Test as Config>::Origin>::signed(old_hotkey), 0, ip, 1337, 4, 0 ));
+		assert_ok!(Subtensor::swap_hotkey(<<Test as Config>::Origin>::signed(coldkey), old_hotkey, new_hotkey));
+
+		let result = Subtensor::serve_axon(<<Test as Config>::Origin>::signed(new_hotkey), 0, ip, 1337, 4, 0 );
+		assert_eq!(result, Err(Error::<Test>::TxRateLimitExceeded.into()));
+	});
+}
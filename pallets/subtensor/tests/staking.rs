@@ -4,7 +4,7 @@ mod mock;
 use mock::*;
 use mock::{TestXt};
 use frame_support::sp_runtime::DispatchError;
-use pallet_subtensor::{Error};
+use pallet_subtensor::{Error, WeightInfo, RewardDestination};
 use frame_support::weights::{GetDispatchInfo, DispatchInfo, DispatchClass, Pays};
 
 /***********************************************************
@@ -19,7 +19,7 @@ fn test_add_stake_dispatch_info_ok() {
 		let stake = 5000;
         let call = Call::Subtensor(SubtensorCall::add_stake(account_id, stake));
 		assert_eq!(call.get_dispatch_info(), DispatchInfo {
-			weight: 0,
+			weight: <() as WeightInfo>::add_stake(),
 			class: DispatchClass::Normal,
 			pays_fee: Pays::No
 		});
@@ -191,7 +191,7 @@ fn test_remove_stake_dispatch_info_ok() {
 		let call = Call::Subtensor(SubtensorCall::remove_stake(account_id, stake));
 
 		assert_eq!(call.get_dispatch_info(), DispatchInfo {
-			weight: 0,
+			weight: <() as WeightInfo>::remove_stake(),
 			class: DispatchClass::Normal,
 			pays_fee: Pays::No
 		});
@@ -278,6 +278,417 @@ fn test_remove_stake_no_enough_stake() {
 	});
 }
 
+#[test]
+fn test_remove_stake_unbonds() {
+	new_test_ext().execute_with(|| {
+		let coldkey_id = 544;
+		let hotkey_id = 54544;
+		let neuron = register_ok_neuron( hotkey_id, coldkey_id );
+		Subtensor::add_stake_to_neuron_hotkey_account(neuron.uid, 10000);
+		Subtensor::set_unbonding_period( 10 );
+
+		assert_ok!(Subtensor::remove_stake(<<Test as Config>::Origin>::signed(coldkey_id), hotkey_id, 4000));
+
+		// The stake leaves the neuron and the total right away, the balance is only paid out once unbonded.
+		assert_eq!(Subtensor::get_stake_of_neuron_hotkey_account_by_uid(neuron.uid), 6000);
+		assert_eq!(Subtensor::get_total_stake(), 6000);
+		assert_eq!(Subtensor::get_coldkey_balance(&coldkey_id), 0);
+		assert_eq!(Subtensor::unbonding(coldkey_id), vec![ (4000, 10) ]);
+
+		// Removals in the same block unlock together.
+		assert_ok!(Subtensor::remove_stake(<<Test as Config>::Origin>::signed(coldkey_id), hotkey_id, 1000));
+		assert_eq!(Subtensor::unbonding(coldkey_id), vec![ (5000, 10) ]);
+	});
+}
+
+#[test]
+fn test_remove_stake_err_too_many_unbonding_chunks() {
+	new_test_ext().execute_with(|| {
+		let coldkey_id = 544;
+		let hotkey_id = 54544;
+		let neuron = register_ok_neuron( hotkey_id, coldkey_id );
+		Subtensor::add_stake_to_neuron_hotkey_account(neuron.uid, 10000);
+		Subtensor::set_unbonding_period( 10 );
+
+		// The mock allows 4 chunks, one per block.
+		for _ in 0..4 {
+			assert_ok!(Subtensor::remove_stake(<<Test as Config>::Origin>::signed(coldkey_id), hotkey_id, 1000));
+			step_block( 1 );
+		}
+		let result = Subtensor::remove_stake(<<Test as Config>::Origin>::signed(coldkey_id), hotkey_id, 1000);
+		assert_eq!(result, Err(Error::<Test>::TooManyUnbondingChunks.into()));
+		assert_eq!(Subtensor::get_stake_of_neuron_hotkey_account_by_uid(neuron.uid), 6000);
+		assert_eq!(Subtensor::unbonding(coldkey_id).len(), 4);
+
+		// Withdrawing the first chunk makes room again.
+		step_block( 6 );
+		assert_ok!(Subtensor::withdraw_unbonded(<<Test as Config>::Origin>::signed(coldkey_id)));
+		assert_ok!(Subtensor::remove_stake(<<Test as Config>::Origin>::signed(coldkey_id), hotkey_id, 1000));
+	});
+}
+
+/***********************************************************
+	staking::withdraw_unbonded() tests
+************************************************************/
+
+#[test]
+fn test_withdraw_unbonded_dispatch_info_ok() {
+	new_test_ext().execute_with(|| {
+		let call = Call::Subtensor(SubtensorCall::withdraw_unbonded());
+		assert_eq!(call.get_dispatch_info(), DispatchInfo {
+			weight: <() as WeightInfo>::withdraw_unbonded(),
+			class: DispatchClass::Normal,
+			pays_fee: Pays::No
+		});
+	});
+}
+
+#[test]
+fn test_withdraw_unbonded_ok() {
+	new_test_ext().execute_with(|| {
+		let coldkey_id = 544;
+		let hotkey_id = 54544;
+		let neuron = register_ok_neuron( hotkey_id, coldkey_id );
+		Subtensor::add_stake_to_neuron_hotkey_account(neuron.uid, 10000);
+		Subtensor::set_unbonding_period( 10 );
+		assert_ok!(Subtensor::remove_stake(<<Test as Config>::Origin>::signed(coldkey_id), hotkey_id, 4000));
+		step_block( 5 );
+		assert_ok!(Subtensor::remove_stake(<<Test as Config>::Origin>::signed(coldkey_id), hotkey_id, 1000));
+
+		// Only the first chunk has unbonded after 10 blocks.
+		step_block( 5 );
+		assert_ok!(Subtensor::withdraw_unbonded(<<Test as Config>::Origin>::signed(coldkey_id)));
+		assert_eq!(Subtensor::get_coldkey_balance(&coldkey_id), 4000);
+		assert_eq!(Subtensor::unbonding(coldkey_id), vec![ (1000, 15) ]);
+
+		step_block( 5 );
+		assert_ok!(Subtensor::withdraw_unbonded(<<Test as Config>::Origin>::signed(coldkey_id)));
+		assert_eq!(Subtensor::get_coldkey_balance(&coldkey_id), 5000);
+		assert_eq!(Subtensor::get_unbonding_stake(&coldkey_id), 0);
+	});
+}
+
+#[test]
+fn test_withdraw_unbonded_err_signature() {
+	new_test_ext().execute_with(|| {
+		let result = Subtensor::withdraw_unbonded(<<Test as Config>::Origin>::none());
+		assert_eq!(result, DispatchError::BadOrigin.into());
+	});
+}
+
+#[test]
+fn test_withdraw_unbonded_err_nothing_unbonded() {
+	new_test_ext().execute_with(|| {
+		let coldkey_id = 544;
+		let hotkey_id = 54544;
+		let neuron = register_ok_neuron( hotkey_id, coldkey_id );
+		Subtensor::add_stake_to_neuron_hotkey_account(neuron.uid, 10000);
+		Subtensor::set_unbonding_period( 10 );
+
+		let result = Subtensor::withdraw_unbonded(<<Test as Config>::Origin>::signed(coldkey_id));
+		assert_eq!(result, Err(Error::<Test>::NoUnbondedStake.into()));
+
+		assert_ok!(Subtensor::remove_stake(<<Test as Config>::Origin>::signed(coldkey_id), hotkey_id, 4000));
+		step_block( 9 );
+		let result = Subtensor::withdraw_unbonded(<<Test as Config>::Origin>::signed(coldkey_id));
+		assert_eq!(result, Err(Error::<Test>::NoUnbondedStake.into()));
+		assert_eq!(Subtensor::get_coldkey_balance(&coldkey_id), 0);
+	});
+}
+
+
+/***********************************************************
+	staking::move_stake() tests
+************************************************************/
+
+#[test]
+fn test_move_stake_dispatch_info_ok() {
+	new_test_ext().execute_with(|| {
+		let call = Call::Subtensor(SubtensorCall::move_stake(0, 1, 5000));
+		assert_eq!(call.get_dispatch_info(), DispatchInfo {
+			weight: <() as WeightInfo>::move_stake(),
+			class: DispatchClass::Normal,
+			pays_fee: Pays::No
+		});
+	});
+}
+
+#[test]
+fn test_move_stake_ok() {
+	new_test_ext().execute_with(|| {
+		let coldkey_account_id = 4343;
+		let origin_hotkey = 4968585;
+		let destination_hotkey = 4968586;
+		let amount = 10000;
+
+		let origin_neuron = register_ok_neuron( origin_hotkey, coldkey_account_id );
+		let destination_neuron = register_ok_neuron( destination_hotkey, coldkey_account_id );
+		Subtensor::add_stake_to_neuron_hotkey_account(origin_neuron.uid, amount);
+		assert_eq!(Subtensor::get_total_stake(), amount);
+
+		assert_ok!(Subtensor::move_stake(<<Test as Config>::Origin>::signed(coldkey_account_id), origin_hotkey, destination_hotkey, 3000));
+		assert_eq!(Subtensor::get_stake_of_neuron_hotkey_account_by_uid(origin_neuron.uid), 7000);
+		assert_eq!(Subtensor::get_stake_of_neuron_hotkey_account_by_uid(destination_neuron.uid), 3000);
+
+		// The stake never passes through the coldkey and the total is unchanged.
+		assert_eq!(Subtensor::get_coldkey_balance(&coldkey_account_id), 0);
+		assert_eq!(Subtensor::get_total_stake(), amount);
+
+		// All of it can be moved back.
+		assert_ok!(Subtensor::move_stake(<<Test as Config>::Origin>::signed(coldkey_account_id), destination_hotkey, origin_hotkey, 3000));
+		assert_eq!(Subtensor::get_stake_of_neuron_hotkey_account_by_uid(origin_neuron.uid), amount);
+		assert_eq!(Subtensor::get_stake_of_neuron_hotkey_account_by_uid(destination_neuron.uid), 0);
+		assert_eq!(Subtensor::get_total_stake(), amount);
+	});
+}
+
+#[test]
+fn test_move_stake_err_signature() {
+	new_test_ext().execute_with(|| {
+		let result = Subtensor::move_stake(<<Test as Config>::Origin>::none(), 1, 2, 1000);
+		assert_eq!(result, DispatchError::BadOrigin.into());
+	});
+}
+
+#[test]
+fn test_move_stake_err_not_active() {
+	new_test_ext().execute_with(|| {
+		let coldkey_id = 544;
+		let hotkey_id = 54544;
+		let _neuron = register_ok_neuron( hotkey_id, coldkey_id );
+
+		let result = Subtensor::move_stake(<<Test as Config>::Origin>::signed(coldkey_id), hotkey_id, 1337, 1000);
+		assert_eq!(result, Err(Error::<Test>::NotRegistered.into()));
+		let result = Subtensor::move_stake(<<Test as Config>::Origin>::signed(coldkey_id), 1337, hotkey_id, 1000);
+		assert_eq!(result, Err(Error::<Test>::NotRegistered.into()));
+	});
+}
+
+#[test]
+fn test_move_stake_err_same_hotkey() {
+	new_test_ext().execute_with(|| {
+		let coldkey_id = 544;
+		let hotkey_id = 54544;
+		let neuron = register_ok_neuron( hotkey_id, coldkey_id );
+		Subtensor::add_stake_to_neuron_hotkey_account(neuron.uid, 1000);
+
+		let result = Subtensor::move_stake(<<Test as Config>::Origin>::signed(coldkey_id), hotkey_id, hotkey_id, 1000);
+		assert_eq!(result, Err(Error::<Test>::SameHotkey.into()));
+	});
+}
+
+#[test]
+fn test_move_stake_err_hotkeys_do_not_share_coldkey() {
+	new_test_ext().execute_with(|| {
+		let coldkey_id = 544;
+		let other_coldkey_id = 545;
+		let origin_hotkey = 54544;
+		let destination_hotkey = 54545;
+		let origin_neuron = register_ok_neuron( origin_hotkey, coldkey_id );
+		let destination_neuron = register_ok_neuron( destination_hotkey, other_coldkey_id );
+		Subtensor::add_stake_to_neuron_hotkey_account(origin_neuron.uid, 1000);
+
+		// Moving into a neuron of another coldkey.
+		let result = Subtensor::move_stake(<<Test as Config>::Origin>::signed(coldkey_id), origin_hotkey, destination_hotkey, 1000);
+		assert_eq!(result, Err(Error::<Test>::NonAssociatedColdKey.into()));
+
+		// Moving out of a neuron of another coldkey.
+		let result = Subtensor::move_stake(<<Test as Config>::Origin>::signed(other_coldkey_id), origin_hotkey, destination_hotkey, 1000);
+		assert_eq!(result, Err(Error::<Test>::NonAssociatedColdKey.into()));
+
+		assert_eq!(Subtensor::get_stake_of_neuron_hotkey_account_by_uid(origin_neuron.uid), 1000);
+		assert_eq!(Subtensor::get_stake_of_neuron_hotkey_account_by_uid(destination_neuron.uid), 0);
+	});
+}
+
+#[test]
+fn test_move_stake_err_not_enough_stake() {
+	new_test_ext().execute_with(|| {
+		let coldkey_id = 544;
+		let origin_hotkey = 54544;
+		let destination_hotkey = 54545;
+		let origin_neuron = register_ok_neuron( origin_hotkey, coldkey_id );
+		let _destination_neuron = register_ok_neuron( destination_hotkey, coldkey_id );
+		Subtensor::add_stake_to_neuron_hotkey_account(origin_neuron.uid, 1000);
+
+		let result = Subtensor::move_stake(<<Test as Config>::Origin>::signed(coldkey_id), origin_hotkey, destination_hotkey, 1001);
+		assert_eq!(result, Err(Error::<Test>::NotEnoughStaketoWithdraw.into()));
+		assert_eq!(Subtensor::get_stake_of_neuron_hotkey_account_by_uid(origin_neuron.uid), 1000);
+		assert_eq!(Subtensor::get_total_stake(), 1000);
+	});
+}
+
+/***********************************************************
+	staking::transfer_stake() tests
+************************************************************/
+
+#[test]
+fn test_transfer_stake_dispatch_info_ok() {
+	new_test_ext().execute_with(|| {
+		let call = Call::Subtensor(SubtensorCall::transfer_stake(0, 1, 5000));
+		assert_eq!(call.get_dispatch_info(), DispatchInfo {
+			weight: <() as WeightInfo>::transfer_stake(),
+			class: DispatchClass::Normal,
+			pays_fee: Pays::No
+		});
+	});
+}
+
+#[test]
+fn test_transfer_stake_ok() {
+	new_test_ext().execute_with(|| {
+		let coldkey_id = 544;
+		let other_coldkey_id = 545;
+		let origin_hotkey = 54544;
+		let destination_hotkey = 54545;
+		let origin_neuron = register_ok_neuron( origin_hotkey, coldkey_id );
+		let destination_neuron = register_ok_neuron( destination_hotkey, other_coldkey_id );
+		Subtensor::add_stake_to_neuron_hotkey_account(origin_neuron.uid, 10000);
+
+		assert_ok!(Subtensor::transfer_stake(<<Test as Config>::Origin>::signed(coldkey_id), origin_hotkey, destination_hotkey, 4000));
+		assert_eq!(Subtensor::get_stake_of_neuron_hotkey_account_by_uid(origin_neuron.uid), 6000);
+		assert_eq!(Subtensor::get_stake_of_neuron_hotkey_account_by_uid(destination_neuron.uid), 4000);
+		assert_eq!(Subtensor::get_total_stake(), 10000);
+
+		// The transferred stake now belongs to the other coldkey.
+		let result = Subtensor::remove_stake(<<Test as Config>::Origin>::signed(coldkey_id), destination_hotkey, 4000);
+		assert_eq!(result, Err(Error::<Test>::NonAssociatedColdKey.into()));
+		assert_ok!(Subtensor::remove_stake(<<Test as Config>::Origin>::signed(other_coldkey_id), destination_hotkey, 4000));
+		assert_eq!(Subtensor::get_coldkey_balance(&other_coldkey_id), 4000);
+		assert_eq!(Subtensor::get_total_stake(), 6000);
+	});
+}
+
+#[test]
+fn test_transfer_stake_err_signature() {
+	new_test_ext().execute_with(|| {
+		let result = Subtensor::transfer_stake(<<Test as Config>::Origin>::none(), 1, 2, 1000);
+		assert_eq!(result, DispatchError::BadOrigin.into());
+	});
+}
+
+#[test]
+fn test_transfer_stake_err_not_active() {
+	new_test_ext().execute_with(|| {
+		let coldkey_id = 544;
+		let hotkey_id = 54544;
+		let neuron = register_ok_neuron( hotkey_id, coldkey_id );
+		Subtensor::add_stake_to_neuron_hotkey_account(neuron.uid, 1000);
+
+		let result = Subtensor::transfer_stake(<<Test as Config>::Origin>::signed(coldkey_id), hotkey_id, 1337, 1000);
+		assert_eq!(result, Err(Error::<Test>::NotRegistered.into()));
+		assert_eq!(Subtensor::get_total_stake(), 1000);
+	});
+}
+
+#[test]
+fn test_transfer_stake_err_same_hotkey() {
+	new_test_ext().execute_with(|| {
+		let coldkey_id = 544;
+		let hotkey_id = 54544;
+		let neuron = register_ok_neuron( hotkey_id, coldkey_id );
+		Subtensor::add_stake_to_neuron_hotkey_account(neuron.uid, 1000);
+
+		let result = Subtensor::transfer_stake(<<Test as Config>::Origin>::signed(coldkey_id), hotkey_id, hotkey_id, 1000);
+		assert_eq!(result, Err(Error::<Test>::SameHotkey.into()));
+	});
+}
+
+#[test]
+fn test_transfer_stake_err_neuron_does_not_belong_to_coldkey() {
+	new_test_ext().execute_with(|| {
+		let coldkey_id = 544;
+		let other_coldkey_id = 545;
+		let origin_hotkey = 54544;
+		let destination_hotkey = 54545;
+		let origin_neuron = register_ok_neuron( origin_hotkey, coldkey_id );
+		let _destination_neuron = register_ok_neuron( destination_hotkey, other_coldkey_id );
+		Subtensor::add_stake_to_neuron_hotkey_account(origin_neuron.uid, 1000);
+
+		// Only the owner of the origin hotkey can transfer its stake.
+		let result = Subtensor::transfer_stake(<<Test as Config>::Origin>::signed(other_coldkey_id), origin_hotkey, destination_hotkey, 1000);
+		assert_eq!(result, Err(Error::<Test>::NonAssociatedColdKey.into()));
+		assert_eq!(Subtensor::get_stake_of_neuron_hotkey_account_by_uid(origin_neuron.uid), 1000);
+	});
+}
+
+#[test]
+fn test_transfer_stake_err_not_enough_stake() {
+	new_test_ext().execute_with(|| {
+		let coldkey_id = 544;
+		let origin_hotkey = 54544;
+		let destination_hotkey = 54545;
+		let origin_neuron = register_ok_neuron( origin_hotkey, coldkey_id );
+		let _destination_neuron = register_ok_neuron( destination_hotkey, 545 );
+		Subtensor::add_stake_to_neuron_hotkey_account(origin_neuron.uid, 1000);
+
+		let result = Subtensor::transfer_stake(<<Test as Config>::Origin>::signed(coldkey_id), origin_hotkey, destination_hotkey, 1001);
+		assert_eq!(result, Err(Error::<Test>::NotEnoughStaketoWithdraw.into()));
+		assert_eq!(Subtensor::get_total_stake(), 1000);
+	});
+}
+
+
+/***********************************************************
+	staking::set_reward_destination() tests
+************************************************************/
+
+#[test]
+fn test_set_reward_destination_dispatch_info_ok() {
+	new_test_ext().execute_with(|| {
+		let call = Call::Subtensor(SubtensorCall::set_reward_destination(0, RewardDestination::Coldkey));
+		assert_eq!(call.get_dispatch_info(), DispatchInfo {
+			weight: <() as WeightInfo>::set_reward_destination(),
+			class: DispatchClass::Normal,
+			pays_fee: Pays::No
+		});
+	});
+}
+
+#[test]
+fn test_set_reward_destination_ok() {
+	new_test_ext().execute_with(|| {
+		let coldkey_id = 544;
+		let hotkey_id = 54544;
+		let neuron = register_ok_neuron( hotkey_id, coldkey_id );
+		assert_eq!(Subtensor::reward_destination(neuron.uid), RewardDestination::Stake);
+		assert_ok!(Subtensor::set_reward_destination(<<Test as Config>::Origin>::signed(coldkey_id), hotkey_id, RewardDestination::Account(1)));
+		assert_eq!(Subtensor::reward_destination(neuron.uid), RewardDestination::Account(1));
+		assert_ok!(Subtensor::set_reward_destination(<<Test as Config>::Origin>::signed(coldkey_id), hotkey_id, RewardDestination::Stake));
+		assert_eq!(Subtensor::reward_destination(neuron.uid), RewardDestination::Stake);
+	});
+}
+
+#[test]
+fn test_set_reward_destination_err_not_active() {
+	new_test_ext().execute_with(|| {
+		let result = Subtensor::set_reward_destination(<<Test as Config>::Origin>::signed(544), 54544, RewardDestination::Coldkey);
+		assert_eq!(result, Err(Error::<Test>::NotRegistered.into()));
+	});
+}
+
+#[test]
+fn test_set_reward_destination_err_neuron_does_not_belong_to_coldkey() {
+	new_test_ext().execute_with(|| {
+		let hotkey_id = 54544;
+		let neuron = register_ok_neuron( hotkey_id, 544 );
+		let result = Subtensor::set_reward_destination(<<Test as Config>::Origin>::signed(545), hotkey_id, RewardDestination::Coldkey);
+		assert_eq!(result, Err(Error::<Test>::NonAssociatedColdKey.into()));
+		assert_eq!(Subtensor::reward_destination(neuron.uid), RewardDestination::Stake);
+	});
+}
+
+#[test]
+fn test_reward_destination_cleared_on_registration() {
+	new_test_ext().execute_with(|| {
+		let neuron = register_ok_neuron( 1, 544 );
+		assert_ok!(Subtensor::set_reward_destination(<<Test as Config>::Origin>::signed(544), 1, RewardDestination::Coldkey));
+		Subtensor::set_max_allowed_uids( 1 );
+		let replacement = register_ok_neuron( 2, 545 );
+		assert_eq!(replacement.uid, neuron.uid);
+		assert_eq!(Subtensor::reward_destination(neuron.uid), RewardDestination::Stake);
+	});
+}
 
 /***********************************************************
 	staking::get_coldkey_balance() tests
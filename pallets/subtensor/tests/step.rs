@@ -1,5 +1,10 @@
 mod mock;
 use mock::*;
+use frame_support::traits::Hooks;
+use frame_support::assert_ok;
+use frame_support::weights::Weight;
+use frame_system::Config;
+use pallet_subtensor::{Error, WeightInfo, RewardDestination};
 
 /***********************************************************
 	staking::add_stake() tests
@@ -348,55 +353,168 @@ fn test_two_steps_with_many_outward_weights() {
 }
 
 
-// #[test]
-// fn test_steps_with_foundation_distribution() {
-//     new_test_ext().execute_with( || {
-//         Subtensor::set_max_registratations_per_block( 100 );
-//         let initial_stake:u64 = 1000000000;
-//         for i in 0..4 {
-//             register_ok_neuron(i as u64, i as u64 );
-//         }
-//         let weights_matrix: Vec<Vec<u32>> = vec! [
-//             vec! [0, u32::max_value(), 0, 0 ],
-//             vec! [0, 0, u32::max_value(), 0 ],
-//             vec! [0, 0, 0, u32::max_value() ], 
-//             vec! [u32::max_value(), 0, 0, 0 ],
-//         ];
-//         Subtensor::set_weights_from_matrix( weights_matrix.clone() );
-//         Subtensor::set_stake_from_vector( vec![ initial_stake; 4 ] );
-//         step_block (1);
-//         assert!( approx_equals( Subtensor::get_total_issuance(), Subtensor::get_initial_total_issuance() + 1000000000, 10)); // approx
-//         assert!( vec_approx_equals ( &Subtensor::get_stake(), &vec![1250000000, 1250000000, 1250000000, 1250000000], 10) );
-//         assert!( vec_approx_equals ( &Subtensor::get_emission(), &vec![250000000, 250000000, 250000000, 250000000], 10) );
-//         assert_eq!( Subtensor::get_coldkey_balance( &Subtensor::get_foundation_account() ), 0);
-
-//         Subtensor::set_foundation_distribution( 50 );
-//         step_block (1);
-//         assert!( approx_equals( Subtensor::get_total_issuance(), Subtensor::get_initial_total_issuance() + 2000000000, 10)); // approx
-//         assert!( vec_approx_equals ( &Subtensor::get_stake(), &vec![1375000000, 1375000000, 1375000000, 1375000000], 10) );
-//         assert!( vec_approx_equals ( &Subtensor::get_emission(), &vec![125000000, 125000000, 125000000, 125000000], 10) );
-//         assert_eq!( Subtensor::get_coldkey_balance( &Subtensor::get_foundation_account() ), 500000000);
-
-//         Subtensor::set_foundation_distribution( 0 );
-//         step_block (1);
-//         assert!( approx_equals( Subtensor::get_total_issuance(), Subtensor::get_initial_total_issuance() + 3000000000, 10)); // approx
-//         assert!( vec_approx_equals ( &Subtensor::get_stake(), &vec![1625000000, 1625000000, 1625000000, 1625000000], 10) );
-//         assert!( vec_approx_equals ( &Subtensor::get_emission(), &vec![250000000, 250000000, 250000000, 250000000], 10) );
-//         assert_eq!( Subtensor::get_coldkey_balance( &Subtensor::get_foundation_account() ), 500000000);
-
-//         // Test set foundation account.
-//         Subtensor::set_foundation_distribution( 50 );
-//         let prev_foundation_account: u64 = Subtensor::get_foundation_account();
-//         Subtensor::set_foundation_account( 1 ); 
-//         assert_eq!( Subtensor::get_foundation_account(), 1 );
-//         step_block (1);
-//         assert!( approx_equals( Subtensor::get_total_issuance(), Subtensor::get_initial_total_issuance() + 4000000000, 10)); // approx
-//         assert!( vec_approx_equals ( &Subtensor::get_stake(), &vec![1750000000, 1750000000, 1750000000, 1750000000], 10) );
-//         assert!( vec_approx_equals ( &Subtensor::get_emission(), &vec![125000000, 125000000, 125000000, 125000000], 10) );
-//         assert_eq!( Subtensor::get_coldkey_balance( &prev_foundation_account ), 500000000);
-//         assert_eq!( Subtensor::get_coldkey_balance( &Subtensor::get_foundation_account() ), 500000000);
-//     });
-// }
+#[test]
+fn test_steps_with_foundation_distribution() {
+    new_test_ext().execute_with( || {
+        Subtensor::set_max_registratations_per_block( 100 );
+        let initial_stake:u64 = 1000000000;
+        for i in 0..4 {
+            register_ok_neuron(i as u64, i as u64 );
+        }
+        let weights_matrix: Vec<Vec<u32>> = vec! [
+            vec! [0, u32::max_value(), 0, 0 ],
+            vec! [0, 0, u32::max_value(), 0 ],
+            vec! [0, 0, 0, u32::max_value() ], 
+            vec! [u32::max_value(), 0, 0, 0 ],
+        ];
+        Subtensor::set_weights_from_matrix( weights_matrix.clone() );
+        Subtensor::set_stake_from_vector( vec![ initial_stake; 4 ] );
+        Subtensor::set_foundation_account( 10 );
+        step_block (1);
+        assert!( approx_equals( Subtensor::get_total_issuance(), Subtensor::get_initial_total_issuance() + 1000000000, 10)); // approx
+        assert!( vec_approx_equals ( &Subtensor::get_stake(), &vec![1250000000, 1250000000, 1250000000, 1250000000], 10) );
+        assert!( vec_approx_equals ( &Subtensor::get_emission(), &vec![250000000, 250000000, 250000000, 250000000], 10) );
+        assert_eq!( Subtensor::get_coldkey_balance( &Subtensor::get_foundation_account() ), 0);
+
+        Subtensor::set_foundation_distribution( 50 );
+        step_block (1);
+        assert!( approx_equals( Subtensor::get_total_issuance(), Subtensor::get_initial_total_issuance() + 2000000000, 10)); // approx
+        assert!( vec_approx_equals ( &Subtensor::get_stake(), &vec![1375000000, 1375000000, 1375000000, 1375000000], 10) );
+        assert!( vec_approx_equals ( &Subtensor::get_emission(), &vec![125000000, 125000000, 125000000, 125000000], 10) );
+        assert_eq!( Subtensor::get_coldkey_balance( &Subtensor::get_foundation_account() ), 500000000);
+
+        Subtensor::set_foundation_distribution( 0 );
+        step_block (1);
+        assert!( approx_equals( Subtensor::get_total_issuance(), Subtensor::get_initial_total_issuance() + 3000000000, 10)); // approx
+        assert!( vec_approx_equals ( &Subtensor::get_stake(), &vec![1625000000, 1625000000, 1625000000, 1625000000], 10) );
+        assert!( vec_approx_equals ( &Subtensor::get_emission(), &vec![250000000, 250000000, 250000000, 250000000], 10) );
+        assert_eq!( Subtensor::get_coldkey_balance( &Subtensor::get_foundation_account() ), 500000000);
+
+        // The whole emission goes to the foundation.
+        Subtensor::set_foundation_distribution( 100 );
+        step_block (1);
+        assert!( approx_equals( Subtensor::get_total_issuance(), Subtensor::get_initial_total_issuance() + 4000000000, 10)); // approx
+        assert!( vec_approx_equals ( &Subtensor::get_stake(), &vec![1625000000, 1625000000, 1625000000, 1625000000], 10) );
+        assert_eq!( Subtensor::get_emission(), vec![0, 0, 0, 0] );
+        assert_eq!( Subtensor::get_coldkey_balance( &Subtensor::get_foundation_account() ), 1500000000);
+
+        // Test set foundation account.
+        Subtensor::set_foundation_distribution( 50 );
+        let prev_foundation_account: u64 = Subtensor::get_foundation_account();
+        Subtensor::set_foundation_account( 1 ); 
+        assert_eq!( Subtensor::get_foundation_account(), 1 );
+        step_block (1);
+        assert!( approx_equals( Subtensor::get_total_issuance(), Subtensor::get_initial_total_issuance() + 5000000000, 10)); // approx
+        assert!( vec_approx_equals ( &Subtensor::get_stake(), &vec![1750000000, 1750000000, 1750000000, 1750000000], 10) );
+        assert!( vec_approx_equals ( &Subtensor::get_emission(), &vec![125000000, 125000000, 125000000, 125000000], 10) );
+        assert_eq!( Subtensor::get_coldkey_balance( &prev_foundation_account ), 1500000000);
+        assert_eq!( Subtensor::get_coldkey_balance( &Subtensor::get_foundation_account() ), 500000000);
+
+        // Only the neuron emission is staked.
+        assert!( approx_equals( Subtensor::get_total_stake(), 4 * 1750000000, 10 ) );
+    });
+}
+
+// Tests that nothing is paid out while the foundation account is unset.
+#[test]
+fn test_foundation_distribution_without_foundation_account() {
+    new_test_ext().execute_with( || {
+        Subtensor::set_max_registratations_per_block( 100 );
+        for i in 0..4 {
+            register_ok_neuron(i as u64, i as u64 );
+        }
+        Subtensor::set_weights_from_matrix( vec! [
+            vec! [0, u32::max_value(), 0, 0 ],
+            vec! [0, 0, u32::max_value(), 0 ],
+            vec! [0, 0, 0, u32::max_value() ], 
+            vec! [u32::max_value(), 0, 0, 0 ],
+        ]);
+        Subtensor::set_stake_from_vector( vec![ 1000000000; 4 ] );
+        Subtensor::set_foundation_distribution( 50 );
+        assert_eq!( Subtensor::get_foundation_emission( 1000000000 ), 0 );
+        step_block (1);
+        assert!( approx_equals( Subtensor::get_total_issuance(), Subtensor::get_initial_total_issuance() + 1000000000, 10)); // approx
+        assert!( vec_approx_equals ( &Subtensor::get_emission(), &vec![250000000, 250000000, 250000000, 250000000], 10) );
+        assert_eq!( Subtensor::get_coldkey_balance( &Subtensor::get_foundation_account() ), 0);
+    });
+}
+
+// Tests that a step split across blocks pays the foundation once, when it starts.
+#[test]
+fn test_chunked_step_with_foundation_distribution() {
+    new_test_ext().execute_with( || {
+        setup_chunked_step_graph();
+        Subtensor::set_step_chunk_size( 4 );
+        Subtensor::set_foundation_account( 10 );
+        Subtensor::set_foundation_distribution( 20 );
+        let initial_issuance: u64 = Subtensor::get_total_issuance();
+        step_block( 1 );
+        assert_eq!( Subtensor::get_coldkey_balance( &10 ), 200000000 );
+        step_block( 4 );
+        assert!( !Subtensor::has_pending_step() );
+        assert_eq!( Subtensor::get_coldkey_balance( &10 ), 200000000 );
+        assert!( approx_equals( Subtensor::get_emission().iter().sum(), 800000000, 10 ) );
+        assert!( approx_equals( Subtensor::get_total_issuance() - initial_issuance, 1000000000, 10 ) );
+    });
+}
+
+// Tests that emission is paid into the reward destination of each neuron.
+#[test]
+fn test_steps_with_reward_destination() {
+    new_test_ext().execute_with( || {
+        Subtensor::set_max_registratations_per_block( 100 );
+        for i in 0..4 {
+            register_ok_neuron(i as u64, i as u64 );
+        }
+        Subtensor::set_weights_from_matrix( vec! [
+            vec! [0, u32::max_value(), 0, 0 ],
+            vec! [0, 0, u32::max_value(), 0 ],
+            vec! [0, 0, 0, u32::max_value() ], 
+            vec! [u32::max_value(), 0, 0, 0 ],
+        ]);
+        Subtensor::set_stake_from_vector( vec![ 1000000000; 4 ] );
+        assert_ok!( Subtensor::set_reward_destination( <<Test as Config>::Origin>::signed(1), 1, RewardDestination::Coldkey ) );
+        assert_ok!( Subtensor::set_reward_destination( <<Test as Config>::Origin>::signed(2), 2, RewardDestination::Account(20) ) );
+        step_block (1);
+        assert!( vec_approx_equals ( &Subtensor::get_emission(), &vec![250000000, 250000000, 250000000, 250000000], 10) );
+        assert!( vec_approx_equals ( &Subtensor::get_stake(), &vec![1250000000, 1000000000, 1000000000, 1250000000], 10) );
+        assert!( approx_equals( Subtensor::get_coldkey_balance( &1 ) as u64, 250000000, 10 ) );
+        assert!( approx_equals( Subtensor::get_coldkey_balance( &20 ) as u64, 250000000, 10 ) );
+
+        // The issuance counts the whole emission, the stake only what was restaked.
+        assert!( approx_equals( Subtensor::get_total_issuance(), Subtensor::get_initial_total_issuance() + 1000000000, 10)); // approx
+        assert_eq!( Subtensor::get_total_stake(), Subtensor::get_stake().iter().sum::<u64>() );
+
+        // Back to restaking.
+        assert_ok!( Subtensor::set_reward_destination( <<Test as Config>::Origin>::signed(1), 1, RewardDestination::Stake ) );
+        let balance_20 = Subtensor::get_coldkey_balance( &20 );
+        step_block (1);
+        assert!( Subtensor::get_stake_of_neuron_hotkey_account_by_uid( 1 ) > 1000000000 );
+        assert_eq!( Subtensor::get_stake_of_neuron_hotkey_account_by_uid( 2 ), 1000000000 );
+        assert!( Subtensor::get_coldkey_balance( &20 ) > balance_20 );
+        assert_eq!( Subtensor::get_total_stake(), Subtensor::get_stake().iter().sum::<u64>() );
+    });
+}
+
+// Tests that a step split across blocks pays into the reward destinations as well.
+#[test]
+fn test_chunked_step_with_reward_destination() {
+    new_test_ext().execute_with( || {
+        setup_chunked_step_graph();
+        Subtensor::set_step_chunk_size( 4 );
+        assert_ok!( Subtensor::set_reward_destination( <<Test as Config>::Origin>::signed(0), 0, RewardDestination::Coldkey ) );
+        let initial_stake: u64 = Subtensor::get_stake_of_neuron_hotkey_account_by_uid( 0 );
+        let initial_issuance: u64 = Subtensor::get_total_issuance();
+        step_block( 5 );
+        assert!( !Subtensor::has_pending_step() );
+        let neuron = Subtensor::get_neuron_for_uid( 0 );
+        assert!( neuron.emission > 0 );
+        assert_eq!( neuron.stake, initial_stake );
+        assert_eq!( Subtensor::get_coldkey_balance( &0 ) as u64, neuron.emission );
+        assert!( approx_equals( Subtensor::get_total_issuance() - initial_issuance, Subtensor::get_block_emission(), 10 ) );
+        assert_eq!( Subtensor::get_total_stake(), Subtensor::get_stake().iter().sum::<u64>() );
+    });
+}
 
 
 #[test]
@@ -630,3 +748,286 @@ fn test_two_steps_with_partial_activity() {
     });
 }
 
+
+// Tests that the simulated step writes nothing and matches the applied step.
+#[test]
+fn test_compute_mechanism_step_matches_step() {
+    new_test_ext().execute_with( || {
+        Subtensor::set_max_registratations_per_block( 100 );
+        let initial_stake:u64 = 1000000000;
+        for i in 0..4 {
+            register_ok_neuron(i as u64, i as u64 );
+        }
+        Subtensor::set_stake_from_vector( vec![ initial_stake; 4 ] );
+        let weights_matrix: Vec<Vec<u32>> = vec! [
+            vec! [0, u32::max_value(), 0, 0 ],
+            vec! [0, 0, u32::max_value(), 0 ],
+            vec! [0, 0, 0, u32::max_value() ], 
+            vec! [u32::max_value(), 0, 0, 0 ],
+        ];
+        Subtensor::set_weights_from_matrix( weights_matrix );
+        System::set_block_number( 1 );
+
+        // Simulating does not write anything.
+        let step = Subtensor::compute_mechanism_step( Subtensor::get_block_emission() );
+        assert_eq!( Subtensor::get_stake(), vec![ initial_stake; 4 ] );
+        assert_eq!( Subtensor::get_ranks(), vec![0; 4] );
+        assert_eq!( Subtensor::get_emission(), vec![0; 4] );
+        assert_eq!( Subtensor::get_bonds(), vec![ [ 0; 4]; 4]);
+        assert_eq!( Subtensor::get_total_issuance(), Subtensor::get_initial_total_issuance() );
+        assert_eq!( Subtensor::get_last_mechanism_step_block(), 0 );
+        assert!( vec_approx_equals ( &step.emission, &vec![250000000, 250000000, 250000000, 250000000], 10) );
+        assert_eq!( step.block, 1 );
+        assert_eq!( step.pruned_uids, Vec::<u32>::new() );
+
+        // The applied step writes exactly the simulated values.
+        Subtensor::mechanism_step( Subtensor::get_block_emission() );
+        assert_eq!( Subtensor::get_stake(), step.stake );
+        assert_eq!( Subtensor::get_ranks(), step.ranks );
+        assert_eq!( Subtensor::get_trust(), step.trust );
+        assert_eq!( Subtensor::get_consensus(), step.consensus );
+        assert_eq!( Subtensor::get_incentive(), step.incentive );
+        assert_eq!( Subtensor::get_dividends(), step.dividends );
+        assert_eq!( Subtensor::get_emission(), step.emission );
+        assert_eq!( Subtensor::get_active(), step.active );
+        assert_eq!( Subtensor::get_total_stake(), initial_stake * 4 + step.total_emission );
+        for uid in 0..4 {
+            assert_eq!( Subtensor::bonds( uid ), step.bonds[ uid as usize ] );
+            assert_eq!( Subtensor::get_neuron_for_uid( uid ).priority, step.priority[ uid as usize ] );
+            // Bonds start at zero so every delta is the new bond.
+            assert_eq!( step.bond_deltas[ uid as usize ], vec![ ( (uid + 1) % 4, step.bonds[ uid as usize ][0].1 as i128 ) ] );
+        }
+
+        // The next step moves each bond half way to 250,000,000.
+        System::set_block_number( 2 );
+        let step = Subtensor::compute_mechanism_step( Subtensor::get_block_emission() );
+        for uid in 0..4 {
+            let ( uid_j, delta ) = step.bond_deltas[ uid as usize ][0];
+            assert_eq!( uid_j, (uid + 1) % 4 );
+            assert!( approx_equals( delta as u64, 62_500_000, 10 ) );
+        }
+    });
+}
+
+// Tests that the simulated stake follows the reward destinations and nominator payouts.
+#[test]
+fn test_compute_mechanism_step_stake_follows_payouts() {
+    new_test_ext().execute_with( || {
+        Subtensor::set_max_registratations_per_block( 100 );
+        for i in 0..4 {
+            register_ok_neuron(i as u64, i as u64 );
+        }
+        Subtensor::set_stake_from_vector( vec![ 1000000000; 4 ] );
+        Subtensor::set_weights_from_matrix( vec! [
+            vec! [0, u32::max_value(), 0, 0 ],
+            vec! [0, 0, u32::max_value(), 0 ],
+            vec! [0, 0, 0, u32::max_value() ], 
+            vec! [u32::max_value(), 0, 0, 0 ],
+        ]);
+        assert_ok!( Subtensor::set_reward_destination( <<Test as Config>::Origin>::signed(1), 1, RewardDestination::Coldkey ) );
+        assert_ok!( Subtensor::set_reward_destination( <<Test as Config>::Origin>::signed(2), 2, RewardDestination::Account(20) ) );
+        assert_ok!( Subtensor::set_delegate_take( <<Test as Config>::Origin>::signed(3), 3, 20 ) );
+        Subtensor::add_balance_to_coldkey_account( &30, 1000000000 );
+        assert_ok!( Subtensor::add_stake( <<Test as Config>::Origin>::signed(30), 3, 500000000 ) );
+        System::set_block_number( 1 );
+
+        // The rewards paid into accounts are not counted into the stake.
+        let step = Subtensor::compute_mechanism_step( Subtensor::get_block_emission() );
+        assert!( step.emission[1] > 0 && step.emission[2] > 0 && step.emission[3] > 0 );
+        assert_eq!( step.stake[1], 1000000000 );
+        assert_eq!( step.stake[2], 1000000000 );
+        assert_eq!( Subtensor::get_nominator_stake( &3, &30 ), 500000000 );
+
+        Subtensor::mechanism_step( Subtensor::get_block_emission() );
+        assert_eq!( Subtensor::get_stake(), step.stake );
+        assert!( Subtensor::get_nominator_stake( &3, &30 ) > 500000000 );
+        assert_eq!( Subtensor::get_total_stake(), step.stake.iter().sum::<u64>() );
+    });
+}
+
+// Tests that on_initialize only accounts for the mechanism step on step blocks and
+// that the step weight follows the number of non-zero weights and bonds.
+#[test]
+fn test_on_initialize_weight() {
+	new_test_ext().execute_with(|| {
+        Subtensor::set_max_registratations_per_block( 100 );
+        for i in 0..4 {
+            register_ok_neuron(i as u64, i as u64 );
+        }
+        Subtensor::set_stake_from_vector( vec![ 1_000_000_000; 4 ] );
+        Subtensor::set_weights_from_matrix( vec! [
+            vec! [0, u32::max_value(), 0, 0 ],
+            vec! [0, 0, u32::max_value(), 0 ],
+            vec! [0, 0, 0, u32::max_value() ], 
+            vec! [u32::max_value(), 0, 0, 0 ],
+        ]);
+        Subtensor::set_blocks_per_step( 2 );
+        Subtensor::set_blocks_since_last_step( 0 );
+
+        // No step on the first block.
+        System::set_block_number( 1 );
+        let weight_without_step = Subtensor::on_initialize( 1 );
+        assert_eq!( Subtensor::get_blocks_since_last_step(), 1 );
+
+        // The step reads 4 weights and no bonds yet.
+        System::set_block_number( 2 );
+        let weight_with_step = Subtensor::on_initialize( 2 );
+        assert_eq!( Subtensor::get_blocks_since_last_step(), 0 );
+        assert_eq!( weight_with_step, weight_without_step + <() as WeightInfo>::mechanism_step( 4, 4, 0 ) );
+
+        // The next step also reads the 4 bonds bought by the previous one.
+        System::set_block_number( 3 );
+        Subtensor::on_initialize( 3 );
+        System::set_block_number( 4 );
+        let weight_with_step = Subtensor::on_initialize( 4 );
+        assert_eq!( weight_with_step, weight_without_step + <() as WeightInfo>::mechanism_step( 4, 4, 4 ) );
+	});
+}
+
+// Registers 5 neurons with uneven stake and weights.
+#[allow(dead_code)]
+fn setup_chunked_step_graph() {
+    Subtensor::set_max_registratations_per_block( 100 );
+    for i in 0..5 {
+        register_ok_neuron( i as u64, i as u64 );
+    }
+    Subtensor::set_stake_from_vector( vec![ 1_000_000_000, 2_000_000_000, 500_000_000, 0, 3_000_000_000 ] );
+    Subtensor::set_weights_from_matrix( vec! [
+        vec! [0, u32::max_value() / 2, u32::max_value() / 2, 0, 0 ],
+        vec! [0, 0, u32::max_value(), 0, 0 ],
+        vec! [u32::max_value() / 4, 0, 0, u32::max_value() / 4 * 3, 0 ],
+        vec! [0, u32::max_value(), 0, 0, 0 ],
+        vec! [u32::max_value() / 3, u32::max_value() / 3, 0, 0, u32::max_value() / 3 ],
+    ]);
+}
+
+// Returns the values a step writes.
+#[allow(dead_code)]
+fn step_state() -> ( Vec<Vec<u64>>, Vec<Vec<(u32,u64)>>, Vec<u64>, u64, u64 ) {
+    let columns = vec![
+        Subtensor::get_stake(),
+        Subtensor::get_ranks(),
+        Subtensor::get_trust(),
+        Subtensor::get_consensus(),
+        Subtensor::get_incentive(),
+        Subtensor::get_dividends(),
+        Subtensor::get_emission(),
+    ];
+    let bonds = (0..5).map( |uid| Subtensor::bonds( uid ) ).collect();
+    let priority = (0..5).map( |uid| Subtensor::get_neuron_for_uid( uid ).priority ).collect();
+    ( columns, bonds, priority, Subtensor::get_total_issuance(), Subtensor::get_total_stake() )
+}
+
+// Tests that a step split across blocks writes exactly what the single block step writes.
+#[test]
+fn test_chunked_step_matches_single_block_step() {
+    let mut expected = vec![];
+    new_test_ext().execute_with( || {
+        setup_chunked_step_graph();
+        for _ in 0..3 {
+            step_block( 1 );
+            expected.push( step_state() );
+        }
+    });
+    new_test_ext().execute_with( || {
+        setup_chunked_step_graph();
+        Subtensor::set_step_chunk_size( 2 );
+        for i in 0..3 {
+            // 5 uids walked 4 times, 2 at a time, take 10 blocks.
+            step_block( 1 );
+            for _ in 0..9 {
+                assert!( Subtensor::has_pending_step() );
+                step_block( 1 );
+            }
+            assert!( !Subtensor::has_pending_step() );
+            assert_eq!( step_state(), expected[ i ] );
+            // Pay out a single block with the next step, as the single block step does.
+            Subtensor::set_blocks_since_last_step( 0 );
+        }
+    });
+}
+
+// Tests that a block runs no more uids of the step than fit in the max step weight.
+#[test]
+fn test_max_step_rows_fits_max_step_weight() {
+    new_test_ext().execute_with( || {
+        let n: u32 = 100;
+        let max_weight: Weight = <() as WeightInfo>::mechanism_step( 30, 30 * n, 30 * n );
+        assert_eq!( Subtensor::max_step_rows( n, max_weight ), 30 );
+        assert_eq!( Subtensor::max_step_rows( n, max_weight - 1 ), 29 );
+        assert_eq!( Subtensor::max_step_rows( n, Weight::MAX ), n );
+        // A step always makes progress, one uid at a time.
+        assert_eq!( Subtensor::max_step_rows( n, 0 ), 1 );
+        assert_eq!( Subtensor::max_step_rows( 0, 0 ), 1 );
+
+        // The mock lets any step fit, the step chunk size is used as is.
+        assert_eq!( Subtensor::get_step_rows_per_block( n ), 0 );
+        Subtensor::set_step_chunk_size( 2 );
+        assert_eq!( Subtensor::get_step_rows_per_block( n ), 2 );
+    });
+}
+
+// Tests that the blocks spent on a chunked step are paid out by the next step.
+#[test]
+fn test_chunked_step_keeps_total_emission() {
+    new_test_ext().execute_with( || {
+        setup_chunked_step_graph();
+        Subtensor::set_step_chunk_size( 4 );
+        let initial_issuance: u64 = Subtensor::get_total_issuance();
+
+        // The first step starts on block 1 and runs until block 5.
+        step_block( 5 );
+        assert!( !Subtensor::has_pending_step() );
+        assert_eq!( Subtensor::get_last_mechanism_step_block(), 1 );
+        assert!( approx_equals( Subtensor::get_total_issuance() - initial_issuance, Subtensor::get_block_emission(), 10 ) );
+        assert_eq!( Subtensor::get_blocks_since_last_step(), 4 );
+
+        // The second step starts on block 6 and pays out blocks 2 to 6.
+        step_block( 5 );
+        assert!( !Subtensor::has_pending_step() );
+        assert_eq!( Subtensor::get_last_mechanism_step_block(), 6 );
+        assert!( approx_equals( Subtensor::get_total_issuance() - initial_issuance, 6 * Subtensor::get_block_emission(), 10 ) );
+        assert_eq!( Subtensor::get_total_stake(), 6_500_000_000 + Subtensor::get_total_issuance() - initial_issuance );
+    });
+}
+
+// Tests that a registration pruning a uid waits for a running step, so the step pays out
+// the same total emission as the single block step followed by the registration.
+#[test]
+fn test_chunked_step_defers_pruning_registrations() {
+    let mut expected_total_emission: u64 = 0;
+    let mut expected_state = vec![];
+    new_test_ext().execute_with( || {
+        setup_chunked_step_graph();
+        Subtensor::set_max_allowed_uids( 5 );
+        Subtensor::set_immunity_period( 0 );
+        step_block( 1 );
+        expected_total_emission = pallet_subtensor::TotalEmission::<Test>::get();
+        expected_state.push( step_state() );
+        register_ok_neuron( 5, 5 );
+    });
+    new_test_ext().execute_with( || {
+        setup_chunked_step_graph();
+        Subtensor::set_step_chunk_size( 1 );
+        Subtensor::set_max_allowed_uids( 5 );
+        Subtensor::set_immunity_period( 0 );
+
+        // One uid per block, the step is in its Dividends phase on block 11.
+        step_block( 11 );
+        assert!( Subtensor::has_pending_step() );
+        let block_number: u64 = Subtensor::get_current_block_as_u64();
+        let (nonce, work): (u64, Vec<u8>) = Subtensor::create_work_for_block_number( block_number );
+        let result = Subtensor::register( <<Test as Config>::Origin>::signed(5), block_number, nonce, work, 5, 5 );
+        assert_eq!( result, Err(Error::<Test>::StepInProgress.into()) );
+        assert_eq!( Subtensor::get_neuron_count(), 5 );
+
+        while Subtensor::has_pending_step() {
+            step_block( 1 );
+        }
+        assert_eq!( pallet_subtensor::TotalEmission::<Test>::get(), expected_total_emission );
+        assert_eq!( step_state(), expected_state[ 0 ] );
+
+        // Once the step is done the registration goes through.
+        register_ok_neuron( 5, 5 );
+    });
+}
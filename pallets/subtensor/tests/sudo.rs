@@ -5,6 +5,7 @@ use frame_system::Config;
 mod mock;
 use mock::*;
 use frame_support::sp_runtime::DispatchError;
+use pallet_subtensor::CallType;
 
 
 #[test]
@@ -34,6 +35,71 @@ fn test_sudo_set_blocks_per_step() {
     });
 }
 
+#[test]
+fn test_sudo_set_step_chunk_size() {
+	new_test_ext().execute_with(|| {
+        let step_chunk_size: u64 = 100;
+		assert_ok!(Subtensor::sudo_set_step_chunk_size(<<Test as Config>::Origin>::root(), step_chunk_size));
+        assert_eq!(Subtensor::get_step_chunk_size(), step_chunk_size);
+    });
+}
+
+#[test]
+fn test_sudo_set_transaction_byte_fee() {
+	new_test_ext().execute_with(|| {
+        let transaction_byte_fee: u64 = 100;
+		assert_ok!(Subtensor::sudo_set_transaction_byte_fee(<<Test as Config>::Origin>::root(), CallType::Serve, transaction_byte_fee));
+        assert_eq!(Subtensor::get_transaction_byte_fee(&CallType::Serve), transaction_byte_fee);
+        assert_eq!(Subtensor::get_transaction_byte_fee(&CallType::SetWeights), 0);
+    });
+}
+
+#[test]
+fn test_sudo_set_tx_rate_limit() {
+	new_test_ext().execute_with(|| {
+        let tx_rate_limit: u64 = 10;
+		assert_ok!(Subtensor::sudo_set_tx_rate_limit(<<Test as Config>::Origin>::root(), CallType::SetWeights, tx_rate_limit));
+        assert_eq!(Subtensor::get_tx_rate_limit(&CallType::SetWeights), tx_rate_limit);
+        assert_eq!(Subtensor::get_tx_rate_limit(&CallType::Serve), 0);
+    });
+}
+
+#[test]
+fn test_sudo_set_feeless_quota() {
+	new_test_ext().execute_with(|| {
+        let feeless_quota: u64 = 10;
+		assert_ok!(Subtensor::sudo_set_feeless_quota(<<Test as Config>::Origin>::root(), feeless_quota));
+        assert_eq!(Subtensor::get_feeless_quota(), feeless_quota);
+    });
+}
+
+#[test]
+fn test_sudo_set_feeless_quota_interval() {
+	new_test_ext().execute_with(|| {
+        let feeless_quota_interval: u64 = 10;
+		assert_ok!(Subtensor::sudo_set_feeless_quota_interval(<<Test as Config>::Origin>::root(), feeless_quota_interval));
+        assert_eq!(Subtensor::get_feeless_quota_interval(), feeless_quota_interval);
+    });
+}
+
+#[test]
+fn test_sudo_set_unbonding_period() {
+	new_test_ext().execute_with(|| {
+        let unbonding_period: u64 = 100;
+		assert_ok!(Subtensor::sudo_set_unbonding_period(<<Test as Config>::Origin>::root(), unbonding_period));
+        assert_eq!(Subtensor::get_unbonding_period(), unbonding_period);
+    });
+}
+
+#[test]
+fn test_sudo_set_coldkey_swap_delay() {
+	new_test_ext().execute_with(|| {
+        let coldkey_swap_delay: u64 = 100;
+		assert_ok!(Subtensor::sudo_set_coldkey_swap_delay(<<Test as Config>::Origin>::root(), coldkey_swap_delay));
+        assert_eq!(Subtensor::get_coldkey_swap_delay(), coldkey_swap_delay);
+    });
+}
+
 #[test]
 fn test_sudo_set_bonds_moving_average() {
 	new_test_ext().execute_with(|| {
@@ -223,6 +289,78 @@ fn test_fails_sudo_set_blocks_per_step() {
 }
 
 
+#[test]
+fn test_fails_sudo_set_step_chunk_size() {
+	new_test_ext().execute_with(|| {
+        let step_chunk_size: u64 = 100;
+        let init_step_chunk_size: u64 = Subtensor::get_step_chunk_size();
+		assert_eq!(Subtensor::sudo_set_step_chunk_size(<<Test as Config>::Origin>::signed(0), step_chunk_size), Err(DispatchError::BadOrigin.into()));
+        assert_eq!(Subtensor::get_step_chunk_size(), init_step_chunk_size);
+    });
+}
+
+
+#[test]
+fn test_fails_sudo_set_transaction_byte_fee() {
+	new_test_ext().execute_with(|| {
+        let transaction_byte_fee: u64 = 100;
+        let init_transaction_byte_fee: u64 = Subtensor::get_transaction_byte_fee(&CallType::Serve);
+		assert_eq!(Subtensor::sudo_set_transaction_byte_fee(<<Test as Config>::Origin>::signed(0), CallType::Serve, transaction_byte_fee), Err(DispatchError::BadOrigin.into()));
+        assert_eq!(Subtensor::get_transaction_byte_fee(&CallType::Serve), init_transaction_byte_fee);
+    });
+}
+
+#[test]
+fn test_fails_sudo_set_tx_rate_limit() {
+	new_test_ext().execute_with(|| {
+        let tx_rate_limit: u64 = 10;
+        let init_tx_rate_limit: u64 = Subtensor::get_tx_rate_limit(&CallType::SetWeights);
+		assert_eq!(Subtensor::sudo_set_tx_rate_limit(<<Test as Config>::Origin>::signed(0), CallType::SetWeights, tx_rate_limit), Err(DispatchError::BadOrigin.into()));
+        assert_eq!(Subtensor::get_tx_rate_limit(&CallType::SetWeights), init_tx_rate_limit);
+    });
+}
+
+#[test]
+fn test_fails_sudo_set_feeless_quota() {
+	new_test_ext().execute_with(|| {
+        let feeless_quota: u64 = 10;
+        let init_feeless_quota: u64 = Subtensor::get_feeless_quota();
+		assert_eq!(Subtensor::sudo_set_feeless_quota(<<Test as Config>::Origin>::signed(0), feeless_quota), Err(DispatchError::BadOrigin.into()));
+        assert_eq!(Subtensor::get_feeless_quota(), init_feeless_quota);
+    });
+}
+
+#[test]
+fn test_fails_sudo_set_feeless_quota_interval() {
+	new_test_ext().execute_with(|| {
+        let feeless_quota_interval: u64 = 10;
+        let init_feeless_quota_interval: u64 = Subtensor::get_feeless_quota_interval();
+		assert_eq!(Subtensor::sudo_set_feeless_quota_interval(<<Test as Config>::Origin>::signed(0), feeless_quota_interval), Err(DispatchError::BadOrigin.into()));
+        assert_eq!(Subtensor::get_feeless_quota_interval(), init_feeless_quota_interval);
+    });
+}
+
+#[test]
+fn test_fails_sudo_set_unbonding_period() {
+	new_test_ext().execute_with(|| {
+        let unbonding_period: u64 = 100;
+        let init_unbonding_period: u64 = Subtensor::get_unbonding_period();
+		assert_eq!(Subtensor::sudo_set_unbonding_period(<<Test as Config>::Origin>::signed(0), unbonding_period), Err(DispatchError::BadOrigin.into()));
+        assert_eq!(Subtensor::get_unbonding_period(), init_unbonding_period);
+    });
+}
+
+#[test]
+fn test_fails_sudo_set_coldkey_swap_delay() {
+	new_test_ext().execute_with(|| {
+        let coldkey_swap_delay: u64 = 100;
+        let init_coldkey_swap_delay: u64 = Subtensor::get_coldkey_swap_delay();
+		assert_eq!(Subtensor::sudo_set_coldkey_swap_delay(<<Test as Config>::Origin>::signed(0), coldkey_swap_delay), Err(DispatchError::BadOrigin.into()));
+        assert_eq!(Subtensor::get_coldkey_swap_delay(), init_coldkey_swap_delay);
+    });
+}
+
+
 #[test]
 fn test_fails_sudo_set_bonds_moving_average() {
 	new_test_ext().execute_with(|| {
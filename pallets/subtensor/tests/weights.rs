@@ -1,6 +1,6 @@
 mod mock;
 use mock::*;
-use pallet_subtensor::{Error};
+use pallet_subtensor::{Error, WeightInfo, CallType};
 use frame_support::weights::{GetDispatchInfo, DispatchInfo, DispatchClass, Pays};
 use frame_support::{assert_ok};
 use sp_runtime::DispatchError;
@@ -20,7 +20,7 @@ fn test_set_weights_dispatch_info_ok() {
 		let call = Call::Subtensor(SubtensorCall::set_weights(w_uids, w_vals));
 
 		assert_eq!(call.get_dispatch_info(), DispatchInfo {
-			weight: 0,
+			weight: <() as WeightInfo>::set_weights( 2 ),
 			class: DispatchClass::Normal,
 			pays_fee: Pays::No
 		});
@@ -218,9 +218,23 @@ fn test_set_weight_max_min_ratio_to_high() {
 	});
 }
 
+#[test]
+fn test_set_weights_rate_limit() {
+	new_test_ext().execute_with(|| {
+		let hotkey: u64 = 666;
+		let neuron = register_ok_neuron( hotkey, 77 );
+		Subtensor::set_tx_rate_limit( CallType::SetWeights, 10 );
 
+		assert_ok!(Subtensor::set_weights(Origin::signed(hotkey), vec![neuron.uid], vec![u32::MAX]));
+		let result = Subtensor::set_weights(Origin::signed(hotkey), vec![neuron.uid], vec![u32::MAX]);
+		assert_eq!(result, Err(Error::<Test>::TxRateLimitExceeded.into()));
 
+		// Still limited one block before the limit has passed.
+		step_block(9);
+		let result = Subtensor::set_weights(Origin::signed(hotkey), vec![neuron.uid], vec![u32::MAX]);
+		assert_eq!(result, Err(Error::<Test>::TxRateLimitExceeded.into()));
 
-
-
-
+		step_block(1);
+		assert_ok!(Subtensor::set_weights(Origin::signed(hotkey), vec![neuron.uid], vec![u32::MAX]));
+	});
+}
@@ -33,7 +33,7 @@ pub use frame_support::{
 	construct_runtime, parameter_types, StorageValue,
 	traits::{KeyOwnerProofSystem, Randomness},
 	weights::{
-		Weight, IdentityFee,
+		Weight, IdentityFee, DispatchClass,
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
 	},
 };
@@ -96,7 +96,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 114,
+	spec_version: 132,
 	impl_version: 6,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 6,
@@ -134,12 +134,38 @@ pub fn native_version() -> NativeVersion {
 const NORMAL_DISPATCH_RATIO_WEIGHT: Perbill = Perbill::from_percent( 75 ); 
 const NORMAL_DISPATCH_RATIO_LENGTH: Perbill = Perbill::from_percent( 2 ); 
 
+/// 25% of the block weight is reserved for on_initialize, which runs the mechanism step.
+/// avg_block_initialization only sets an average, the share is enforced by the pallet instead:
+/// it is passed as MaxStepWeight and a step whose worst case weight exceeds it runs in chunks
+/// that fit. Normal extrinsics are capped at the remaining 75%, so they never eat into it.
+const AVERAGE_ON_INITIALIZE_RATIO: Perbill = Perbill::from_percent( 25 );
+
+/// We allow for 5 seconds of compute with a 12 second average block time.
+const MAXIMUM_BLOCK_WEIGHT: Weight = 5 * WEIGHT_PER_SECOND;
+
 parameter_types! {
 	pub const Version: RuntimeVersion = VERSION;
 	pub const BlockHashCount: BlockNumber = 2400;
-	/// We allow for 2 seconds of compute with a 6 second average block time.
-	pub BlockWeights: frame_system::limits::BlockWeights = frame_system::limits::BlockWeights
-		::with_sensible_defaults(5 * WEIGHT_PER_SECOND, NORMAL_DISPATCH_RATIO_WEIGHT);
+	/// Normal extrinsics may use NORMAL_DISPATCH_RATIO_WEIGHT of the block, the remainder is left
+	/// for the mechanism step in on_initialize and for operational calls.
+	pub BlockWeights: frame_system::limits::BlockWeights = frame_system::limits::BlockWeights::builder()
+		.base_block( BlockExecutionWeight::get() )
+		.for_class( DispatchClass::all(), |weights| {
+			weights.base_extrinsic = ExtrinsicBaseWeight::get();
+		})
+		.for_class( DispatchClass::Normal, |weights| {
+			weights.max_total = Some( NORMAL_DISPATCH_RATIO_WEIGHT * MAXIMUM_BLOCK_WEIGHT );
+		})
+		.for_class( DispatchClass::Operational, |weights| {
+			weights.max_total = Some( MAXIMUM_BLOCK_WEIGHT );
+			// Operational transactions may use the space left by normal ones.
+			weights.reserved = Some( MAXIMUM_BLOCK_WEIGHT - NORMAL_DISPATCH_RATIO_WEIGHT * MAXIMUM_BLOCK_WEIGHT );
+		})
+		.avg_block_initialization( AVERAGE_ON_INITIALIZE_RATIO )
+		.build_or_panic();
+
+	/// The share of the block reserved for on_initialize, the mechanism step is chunked to fit in it.
+	pub MaxStepWeight: Weight = AVERAGE_ON_INITIALIZE_RATIO * MAXIMUM_BLOCK_WEIGHT;
 
 	/// With the dispatch ratio of 2% blocks can have max size 0.02 * 1024 * 1024 = 0.02Mb
 	/// An average validator sets weights every 100 blocks each of size ~= 500 * 8 = 0.004 Mb. 
@@ -285,6 +311,7 @@ parameter_types! {
 	pub const InitialValidatorEpochsPerReset: u64 = 10;
 	pub const InitialImmunityPeriod: u64 = 200;
 	pub const InitialBlocksPerStep: u64 = 100;
+	pub const InitialStepChunkSize: u64 = 0;
 	pub const InitialMaxAllowedUids: u64 = 2000;
 	pub const InitialMinAllowedWeights: u64 = 1;
 	pub const InitialMaxAllowedMaxMinRatio: u64 = 10;
@@ -300,12 +327,20 @@ parameter_types! {
 	pub const InitialAdjustmentInterval: u64 = 100;
 	pub const InitialMaxRegistrationsPerBlock: u64 = 2;
 	pub const InitialTargetRegistrationsPerInterval: u64 = 2;
+	pub const InitialColdkeySwapDelay: u64 = 7200;
+	pub const InitialUnbondingPeriod: u64 = 7200;
+	pub const MaxUnbondingChunks: u32 = 32;
+	pub const InitialTransactionByteFee: u64 = 100;
+	pub const InitialFeelessQuota: u64 = 10;
+	pub const InitialFeelessQuotaInterval: u64 = 100;
+	pub const InitialWeightsRateLimit: u64 = 100;
+	pub const InitialServingRateLimit: u64 = 10;
 }
 /// Configure the pallet-subtensor in pallets/subtensor.
 impl pallet_subtensor::Config for Runtime {
 	type Currency = Balances;
 	type Event = Event;
-	type TransactionByteFee = ();
+	type WeightInfo = pallet_subtensor::default_weights::SubstrateWeight<Runtime>;
 	type SDebug = SDebug;
 	type InitialRho = InitialRho;
 	type InitialKappa = InitialKappa;
@@ -323,6 +358,8 @@ impl pallet_subtensor::Config for Runtime {
 	type InitialIncentivePruningDenominator = InitialIncentivePruningDenominator;
 	type InitialFoundationDistribution = InitialFoundationDistribution;
 	type InitialBlocksPerStep = InitialBlocksPerStep;
+	type InitialStepChunkSize = InitialStepChunkSize;
+	type MaxStepWeight = MaxStepWeight;
 	type InitialIssuance = InitialIssuance;
 	type InitialDifficulty = InitialDifficulty;
 	type MinimumDifficulty = MinimumDifficulty;
@@ -331,6 +368,14 @@ impl pallet_subtensor::Config for Runtime {
 	type InitialAdjustmentInterval = InitialAdjustmentInterval;
 	type InitialMaxRegistrationsPerBlock = InitialMaxRegistrationsPerBlock;
 	type InitialTargetRegistrationsPerInterval = InitialTargetRegistrationsPerInterval;
+	type InitialColdkeySwapDelay = InitialColdkeySwapDelay;
+	type InitialUnbondingPeriod = InitialUnbondingPeriod;
+	type MaxUnbondingChunks = MaxUnbondingChunks;
+	type InitialTransactionByteFee = InitialTransactionByteFee;
+	type InitialFeelessQuota = InitialFeelessQuota;
+	type InitialFeelessQuotaInterval = InitialFeelessQuotaInterval;
+	type InitialWeightsRateLimit = InitialWeightsRateLimit;
+	type InitialServingRateLimit = InitialServingRateLimit;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -348,7 +393,7 @@ construct_runtime!(
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
 		TransactionPayment: pallet_transaction_payment::{Pallet, Storage},
 		Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>},
-		SubtensorModule: pallet_subtensor::{Pallet, Call, Storage, Event<T>}
+		SubtensorModule: pallet_subtensor::{Pallet, Call, Config<T>, Storage, Event<T>}
 	}
 );
 
@@ -380,6 +425,27 @@ pub type Executive = frame_executive::Executive<
 	AllPallets,
 >;
 
+sp_api::decl_runtime_apis! {
+	/// Read access to subtensor state for rpc and off-chain tooling.
+	pub trait SubtensorApi {
+		/// Returns every neuron field as columns for the uids in [start, end).
+		/// Unset bounds default to the full graph.
+		fn get_metagraph(start: Option<u32>, end: Option<u32>) -> pallet_subtensor::Metagraph<AccountId>;
+
+		/// Returns the neuron registered under this uid, if any.
+		fn get_neuron_by_uid(uid: u32) -> Option<pallet_subtensor::NeuronMetadata<AccountId>>;
+
+		/// Returns the neuron registered with this hotkey, if any.
+		fn get_neuron_by_hotkey(hotkey: AccountId) -> Option<pallet_subtensor::NeuronMetadata<AccountId>>;
+
+		/// Returns all neurons owned by this coldkey, ordered by uid.
+		fn get_neurons_by_coldkey(coldkey: AccountId) -> Vec<pallet_subtensor::NeuronMetadata<AccountId>>;
+
+		/// Runs the mechanism step for this emission against the current state without writing it.
+		fn simulate_mechanism_step(emission: u64) -> pallet_subtensor::MechanismStepResult;
+	}
+}
+
 impl_runtime_apis! {
 	impl sp_api::Core<Block> for Runtime {
 		fn version() -> RuntimeVersion {
@@ -506,6 +572,36 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl crate::SubtensorApi<Block> for Runtime {
+		fn get_metagraph(start: Option<u32>, end: Option<u32>) -> pallet_subtensor::Metagraph<AccountId> {
+			SubtensorModule::get_metagraph(start, end)
+		}
+
+		fn get_neuron_by_uid(uid: u32) -> Option<pallet_subtensor::NeuronMetadata<AccountId>> {
+			SubtensorModule::get_neuron_by_uid(uid)
+		}
+
+		fn get_neuron_by_hotkey(hotkey: AccountId) -> Option<pallet_subtensor::NeuronMetadata<AccountId>> {
+			SubtensorModule::get_neuron_by_hotkey(&hotkey)
+		}
+
+		fn get_neurons_by_coldkey(coldkey: AccountId) -> Vec<pallet_subtensor::NeuronMetadata<AccountId>> {
+			SubtensorModule::get_neurons_by_coldkey(&coldkey)
+		}
+
+		fn simulate_mechanism_step(emission: u64) -> pallet_subtensor::MechanismStepResult {
+			SubtensorModule::compute_mechanism_step(emission)
+		}
+	}
+
+	#[cfg(feature = "try-runtime")]
+	impl frame_try_runtime::TryRuntime<Block> for Runtime {
+		fn on_runtime_upgrade() -> Result<(Weight, Weight), sp_runtime::RuntimeString> {
+			let weight = Executive::try_runtime_upgrade()?;
+			Ok((weight, BlockWeights::get().max_block))
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn dispatch_benchmark(
@@ -0,0 +1,19 @@
+#!/bin/bash
+# Regenerates pallets/subtensor/src/default_weights.rs from the pallet benchmarks.
+# Run it from the repository root on the reference validator hardware, the weights
+# are only as good as the machine they were measured on.
+set -e
+
+cargo build --release --features runtime-benchmarks
+
+./target/release/node-subtensor benchmark \
+	--chain=dev \
+	--steps=50 \
+	--repeat=20 \
+	--pallet=pallet_subtensor \
+	--extrinsic='*' \
+	--execution=wasm \
+	--wasm-execution=compiled \
+	--heap-pages=4096 \
+	--template=./.maintain/frame-weight-template.hbs \
+	--output=./pallets/subtensor/src/default_weights.rs